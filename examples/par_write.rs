@@ -0,0 +1,76 @@
+//! Write a single file from all processes in `MPI_COMM_WORLD`
+//!
+//! Run with e.g.
+//! ```text
+//! cargo build --example par_write --features mpi
+//! mpirun -n 4 target/debug/examples/par_write par.nc
+//! ```
+use mpi::traits::*;
+use netcdf::variable::ParallelAccess;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+struct Opt {
+    path: std::path::PathBuf,
+}
+
+const NX_PER_RANK: usize = 10;
+const NY: usize = 5;
+
+fn main() {
+    let opt = Opt::from_args();
+    let universe = mpi::initialize().expect("Could not initialize MPI");
+    let world = universe.world();
+
+    if let Err(e) = run(&opt.path, &world) {
+        eprintln!("rank {}: {}", world.rank(), e);
+        // Stops all ranks, with the exit code of the failing one
+        world.abort(1);
+    }
+}
+
+fn run(
+    path: &std::path::Path,
+    world: &mpi::topology::SimpleCommunicator,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rank = world.rank() as usize;
+    let size = world.size() as usize;
+
+    {
+        let mut file = netcdf::create_par(path, world)?;
+        file.add_dimension("x", NX_PER_RANK * size)?;
+        file.add_dimension("y", NY)?;
+        let mut var = file.add_variable::<i32>("data", &["x", "y"])?;
+        var.par_access(ParallelAccess::Collective)?;
+
+        // Each rank owns a contiguous block of rows
+        let data = (0..NX_PER_RANK * NY)
+            .map(|i| (rank * NX_PER_RANK * NY + i) as i32)
+            .collect::<Vec<_>>();
+        var.put_values(
+            &data,
+            Some(&[rank * NX_PER_RANK, 0]),
+            Some(&[NX_PER_RANK, NY]),
+        )?;
+    }
+
+    world.barrier();
+
+    let file = netcdf::open_par(path, world)?;
+    let var = file.variable("data").expect("Could not find variable");
+    var.par_access(ParallelAccess::Independent)?;
+
+    let neighbour = (rank + 1) % size;
+    let mut data = vec![0; NX_PER_RANK * NY];
+    var.values_to(
+        &mut data,
+        Some(&[neighbour * NX_PER_RANK, 0]),
+        Some(&[NX_PER_RANK, NY]),
+    )?;
+    for (i, x) in data.into_iter().enumerate() {
+        assert_eq!(x, (neighbour * NX_PER_RANK * NY + i) as i32);
+    }
+    println!("rank {}: read back block of rank {}", rank, neighbour);
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! Derive macro for `netcdf::NetcdfDataset`, reading and writing
+//! whole datasets as structs. See the `netcdf::dataset` module for
+//! the supported field attributes
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitInt, LitStr};
+
+/// What a field is read from and written to
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Variable,
+    Attribute,
+    Dimension,
+    Skip,
+}
+
+/// Options of a field from its `#[netcdf(...)]` attributes
+struct Field {
+    ident: syn::Ident,
+    kind: Kind,
+    name: String,
+    group: Option<String>,
+    unlimited: bool,
+    dimensions: Vec<String>,
+    attributes: Vec<(String, String)>,
+    compression: Option<i32>,
+}
+
+impl Field {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let ident = field
+            .ident
+            .clone()
+            .ok_or_else(|| syn::Error::new(Span::call_site(), "expected named fields"))?;
+        let mut parsed = Self {
+            name: ident.to_string(),
+            ident,
+            kind: Kind::Variable,
+            group: None,
+            unlimited: false,
+            dimensions: Vec::new(),
+            attributes: Vec::new(),
+            compression: None,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("netcdf")) {
+            attr.parse_nested_meta(|meta| {
+                let string = |meta: &syn::meta::ParseNestedMeta| -> syn::Result<String> {
+                    Ok(meta.value()?.parse::<LitStr>()?.value())
+                };
+                let path = &meta.path;
+                if path.is_ident("attribute") {
+                    parsed.kind = Kind::Attribute;
+                } else if path.is_ident("dimension") {
+                    parsed.kind = Kind::Dimension;
+                } else if path.is_ident("skip") {
+                    parsed.kind = Kind::Skip;
+                } else if path.is_ident("unlimited") {
+                    parsed.unlimited = true;
+                } else if path.is_ident("name") {
+                    parsed.name = string(&meta)?;
+                } else if path.is_ident("group") {
+                    parsed.group = Some(string(&meta)?);
+                } else if path.is_ident("dimensions") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let names = content
+                        .parse_terminated(<LitStr as syn::parse::Parse>::parse, syn::Token![,])?;
+                    parsed.dimensions.extend(names.iter().map(LitStr::value));
+                } else if ["units", "long_name", "standard_name"]
+                    .iter()
+                    .any(|x| path.is_ident(x))
+                {
+                    let key = path.get_ident().unwrap().to_string();
+                    parsed.attributes.push((key, string(&meta)?));
+                } else if path.is_ident("compression") {
+                    let level = meta.value()?.parse::<LitInt>()?;
+                    parsed.compression = Some(level.base10_parse()?);
+                } else {
+                    return Err(meta.error("unknown netcdf attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Derives `netcdf::NetcdfDataset`, see the `netcdf::dataset` module
+#[proc_macro_derive(NetcdfDataset, attributes(netcdf))]
+pub fn derive_netcdf_dataset(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "NetcdfDataset requires named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "NetcdfDataset can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut group = String::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("netcdf")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("group") {
+                group = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("only `group` can be given for the struct"))
+            }
+        })?;
+    }
+
+    let fields = fields
+        .iter()
+        .map(Field::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let reads = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let name = &f.name;
+        let group = full_group(&group, f);
+        match f.kind {
+            Kind::Variable => quote! {
+                #ident: ::netcdf::dataset::read_variable(file, #group, #name)?
+            },
+            Kind::Attribute => quote! {
+                #ident: ::netcdf::dataset::read_attribute(file, #group, #name)?
+            },
+            Kind::Dimension => quote! {
+                #ident: ::netcdf::dataset::read_dimension(file, #group, #name)?
+            },
+            Kind::Skip => quote! {
+                #ident: ::std::default::Default::default()
+            },
+        }
+    });
+
+    // Dimensions before attributes before variables,
+    // as variables can only be defined over existing dimensions
+    let writes = [Kind::Dimension, Kind::Attribute, Kind::Variable]
+        .iter()
+        .flat_map(|&kind| fields.iter().filter(move |f| f.kind == kind))
+        .map(|f| {
+            let ident = &f.ident;
+            let name = &f.name;
+            let group = full_group(&group, f);
+            match f.kind {
+                Kind::Dimension => {
+                    let unlimited = f.unlimited;
+                    quote! {
+                        ::netcdf::dataset::write_dimension(
+                            file, #group, #name, self.#ident, #unlimited
+                        )?;
+                    }
+                }
+                Kind::Attribute => quote! {
+                    ::netcdf::dataset::write_attribute(file, #group, #name, &self.#ident)?;
+                },
+                Kind::Variable => {
+                    let dims = &f.dimensions;
+                    let keys = f.attributes.iter().map(|a| &a.0);
+                    let values = f.attributes.iter().map(|a| &a.1);
+                    let compression = match f.compression {
+                        Some(level) => quote!(Some(#level)),
+                        None => quote!(None),
+                    };
+                    quote! {
+                        ::netcdf::dataset::write_variable(
+                            file,
+                            #group,
+                            #name,
+                            &[#(#dims),*],
+                            &self.#ident,
+                            &[#((#keys, #values)),*],
+                            #compression,
+                        )?;
+                    }
+                }
+                Kind::Skip => quote!(),
+            }
+        });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::netcdf::NetcdfDataset for #ident #ty_generics #where_clause {
+            fn from_file(file: &::netcdf::File) -> ::netcdf::error::Result<Self> {
+                Ok(Self {
+                    #(#reads),*
+                })
+            }
+            fn write_to(&self, file: &mut ::netcdf::MutableFile) -> ::netcdf::error::Result<()> {
+                #(#writes)*
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Group of a field below the group of the struct
+fn full_group(group: &str, field: &Field) -> String {
+    match &field.group {
+        Some(g) if !group.is_empty() => format!("{}/{}", group, g),
+        Some(g) => g.clone(),
+        None => group.into(),
+    }
+}
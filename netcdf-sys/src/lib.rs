@@ -6,6 +6,11 @@ mod netcdf_const;
 pub use netcdf_bindings::*;
 pub use netcdf_const::*;
 
+#[cfg(feature = "mpi")]
+mod netcdf_par;
+#[cfg(feature = "mpi")]
+pub use netcdf_par::*;
+
 #[cfg(test)]
 mod tests {
     use super::*;
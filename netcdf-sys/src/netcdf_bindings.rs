@@ -313,6 +313,13 @@ extern "C" {
         options_maskp: *mut c_int,
         pixels_per_blockp: *mut c_int,
     ) -> c_int;
+    pub fn nc_def_var_filter(
+        ncid: c_int,
+        varid: c_int,
+        id: c_uint,
+        nparams: usize,
+        parms: *const c_uint,
+    ) -> c_int;
     pub fn nc_def_var_fletcher32(ncid: c_int, varid: c_int, fletcher32: c_int) -> c_int;
     pub fn nc_inq_var_fletcher32(ncid: c_int, varid: c_int, fletcher32p: *mut c_int) -> c_int;
     pub fn nc_def_var_chunking(
@@ -25,6 +25,13 @@ pub const NC_FILL_INT: ::std::os::raw::c_int = -2147483647;
 pub const NC_FILL_UBYTE: ::std::os::raw::c_int = 255;
 pub const NC_FILL_USHORT: ::std::os::raw::c_int = 65535;
 pub const NC_FILL_UINT: ::std::os::raw::c_uint = 4294967295;
+pub const NC_FILL_BYTE: ::std::os::raw::c_schar = -127;
+pub const NC_FILL_CHAR: ::std::os::raw::c_char = 0;
+pub const NC_FILL_SHORT: ::std::os::raw::c_short = -32767;
+pub const NC_FILL_FLOAT: ::std::os::raw::c_float = 9.969_21e36;
+pub const NC_FILL_DOUBLE: ::std::os::raw::c_double = 9.969_209_968_386_869e36;
+pub const NC_FILL_INT64: ::std::os::raw::c_longlong = -9223372036854775806;
+pub const NC_FILL_UINT64: ::std::os::raw::c_ulonglong = 18446744073709551614;
 pub const NC_MAX_BYTE: ::std::os::raw::c_int = 127;
 pub const NC_MIN_BYTE: ::std::os::raw::c_int = -128;
 pub const NC_MAX_CHAR: ::std::os::raw::c_int = 255;
@@ -42,6 +49,9 @@ pub const X_INT64_MAX: ::std::os::raw::c_ulonglong = 9223372036854775807;
 pub const X_INT64_MIN: ::std::os::raw::c_longlong = -9223372036854775808;
 pub const X_UINT64_MAX: ::std::os::raw::c_int = -1;
 pub const _FillValue: &[u8; 11_usize] = b"_FillValue\x00";
+pub const NCPROPS: &[u8; 14_usize] = b"_NCProperties\x00";
+pub const ISNETCDF4ATT: &[u8; 11_usize] = b"_IsNetcdf4\x00";
+pub const SUPERBLOCKATT: &[u8; 19_usize] = b"_SuperblockVersion\x00";
 pub const NC_FILL: ::std::os::raw::c_int = 0;
 pub const NC_NOFILL: ::std::os::raw::c_int = 256;
 pub const NC_NOWRITE: ::std::os::raw::c_int = 0;
@@ -52,6 +62,8 @@ pub const NC_DISKLESS: ::std::os::raw::c_int = 8;
 pub const NC_MMAP: ::std::os::raw::c_int = 16;
 pub const NC_CLASSIC_MODEL: ::std::os::raw::c_int = 256;
 pub const NC_64BIT_OFFSET: ::std::os::raw::c_int = 512;
+pub const NC_64BIT_DATA: ::std::os::raw::c_int = 32;
+pub const NC_CDF5: ::std::os::raw::c_int = 32;
 pub const NC_LOCK: ::std::os::raw::c_int = 1024;
 pub const NC_SHARE: ::std::os::raw::c_int = 2048;
 pub const NC_NETCDF4: ::std::os::raw::c_int = 4096;
@@ -62,6 +74,8 @@ pub const NC_FORMAT_CLASSIC: ::std::os::raw::c_int = 1;
 pub const NC_FORMAT_64BIT: ::std::os::raw::c_int = 2;
 pub const NC_FORMAT_NETCDF4: ::std::os::raw::c_int = 3;
 pub const NC_FORMAT_NETCDF4_CLASSIC: ::std::os::raw::c_int = 4;
+pub const NC_FORMAT_64BIT_DATA: ::std::os::raw::c_int = 5;
+pub const NC_FORMAT_CDF5: ::std::os::raw::c_int = 5;
 pub const NC_FORMAT_NC3: ::std::os::raw::c_int = 1;
 pub const NC_FORMAT_NC_HDF5: ::std::os::raw::c_int = 2;
 pub const NC_FORMAT_NC_HDF4: ::std::os::raw::c_int = 3;
@@ -69,6 +83,17 @@ pub const NC_FORMAT_PNETCDF: ::std::os::raw::c_int = 4;
 pub const NC_FORMAT_DAP2: ::std::os::raw::c_int = 5;
 pub const NC_FORMAT_DAP4: ::std::os::raw::c_int = 6;
 pub const NC_FORMAT_UNDEFINED: ::std::os::raw::c_int = 0;
+pub const NC_FORMATX_NC3: ::std::os::raw::c_int = 1;
+pub const NC_FORMATX_NC_HDF5: ::std::os::raw::c_int = 2;
+pub const NC_FORMATX_NC4: ::std::os::raw::c_int = 2;
+pub const NC_FORMATX_NC_HDF4: ::std::os::raw::c_int = 3;
+pub const NC_FORMATX_PNETCDF: ::std::os::raw::c_int = 4;
+pub const NC_FORMATX_DAP2: ::std::os::raw::c_int = 5;
+pub const NC_FORMATX_DAP4: ::std::os::raw::c_int = 6;
+pub const NC_FORMATX_UDF0: ::std::os::raw::c_int = 8;
+pub const NC_FORMATX_UDF1: ::std::os::raw::c_int = 9;
+pub const NC_FORMATX_NCZARR: ::std::os::raw::c_int = 10;
+pub const NC_FORMATX_UNDEFINED: ::std::os::raw::c_int = 0;
 pub const NC_SIZEHINT_DEFAULT: ::std::os::raw::c_int = 0;
 pub const NC_UNLIMITED: ::std::os::raw::c_int = 0;
 pub const NC_GLOBAL: ::std::os::raw::c_int = -1;
@@ -83,6 +108,7 @@ pub const NC_ENDIAN_LITTLE: ::std::os::raw::c_int = 1;
 pub const NC_ENDIAN_BIG: ::std::os::raw::c_int = 2;
 pub const NC_CHUNKED: ::std::os::raw::c_int = 0;
 pub const NC_CONTIGUOUS: ::std::os::raw::c_int = 1;
+pub const NC_COMPACT: ::std::os::raw::c_int = 2;
 pub const NC_NOCHECKSUM: ::std::os::raw::c_int = 0;
 pub const NC_FLETCHER32: ::std::os::raw::c_int = 1;
 pub const NC_NOSHUFFLE: ::std::os::raw::c_int = 0;
@@ -139,6 +165,8 @@ pub const NC_EACCESS: ::std::os::raw::c_int = -77;
 pub const NC_EAUTH: ::std::os::raw::c_int = -78;
 pub const NC_ENOTFOUND: ::std::os::raw::c_int = -90;
 pub const NC_ECANTREMOVE: ::std::os::raw::c_int = -91;
+pub const NC_EINTERNAL: ::std::os::raw::c_int = -92;
+pub const NC_EPNETCDF: ::std::os::raw::c_int = -93;
 pub const NC4_FIRST_ERROR: ::std::os::raw::c_int = -100;
 pub const NC_EHDFERR: ::std::os::raw::c_int = -101;
 pub const NC_ECANTREAD: ::std::os::raw::c_int = -102;
@@ -171,7 +199,18 @@ pub const NC_ENOTBUILT: ::std::os::raw::c_int = -128;
 pub const NC_EDISKLESS: ::std::os::raw::c_int = -129;
 pub const NC_ECANTEXTEND: ::std::os::raw::c_int = -130;
 pub const NC_EMPI: ::std::os::raw::c_int = -131;
-pub const NC4_LAST_ERROR: ::std::os::raw::c_int = -131;
+pub const NC_EFILTER: ::std::os::raw::c_int = -132;
+pub const NC_ERCFILE: ::std::os::raw::c_int = -133;
+pub const NC_ENULLPAD: ::std::os::raw::c_int = -134;
+pub const NC_EINMEMORY: ::std::os::raw::c_int = -135;
+pub const NC_ENOFILTER: ::std::os::raw::c_int = -136;
+pub const NC_ENCZARR: ::std::os::raw::c_int = -137;
+pub const NC_ES3: ::std::os::raw::c_int = -138;
+pub const NC_EEMPTY: ::std::os::raw::c_int = -139;
+pub const NC_EOBJECT: ::std::os::raw::c_int = -140;
+pub const NC_ENOOBJECT: ::std::os::raw::c_int = -141;
+pub const NC_EPLUGIN: ::std::os::raw::c_int = -142;
+pub const NC4_LAST_ERROR: ::std::os::raw::c_int = -142;
 pub const DIM_WITHOUT_VARIABLE: &[u8; 54_usize] =
     b"This is a netCDF dimension but not a netCDF variable.\x00";
 pub const NC_HAVE_NEW_CHUNKING_API: ::std::os::raw::c_int = 1;
@@ -0,0 +1,27 @@
+//! Parallel I/O functions, only available when libnetcdf
+//! is built with MPI support
+
+use mpi_sys::{MPI_Comm, MPI_Info};
+use std::os::raw::{c_char, c_int};
+
+pub const NC_INDEPENDENT: c_int = 0;
+pub const NC_COLLECTIVE: c_int = 1;
+
+#[link(name = "netcdf")]
+extern "C" {
+    pub fn nc_create_par(
+        path: *const c_char,
+        cmode: c_int,
+        comm: MPI_Comm,
+        info: MPI_Info,
+        ncidp: *mut c_int,
+    ) -> c_int;
+    pub fn nc_open_par(
+        path: *const c_char,
+        mode: c_int,
+        comm: MPI_Comm,
+        info: MPI_Info,
+        ncidp: *mut c_int,
+    ) -> c_int;
+    pub fn nc_var_par_access(ncid: c_int, varid: c_int, par_access: c_int) -> c_int;
+}
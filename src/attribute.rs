@@ -2,9 +2,10 @@
 
 #![allow(clippy::similar_names)]
 use super::error;
+use super::variable::{CStrings, NcStrings};
 use super::LOCK;
 use netcdf_sys::*;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::marker::PhantomData;
 
 /// Extra properties of a variable or a group can be represented
@@ -26,7 +27,7 @@ pub struct Attribute<'a> {
 
 impl<'a> std::fmt::Debug for Attribute<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "name: {}", self.name())?;
+        write!(f, "name: {}", String::from_utf8_lossy(self.name_bytes()))?;
         write!(f, "ncid: {}", self.ncid)?;
         write!(f, "varid: {}", self.varid)
     }
@@ -35,20 +36,32 @@ impl<'a> std::fmt::Debug for Attribute<'a> {
 impl<'a> Attribute<'a> {
     /// Get the name of the attribute
     ///
-    /// # Errors
-    /// attribute could have a name containing an invalid utf8-sequence
+    /// # Panics
+    ///
+    /// The name contains an invalid utf8-sequence, see [`Self::try_name`]
     pub fn name(&self) -> &str {
+        std::str::from_utf8(self.name_bytes()).expect("Attribute name contains invalid sequence")
+    }
+    /// Get the name of the attribute
+    ///
+    /// # Errors
+    ///
+    /// The name contains an invalid utf8-sequence
+    pub fn try_name(&self) -> error::Result<String> {
+        Ok(String::from_utf8(self.name_bytes().to_vec())?)
+    }
+    /// Get the name of the attribute as stored in the file
+    pub fn name_bytes(&self) -> &[u8] {
         let zeropos = self
             .name
             .iter()
             .position(|&x| x == 0)
             .unwrap_or_else(|| self.name.len());
-        std::str::from_utf8(&self.name[..zeropos])
-            .expect("Attribute name contains invalid sequence")
+        &self.name[..zeropos]
     }
     /// Number of elements in this attribute
     fn num_elems(&self) -> error::Result<usize> {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         let mut nelems = 0;
         unsafe {
             error::checked(nc_inq_attlen(
@@ -61,7 +74,7 @@ impl<'a> Attribute<'a> {
         Ok(nelems as _)
     }
     /// Type of this attribute
-    fn typ(&self) -> error::Result<nc_type> {
+    pub(crate) fn typ(&self) -> error::Result<nc_type> {
         let mut atttype = 0;
         unsafe {
             error::checked(nc_inq_atttype(
@@ -73,13 +86,47 @@ impl<'a> Attribute<'a> {
         }
         Ok(atttype)
     }
+    /// Get the value of the attribute converted to `T`.
+    /// Numeric values are only widened when this is lossless,
+    /// e.g. a `Short` can be read as an `i32` or `f64`
+    ///
+    /// # Errors
+    ///
+    /// Not convertible to `T` (`TypeMismatch`), unsupported type or netcdf error
+    pub fn value_as<T>(&self) -> error::Result<T>
+    where
+        T: TryFrom<AttrValue, Error = error::Error>,
+    {
+        self.value()?.try_into()
+    }
     /// Get the value of the attribute
     ///
     /// # Errors
     ///
     /// Unsupported type or netcdf error
-    #[allow(clippy::too_many_lines)]
     pub fn value(&self) -> error::Result<AttrValue> {
+        self.read_value()
+            .map_err(|e| e.with_context(|| self.context()))
+    }
+    /// Context of errors when reading the attribute
+    fn context(&self) -> error::Context {
+        let mut variable = None;
+        if self.varid != NC_GLOBAL {
+            let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+            let e = unsafe { nc_inq_varname(self.ncid, self.varid, name.as_mut_ptr() as *mut _) };
+            if e == NC_NOERR {
+                let zeropos = name.iter().position(|&x| x == 0).unwrap_or(name.len());
+                variable = Some(String::from_utf8_lossy(&name[..zeropos]).into_owned());
+            }
+        }
+        error::Context {
+            variable,
+            attribute: Some(String::from_utf8_lossy(self.name_bytes()).into_owned()),
+            ..error::Context::at(self.ncid)
+        }
+    }
+    #[allow(clippy::too_many_lines)]
+    fn read_value(&self) -> error::Result<AttrValue> {
         let attlen = self.num_elems()?;
         let typ = self.typ()?;
 
@@ -365,6 +412,18 @@ impl<'a> Attribute<'a> {
                     &buf[..pos],
                 ))))
             }
+            NC_STRING => {
+                let mut strings = NcStrings::with_len(attlen);
+                unsafe {
+                    error::checked(nc_get_att_string(
+                        self.ncid,
+                        self.varid,
+                        self.name.as_ptr() as *const _,
+                        strings.as_mut_ptr(),
+                    ))?;
+                }
+                Ok(AttrValue::Strs(strings.to_strings()))
+            }
             x => Err(error::Error::TypeUnknown(x)),
         }
     }
@@ -434,6 +493,7 @@ impl<'a> Iterator for AttributeIterator<'a> {
 /// returned from the file
 #[allow(missing_docs)]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttrValue {
     Uchar(u8),
     Uchars(Vec<u8>),
@@ -455,7 +515,11 @@ pub enum AttrValue {
     Floats(Vec<f32>),
     Double(f64),
     Doubles(Vec<f64>),
+    /// Text, stored as `NC_CHAR`
     Str(String),
+    /// Strings, stored as `NC_STRING`. This is also the
+    /// variant of a single `NC_STRING`
+    Strs(Vec<String>),
 }
 
 impl<'a> Attribute<'a> {
@@ -588,6 +652,17 @@ impl<'a> Attribute<'a> {
                     x.len(),
                     x.as_ptr() as *const _,
                 ),
+                AttrValue::Strs(ref x) => {
+                    let strings = CStrings::new(x)?;
+                    let mut ptrs = strings.as_ptrs();
+                    nc_put_att_string(
+                        ncid,
+                        varid,
+                        cname.as_ptr() as *const _,
+                        ptrs.len(),
+                        ptrs.as_mut_ptr(),
+                    )
+                }
             }
         })?;
 
@@ -599,6 +674,66 @@ impl<'a> Attribute<'a> {
         })
     }
 
+    /// Copies the attribute to a group or variable, possibly in another file
+    pub(crate) fn copy_to<'b>(
+        &self,
+        ncid: nc_type,
+        varid: nc_type,
+    ) -> error::Result<Attribute<'b>> {
+        unsafe {
+            error::checked(nc_copy_att(
+                self.ncid,
+                self.varid,
+                self.name.as_ptr() as *const _,
+                ncid,
+                varid,
+            ))?;
+        }
+        Ok(Attribute {
+            name: self.name,
+            ncid,
+            varid,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Copies the attribute to a file or group, possibly in another file.
+    /// Use [`crate::VariableMut::copy_attribute`] to copy it to a variable
+    pub fn copy_attribute_to<'b, T>(&self, target: &'b mut T) -> error::Result<Attribute<'b>>
+    where
+        T: crate::group::CopyTarget,
+    {
+        target.copy_attribute(self)
+    }
+
+    /// Puts strings as `NC_STRING`, regardless of the number of strings
+    pub(crate) fn put_strings<S: AsRef<str>>(
+        ncid: nc_type,
+        varid: nc_type,
+        name: &str,
+        values: &[S],
+    ) -> error::Result<Self> {
+        let cname = super::utils::short_name_to_bytes(name)?;
+        let strings = CStrings::new(values)?;
+        let mut ptrs = strings.as_ptrs();
+        unsafe {
+            error::checked(nc_put_att_string(
+                ncid,
+                varid,
+                cname.as_ptr() as *const _,
+                ptrs.len(),
+                ptrs.as_mut_ptr(),
+            ))?;
+        }
+
+        Ok(Self {
+            name: cname,
+            ncid,
+            varid,
+            _marker: PhantomData,
+        })
+    }
+
     pub(crate) fn find_from_name(
         ncid: nc_type,
         varid: Option<nc_type>,
@@ -746,9 +881,136 @@ impl From<String> for AttrValue {
         Self::Str(x)
     }
 }
+impl From<Vec<String>> for AttrValue {
+    fn from(x: Vec<String>) -> Self {
+        Self::Strs(x)
+    }
+}
+impl From<Vec<&str>> for AttrValue {
+    fn from(x: Vec<&str>) -> Self {
+        Self::Strs(x.into_iter().map(String::from).collect())
+    }
+}
+
+macro_rules! impl_try_from_attrvalue {
+    ($t: ty; $($scalar: ident, $vec: ident);*) => {
+        impl TryFrom<AttrValue> for $t {
+            type Error = error::Error;
+            fn try_from(x: AttrValue) -> error::Result<Self> {
+                match x {
+                    $(AttrValue::$scalar(x) => Ok(x.into()),)*
+                    _ => Err(error::Error::TypeMismatch),
+                }
+            }
+        }
+        impl TryFrom<AttrValue> for Vec<$t> {
+            type Error = error::Error;
+            fn try_from(x: AttrValue) -> error::Result<Self> {
+                match x {
+                    $(
+                    AttrValue::$scalar(x) => Ok(vec![x.into()]),
+                    AttrValue::$vec(x) => Ok(x.into_iter().map(Into::into).collect()),
+                    )*
+                    _ => Err(error::Error::TypeMismatch),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_attrvalue!(u8; Uchar, Uchars);
+impl_try_from_attrvalue!(i8; Schar, Schars);
+impl_try_from_attrvalue!(u16; Uchar, Uchars; Ushort, Ushorts);
+impl_try_from_attrvalue!(i16; Uchar, Uchars; Schar, Schars; Short, Shorts);
+impl_try_from_attrvalue!(u32; Uchar, Uchars; Ushort, Ushorts; Uint, Uints);
+impl_try_from_attrvalue!(i32; Uchar, Uchars; Schar, Schars; Ushort, Ushorts; Short, Shorts; Int, Ints);
+impl_try_from_attrvalue!(u64; Uchar, Uchars; Ushort, Ushorts; Uint, Uints; Ulonglong, Ulonglongs);
+impl_try_from_attrvalue!(i64; Uchar, Uchars; Schar, Schars; Ushort, Ushorts; Short, Shorts; Uint, Uints; Int, Ints; Longlong, Longlongs);
+impl_try_from_attrvalue!(f32; Uchar, Uchars; Schar, Schars; Ushort, Ushorts; Short, Shorts; Float, Floats);
+impl_try_from_attrvalue!(f64; Uchar, Uchars; Schar, Schars; Ushort, Ushorts; Short, Shorts; Uint, Uints; Int, Ints; Float, Floats; Double, Doubles);
+
+/// Text, or a single `NC_STRING`
+impl TryFrom<AttrValue> for String {
+    type Error = error::Error;
+    fn try_from(x: AttrValue) -> error::Result<Self> {
+        match x {
+            AttrValue::Str(x) => Ok(x),
+            AttrValue::Strs(mut x) if x.len() == 1 => Ok(x.remove(0)),
+            _ => Err(error::Error::TypeMismatch),
+        }
+    }
+}
+impl TryFrom<AttrValue> for Vec<String> {
+    type Error = error::Error;
+    fn try_from(x: AttrValue) -> error::Result<Self> {
+        match x {
+            AttrValue::Str(x) => Ok(vec![x]),
+            AttrValue::Strs(x) => Ok(x),
+            _ => Err(error::Error::TypeMismatch),
+        }
+    }
+}
+
+fn fmt_list<T: std::fmt::Display>(f: &mut std::fmt::Formatter<'_>, x: &[T]) -> std::fmt::Result {
+    for (i, x) in x.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", x)?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Uchar(x) => write!(f, "{}", x),
+            Self::Uchars(x) => fmt_list(f, x),
+            Self::Schar(x) => write!(f, "{}", x),
+            Self::Schars(x) => fmt_list(f, x),
+            Self::Ushort(x) => write!(f, "{}", x),
+            Self::Ushorts(x) => fmt_list(f, x),
+            Self::Short(x) => write!(f, "{}", x),
+            Self::Shorts(x) => fmt_list(f, x),
+            Self::Uint(x) => write!(f, "{}", x),
+            Self::Uints(x) => fmt_list(f, x),
+            Self::Int(x) => write!(f, "{}", x),
+            Self::Ints(x) => fmt_list(f, x),
+            Self::Ulonglong(x) => write!(f, "{}", x),
+            Self::Ulonglongs(x) => fmt_list(f, x),
+            Self::Longlong(x) => write!(f, "{}", x),
+            Self::Longlongs(x) => fmt_list(f, x),
+            Self::Float(x) => write!(f, "{}", x),
+            Self::Floats(x) => fmt_list(f, x),
+            Self::Double(x) => write!(f, "{}", x),
+            Self::Doubles(x) => fmt_list(f, x),
+            Self::Str(x) => write!(f, "{}", x),
+            Self::Strs(x) => fmt_list(f, x),
+        }
+    }
+}
 
 #[test]
 fn conversion() {
     let x = 1.0f32;
     let _b: AttrValue = x.into();
 }
+
+#[test]
+fn try_from_attrvalue() {
+    assert_eq!(f64::try_from(AttrValue::Short(-3)).unwrap(), -3.0);
+    assert_eq!(i64::try_from(AttrValue::Uint(7)).unwrap(), 7);
+    assert!(i32::try_from(AttrValue::Uint(7)).is_err());
+    assert!(f32::try_from(AttrValue::Int(1)).is_err());
+    assert_eq!(
+        Vec::<f64>::try_from(AttrValue::Floats(vec![1.0, 2.5])).unwrap(),
+        vec![1.0, 2.5]
+    );
+    assert_eq!(Vec::<u16>::try_from(AttrValue::Uchar(2)).unwrap(), vec![2]);
+    assert!(String::try_from(AttrValue::Strs(vec![])).is_err());
+    assert_eq!(
+        String::try_from(AttrValue::Strs(vec!["a".into()])).unwrap(),
+        "a"
+    );
+    assert_eq!(AttrValue::Shorts(vec![1, 2]).to_string(), "1, 2");
+}
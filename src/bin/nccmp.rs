@@ -0,0 +1,73 @@
+//! Compares two netCDF files, like `nccmp`. Prints one line per
+//! difference (or the differences as JSON), and exits with 1 if
+//! the files differ
+use netcdf::compare::{compare, Options};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Compares two netCDF files")]
+struct Opt {
+    /// Compare only the metadata, not the values of variables
+    #[structopt(short = "m", long = "metadata")]
+    metadata: bool,
+    /// Absolute tolerance for values
+    #[structopt(short = "t", long = "tolerance", default_value = "0")]
+    tolerance: f64,
+    /// Relative tolerance for values, in percent
+    #[structopt(short = "T", long = "percent", default_value = "0")]
+    percent: f64,
+    /// Compare only these variables
+    #[structopt(short = "v", long = "variables", use_delimiter = true)]
+    variables: Vec<String>,
+    /// Ignore these attributes
+    #[structopt(short = "A", long = "exclude-attributes", use_delimiter = true)]
+    exclude_attributes: Vec<String>,
+    /// Show at most this many differing values per variable
+    #[structopt(short = "n", long = "max")]
+    max: Option<usize>,
+    /// Print the differences as a JSON array
+    #[structopt(long = "json")]
+    json: bool,
+    first: std::path::PathBuf,
+    second: std::path::PathBuf,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    match run(&opt) {
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+        Ok(true) => {
+            std::process::exit(0);
+        }
+        Ok(false) => {
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Whether the files are equal
+fn run(opt: &Opt) -> Result<bool, Box<dyn std::error::Error>> {
+    let first = netcdf::open(&opt.first)?;
+    let second = netcdf::open(&opt.second)?;
+    let options = Options {
+        data: !opt.metadata,
+        absolute_tolerance: opt.tolerance,
+        relative_tolerance: opt.percent / 100.0,
+        variables: opt.variables.clone(),
+        exclude_attributes: opt.exclude_attributes.clone(),
+        max_value_differences: opt.max,
+    };
+    let differences = compare(&first, &second, &options)?;
+    if opt.json {
+        println!("{}", serde_json::to_string_pretty(&differences)?);
+    } else {
+        for difference in &differences {
+            println!("{}", difference);
+        }
+    }
+    Ok(differences.is_empty())
+}
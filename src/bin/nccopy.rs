@@ -0,0 +1,76 @@
+//! Copies a netCDF file, with the options of `nccopy`
+//!
+//! ```text
+//! nccopy -k cdf5 input.nc output.nc
+//! nccopy -d 4 -s -c time/1,lat/180 input.nc output.nc
+//! ```
+use netcdf::convert::{Format, Options};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Copies a netCDF file, optionally changing format, chunking and compression")]
+struct Opt {
+    /// Format of the output (classic, 64bit-offset, cdf5, netcdf4, netcdf4-classic)
+    #[structopt(short = "k", long = "kind", default_value = "netcdf4", parse(try_from_str = parse_format))]
+    format: Format,
+    /// Deflate level (1-9)
+    #[structopt(short = "d", long = "deflate")]
+    deflate: Option<i32>,
+    /// Enable shuffling before compression
+    #[structopt(short = "s", long = "shuffle")]
+    shuffle: bool,
+    /// Chunk lengths along dimensions, as dim/len,dim/len,...
+    #[structopt(short = "c", long = "chunking", use_delimiter = true, parse(try_from_str = parse_chunk))]
+    chunking: Vec<(String, usize)>,
+    /// Maximum number of values to hold in memory per variable
+    #[structopt(short = "m", long = "buffer-len")]
+    buffer_len: Option<usize>,
+    input: std::path::PathBuf,
+    output: std::path::PathBuf,
+}
+
+fn parse_format(s: &str) -> Result<Format, String> {
+    match s {
+        "classic" | "1" => Ok(Format::Classic),
+        "64bit-offset" | "2" => Ok(Format::Offset64),
+        "cdf5" | "5" => Ok(Format::Cdf5),
+        "netcdf4" | "3" => Ok(Format::Netcdf4),
+        "netcdf4-classic" | "4" => Ok(Format::Netcdf4Classic),
+        x => Err(format!("unknown format {}", x)),
+    }
+}
+
+fn parse_chunk(s: &str) -> Result<(String, usize), String> {
+    let mut parts = s.splitn(2, '/');
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(len)) => len
+            .parse()
+            .map(|len| (name.to_string(), len))
+            .map_err(|e| format!("{}: {}", s, e)),
+        _ => Err(format!("{} is not of the form dim/len", s)),
+    }
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    if let Err(e) = run(&opt) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(opt: &Opt) -> Result<(), Box<dyn std::error::Error>> {
+    let defaults = Options::default();
+    let options = Options {
+        format: opt.format,
+        chunking: opt.chunking.iter().cloned().collect(),
+        deflate: opt.deflate,
+        shuffle: opt.shuffle,
+        buffer_len: opt.buffer_len.unwrap_or(defaults.buffer_len),
+    };
+
+    let file = netcdf::open(&opt.input)?;
+    netcdf::convert::convert(&file, &opt.output, &options)?;
+    Ok(())
+}
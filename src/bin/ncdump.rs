@@ -0,0 +1,106 @@
+//! Prints a netCDF file as CDL, with the options of `ncdump`
+use netcdf::cdl::{Data, Options};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(about = "Prints a netCDF file as CDL")]
+struct Opt {
+    /// Show only the header
+    #[structopt(short = "h")]
+    header: bool,
+    /// Show data of coordinate variables
+    #[structopt(short = "c")]
+    coordinates: bool,
+    /// Show data of these variables
+    #[structopt(short = "v", use_delimiter = true)]
+    variables: Vec<String>,
+    /// Show only the kind of file
+    #[structopt(short = "k")]
+    kind: bool,
+    /// Show only the extended format of the file
+    #[structopt(short = "K")]
+    extended_kind: bool,
+    /// Show special virtual attributes
+    #[structopt(short = "s")]
+    special: bool,
+    /// Show CF time variables as dates
+    #[structopt(short = "t")]
+    times: bool,
+    /// Show only these groups, by name or path
+    #[structopt(short = "g", use_delimiter = true)]
+    groups: Vec<String>,
+    /// Significant digits as float_digits[,double_digits]
+    #[structopt(short = "p", parse(try_from_str = parse_precision))]
+    precision: Option<Precision>,
+    path: std::path::PathBuf,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Precision {
+    float_digits: usize,
+    double_digits: Option<usize>,
+}
+
+fn parse_precision(s: &str) -> Result<Precision, String> {
+    let parse = |x: &str| match x.trim().parse() {
+        Ok(0) | Err(_) => Err(format!("invalid precision: {}", s)),
+        Ok(x) => Ok(x),
+    };
+    match s.split_once(',') {
+        Some((f, d)) => Ok(Precision {
+            float_digits: parse(f)?,
+            double_digits: Some(parse(d)?),
+        }),
+        None => Ok(Precision {
+            float_digits: parse(s)?,
+            double_digits: None,
+        }),
+    }
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    match run(&opt) {
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        Ok(()) => {
+            std::process::exit(0);
+        }
+    }
+}
+
+fn run(opt: &Opt) -> Result<(), Box<dyn std::error::Error>> {
+    let file = netcdf::open(&opt.path)?;
+    if opt.kind {
+        println!("{}", file.format()?);
+        return Ok(());
+    }
+    if opt.extended_kind {
+        let (format, mode) = file.format_extended()?;
+        println!("{} mode={:08x}", format, mode);
+        return Ok(());
+    }
+
+    let data = if opt.header {
+        Data::Header
+    } else if opt.coordinates {
+        Data::Coordinates
+    } else if !opt.variables.is_empty() {
+        Data::Variables(opt.variables.clone())
+    } else {
+        Data::All
+    };
+    let options = Options {
+        data,
+        special: opt.special,
+        times: opt.times,
+        groups: opt.groups.clone(),
+        float_digits: opt.precision.map(|p| p.float_digits),
+        double_digits: opt.precision.and_then(|p| p.double_digits),
+    };
+    print!("{}", file.to_cdl_with(&options)?);
+    Ok(())
+}
@@ -0,0 +1,701 @@
+//! Describe datasets in the network Common Data form Language (CDL),
+//! the text format used by `ncdump` and `ncgen`
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let file = netcdf::open("simple_xy.nc")?;
+//! // Equivalent to `ncdump -h simple_xy.nc`
+//! println!("{}", file.to_cdl()?);
+//!
+//! // Equivalent to `ncdump -c simple_xy.nc`
+//! let options = netcdf::cdl::Options {
+//!     data: netcdf::cdl::Data::Coordinates,
+//!     ..Default::default()
+//! };
+//! println!("{}", file.to_cdl_with(&options)?);
+//! # Ok(()) }
+//! ```
+
+use super::attribute::{AttrValue, Attribute, AttributeIterator};
+use super::convert;
+use super::dimension::{self, Dimension};
+use super::error;
+use super::group;
+use super::variable::{self, Numeric, Variable};
+use super::LOCK;
+use netcdf_sys::*;
+use std::fmt::Write;
+
+mod parser;
+mod time;
+pub use parser::{
+    create, parse, AttributeDef, Dataset, DimensionDef, GroupDef, Literal, VariableDef,
+};
+
+/// Variables which have their values written to the data section
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Data {
+    /// Only the header, as `ncdump -h`
+    #[default]
+    Header,
+    /// Values of coordinate variables, as `ncdump -c`
+    Coordinates,
+    /// Values of the named variables, as `ncdump -v`
+    Variables(Vec<String>),
+    /// Values of all variables
+    All,
+}
+
+/// Options for writing CDL
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    /// Which variables to write data for
+    pub data: Data,
+    /// Add the virtual attributes describing the format and
+    /// storage of variables, such as `_ChunkSizes`, as `ncdump -s`
+    pub special: bool,
+    /// Write values of time variables with CF units such
+    /// as `days since 2000-01-01` as dates, as `ncdump -t`
+    pub times: bool,
+    /// Only write these subgroups, given by name or by path
+    /// such as `forecast/model`. All groups are written if empty
+    pub groups: Vec<String>,
+    /// Significant digits of `float` values, 7 if not given
+    pub float_digits: Option<usize>,
+    /// Significant digits of `double` values, 15 if not given
+    pub double_digits: Option<usize>,
+}
+
+impl Options {
+    fn float_digits(&self) -> usize {
+        self.float_digits.unwrap_or(7)
+    }
+    fn double_digits(&self) -> usize {
+        self.double_digits.unwrap_or(15)
+    }
+    /// Whether the group at `path` or one of its subgroups is selected
+    fn selects(&self, path: &str) -> bool {
+        self.groups.is_empty()
+            || self.groups.iter().any(|g| {
+                let g = g.trim_matches('/');
+                g == path
+                    || path.rsplit('/').next() == Some(g)
+                    || path.starts_with(&format!("{}/", g))
+                    || g.starts_with(&format!("{}/", path))
+            })
+    }
+}
+
+/// CDL of a whole file, named after `path`
+pub(crate) fn file_to_cdl(ncid: nc_type, path: &str, options: &Options) -> error::Result<String> {
+    let name = std::path::Path::new(path)
+        .file_stem()
+        .map_or_else(|| "dataset".into(), |s| s.to_string_lossy());
+    let mut out = String::new();
+    writeln!(out, "netcdf {} {{", escape_name(&name)).unwrap();
+    write_group(&mut out, ncid, "", "", options)?;
+    writeln!(out, "}}").unwrap();
+    Ok(out)
+}
+
+/// CDL of a group, as it appears inside the enclosing file
+pub(crate) fn group_to_cdl(ncid: nc_type, options: &Options) -> error::Result<String> {
+    let mut out = String::new();
+    write_subgroup(&mut out, ncid, "", &group_path(ncid)?, options)?;
+    Ok(out)
+}
+
+/// Path of a group without the leading `/`
+fn group_path(ncid: nc_type) -> error::Result<String> {
+    let mut len = 0;
+    unsafe {
+        error::checked(nc_inq_grpname_full(ncid, &mut len, std::ptr::null_mut()))?;
+    }
+    let mut path = vec![0_u8; len + 1];
+    unsafe {
+        error::checked(nc_inq_grpname_full(
+            ncid,
+            std::ptr::null_mut(),
+            path.as_mut_ptr() as *mut _,
+        ))?;
+    }
+    path.truncate(len);
+    Ok(String::from_utf8(path)?.trim_start_matches('/').into())
+}
+
+fn write_subgroup(
+    out: &mut String,
+    ncid: nc_type,
+    indent: &str,
+    path: &str,
+    options: &Options,
+) -> error::Result<()> {
+    let g = group::Group {
+        ncid,
+        _file: std::marker::PhantomData,
+    };
+    let name = escape_name(&g.name());
+    writeln!(out, "{}group: {} {{", indent, name).unwrap();
+    write_group(out, ncid, &format!("{}  ", indent), path, options)?;
+    writeln!(out, "{}  }} // group {}", indent, name).unwrap();
+    Ok(())
+}
+
+/// Writes the group at `path`, which is empty for the root group
+fn write_group(
+    out: &mut String,
+    ncid: nc_type,
+    indent: &str,
+    path: &str,
+    options: &Options,
+) -> error::Result<()> {
+    let format = convert::format_at(ncid)?;
+    let dims = dimension::dimensions_from_location(ncid)?.collect::<error::Result<Vec<_>>>()?;
+    if !dims.is_empty() {
+        writeln!(out, "{}dimensions:", indent).unwrap();
+    }
+    for d in &dims {
+        let mut len = 0;
+        unsafe {
+            error::checked(nc_inq_dimlen(d.id.ncid, d.id.dimid, &mut len))?;
+        }
+        let name = escape_name(&d.name());
+        if dimension::is_unlimited_at(d.id.ncid, d.id.dimid)? {
+            writeln!(
+                out,
+                "{}\t{} = UNLIMITED ; // ({} currently)",
+                indent, name, len
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "{}\t{} = {} ;", indent, name, len).unwrap();
+        }
+    }
+
+    let vars = variable::variables_at_ncid(ncid)?.collect::<error::Result<Vec<_>>>()?;
+    if !vars.is_empty() {
+        writeln!(out, "{}variables:", indent).unwrap();
+    }
+    for v in &vars {
+        write!(
+            out,
+            "{}\t{} {}",
+            indent,
+            type_name(v.vartype())?,
+            escape_name(&v.name())
+        )
+        .unwrap();
+        if !v.dimensions().is_empty() {
+            let dims = v
+                .dimensions()
+                .iter()
+                .map(|d| escape_name(&d.name()))
+                .collect::<Vec<_>>();
+            write!(out, "({})", dims.join(", ")).unwrap();
+        }
+        writeln!(out, " ;").unwrap();
+        for att in attributes(ncid, Some(v.varid))? {
+            write_attribute(out, indent, &escape_name(&v.name()), &att, options)?;
+        }
+        if options.special {
+            write_special(out, indent, v, format)?;
+        }
+    }
+
+    let root = path.is_empty();
+    let atts = attributes(ncid, None)?;
+    if !atts.is_empty() || (root && options.special) {
+        let kind = if root { "global" } else { "group" };
+        writeln!(out, "\n{}// {} attributes:", indent, kind).unwrap();
+    }
+    for att in &atts {
+        write_attribute(out, indent, "", att, options)?;
+    }
+    if root && options.special {
+        let provenance = super::file::provenance_at(ncid)?;
+        if let Some(properties) = provenance.properties {
+            writeln!(
+                out,
+                "{}\t\t:_NCProperties = \"{}\" ;",
+                indent,
+                escape_str(&properties)
+            )
+            .unwrap();
+        }
+        if let Some(version) = provenance.superblock_version {
+            writeln!(out, "{}\t\t:_SuperblockVersion = {} ;", indent, version).unwrap();
+        }
+        if let Some(is_netcdf4) = provenance.is_netcdf4 {
+            writeln!(out, "{}\t\t:_IsNetcdf4 = {} ;", indent, is_netcdf4 as i32).unwrap();
+        }
+        writeln!(out, "{}\t\t:_Format = \"{}\" ;", indent, format).unwrap();
+    }
+
+    let data_vars = vars
+        .iter()
+        .filter(|v| has_data(v, &options.data))
+        .collect::<Vec<_>>();
+    if !data_vars.is_empty() {
+        writeln!(out, "{}data:", indent).unwrap();
+    }
+    for v in data_vars {
+        writeln!(out).unwrap();
+        write_data(out, indent, v, options)?;
+    }
+
+    if format == convert::Format::Netcdf4 {
+        for g in group::groups_at_ncid(ncid)? {
+            let name = g.name();
+            let path = if root {
+                name
+            } else {
+                format!("{}/{}", path, name)
+            };
+            if options.selects(&path) {
+                writeln!(out).unwrap();
+                write_subgroup(out, g.ncid, indent, &path, options)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Virtual attributes describing the storage of `var`
+fn write_special(
+    out: &mut String,
+    indent: &str,
+    var: &Variable,
+    format: convert::Format,
+) -> error::Result<()> {
+    let name = escape_name(&var.name());
+    let mut write = |att: &str, value: String| {
+        writeln!(out, "{}\t\t{}:{} = {} ;", indent, name, att, value).unwrap();
+    };
+
+    let mut no_fill = 0;
+    unsafe {
+        error::checked(nc_inq_var_fill(
+            var.ncid,
+            var.varid,
+            &mut no_fill,
+            std::ptr::null_mut(),
+        ))?;
+    }
+    if !matches!(
+        format,
+        convert::Format::Netcdf4 | convert::Format::Netcdf4Classic
+    ) {
+        if no_fill != 0 {
+            write("_NoFill", "\"true\"".into());
+        }
+        return Ok(());
+    }
+
+    let mut storage = 0;
+    let mut chunks = vec![0; var.dimensions().len()];
+    unsafe {
+        error::checked(nc_inq_var_chunking(
+            var.ncid,
+            var.varid,
+            &mut storage,
+            chunks.as_mut_ptr(),
+        ))?;
+    }
+    match storage {
+        NC_CHUNKED => {
+            write("_Storage", "\"chunked\"".into());
+            let chunks = chunks.iter().map(usize::to_string).collect::<Vec<_>>();
+            write("_ChunkSizes", chunks.join(", "));
+        }
+        NC_CONTIGUOUS => write("_Storage", "\"contiguous\"".into()),
+        NC_COMPACT => write("_Storage", "\"compact\"".into()),
+        _ => {}
+    }
+
+    let (mut shuffle, mut deflate, mut level) = (0, 0, 0);
+    let mut fletcher32 = 0;
+    unsafe {
+        error::checked(nc_inq_var_deflate(
+            var.ncid,
+            var.varid,
+            &mut shuffle,
+            &mut deflate,
+            &mut level,
+        ))?;
+        error::checked(nc_inq_var_fletcher32(var.ncid, var.varid, &mut fletcher32))?;
+    }
+    if deflate != 0 {
+        write("_DeflateLevel", level.to_string());
+    }
+    if shuffle != 0 {
+        write("_Shuffle", "\"true\"".into());
+    }
+    if fletcher32 != 0 {
+        write("_Fletcher32", "\"true\"".into());
+    }
+    let endianness = match var.endian_value()? {
+        variable::Endianness::Little => "little",
+        variable::Endianness::Big => "big",
+        variable::Endianness::Native => {
+            if cfg!(target_endian = "little") {
+                "little"
+            } else {
+                "big"
+            }
+        }
+    };
+    write("_Endianness", format!("\"{}\"", endianness));
+    if no_fill != 0 {
+        write("_NoFill", "\"true\"".into());
+    }
+    Ok(())
+}
+
+fn attributes<'a>(ncid: nc_type, varid: Option<nc_type>) -> error::Result<Vec<Attribute<'a>>> {
+    // Need to lock when reading the first attribute
+    let _l = LOCK.lock();
+    AttributeIterator::new(ncid, varid)?.collect()
+}
+
+fn has_data(var: &Variable, data: &Data) -> bool {
+    match data {
+        Data::Header => false,
+        Data::All => true,
+        Data::Variables(names) => names.contains(&var.name()),
+        Data::Coordinates => match var.dimensions() {
+            [d] => d.name() == var.name(),
+            _ => false,
+        },
+    }
+}
+
+/// Name of a basic type in CDL
+pub(crate) fn type_name(xtype: nc_type) -> error::Result<&'static str> {
+    Ok(match xtype {
+        NC_BYTE => "byte",
+        NC_CHAR => "char",
+        NC_SHORT => "short",
+        NC_INT => "int",
+        NC_FLOAT => "float",
+        NC_DOUBLE => "double",
+        NC_UBYTE => "ubyte",
+        NC_USHORT => "ushort",
+        NC_UINT => "uint",
+        NC_INT64 => "int64",
+        NC_UINT64 => "uint64",
+        NC_STRING => "string",
+        x => return Err(error::Error::TypeUnknown(x)),
+    })
+}
+
+fn write_attribute(
+    out: &mut String,
+    indent: &str,
+    varname: &str,
+    att: &Attribute,
+    options: &Options,
+) -> error::Result<()> {
+    let (fdigits, ddigits) = (options.float_digits(), options.double_digits());
+    let typ = att.typ()?;
+    let value = att.value()?;
+    let prefix = if typ == NC_STRING { "string " } else { "" };
+    write!(
+        out,
+        "{}\t\t{}{}:{} = ",
+        indent,
+        prefix,
+        varname,
+        escape_name(att.name())
+    )
+    .unwrap();
+    match value {
+        AttrValue::Uchar(x) => write!(out, "{}UB", x),
+        AttrValue::Uchars(x) => write_list(out, &x, |x| format!("{}UB", x)),
+        AttrValue::Schar(x) => write!(out, "{}b", x),
+        AttrValue::Schars(x) => write_list(out, &x, |x| format!("{}b", x)),
+        AttrValue::Ushort(x) => write!(out, "{}US", x),
+        AttrValue::Ushorts(x) => write_list(out, &x, |x| format!("{}US", x)),
+        AttrValue::Short(x) => write!(out, "{}s", x),
+        AttrValue::Shorts(x) => write_list(out, &x, |x| format!("{}s", x)),
+        AttrValue::Uint(x) => write!(out, "{}U", x),
+        AttrValue::Uints(x) => write_list(out, &x, |x| format!("{}U", x)),
+        AttrValue::Int(x) => write!(out, "{}", x),
+        AttrValue::Ints(x) => write_list(out, &x, i32::to_string),
+        AttrValue::Ulonglong(x) => write!(out, "{}ULL", x),
+        AttrValue::Ulonglongs(x) => write_list(out, &x, |x| format!("{}ULL", x)),
+        AttrValue::Longlong(x) => write!(out, "{}LL", x),
+        AttrValue::Longlongs(x) => write_list(out, &x, |x| format!("{}LL", x)),
+        AttrValue::Float(x) => write!(out, "{}", float_attr(x, fdigits)),
+        AttrValue::Floats(x) => write_list(out, &x, |&x| float_attr(x, fdigits)),
+        AttrValue::Double(x) => write!(out, "{}", double_attr(x, ddigits)),
+        AttrValue::Doubles(x) => write_list(out, &x, |&x| double_attr(x, ddigits)),
+        AttrValue::Str(x) => write!(out, "\"{}\"", escape_str(&x)),
+        AttrValue::Strs(x) => write_list(out, &x, |x| format!("\"{}\"", escape_str(x))),
+    }
+    .unwrap();
+    writeln!(out, " ;").unwrap();
+    Ok(())
+}
+
+fn write_list<T, F>(out: &mut String, values: &[T], f: F) -> std::fmt::Result
+where
+    F: Fn(&T) -> String,
+{
+    let values = values.iter().map(f).collect::<Vec<_>>();
+    write!(out, "{}", values.join(", "))
+}
+
+fn float_attr(x: f32, digits: usize) -> String {
+    if x.is_nan() {
+        "NaNf".into()
+    } else if x.is_infinite() {
+        format!("{}Infinityf", if x < 0.0 { "-" } else { "" })
+    } else {
+        format!("{}f", with_point(format_g(x.into(), digits)))
+    }
+}
+
+fn double_attr(x: f64, digits: usize) -> String {
+    if x.is_nan() {
+        "NaN".into()
+    } else if x.is_infinite() {
+        format!("{}Infinity", if x < 0.0 { "-" } else { "" })
+    } else {
+        with_point(format_g(x, digits))
+    }
+}
+
+/// Floating point values must be distinguishable from integers
+fn with_point(mut s: String) -> String {
+    if !s.contains(['.', 'e']) {
+        s.push('.');
+    }
+    s
+}
+
+/// Formats as the `%.{precision}g` of `printf`
+fn format_g(x: f64, precision: usize) -> String {
+    if x == 0.0 {
+        return if x.is_sign_negative() { "-0" } else { "0" }.into();
+    }
+    let precision = precision.max(1);
+    let sci = format!("{:.*e}", precision - 1, x);
+    let epos = sci.find('e').unwrap();
+    let exponent: i32 = sci[epos + 1..].parse().unwrap();
+    if exponent < -4 || exponent >= precision as i32 {
+        let mantissa = trim_zeros(&sci[..epos]);
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+    } else {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        trim_zeros(&format!("{:.*}", decimals, x)).into()
+    }
+}
+
+fn trim_zeros(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/// Escapes characters with a special meaning in CDL names
+fn escape_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        if (i == 0 && c.is_ascii_digit()) || " !\"#$%&'()*,:;<=>?[]\\^`{|}~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes a string for use inside double quotes
+fn escape_str(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => {
+                write!(escaped, "\\{:03o}", u32::from(c)).unwrap();
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_data(
+    out: &mut String,
+    indent: &str,
+    var: &Variable,
+    options: &Options,
+) -> error::Result<()> {
+    let shape = var
+        .dimensions()
+        .iter()
+        .map(Dimension::len)
+        .collect::<Vec<_>>();
+    let (fdigits, ddigits) = (options.float_digits(), options.double_digits());
+    let units = if options.times {
+        time_units(var)?
+    } else {
+        None
+    };
+    let values = if let Some(units) = units {
+        // Times may be of any numeric type, the fill value is read in that type
+        let fill = fill_value_f64(var)?;
+        numeric_data::<f64, _>(var, fill, |x| format!("\"{}\"", units.format(x)))?
+    } else {
+        match var.vartype() {
+            NC_BYTE => numeric_data::<i8, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_UBYTE => numeric_data::<u8, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_SHORT => numeric_data::<i16, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_USHORT => numeric_data::<u16, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_INT => numeric_data::<i32, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_UINT => numeric_data::<u32, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_INT64 => numeric_data::<i64, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_UINT64 => numeric_data::<u64, _>(var, var.fill_value()?, |x| x.to_string())?,
+            NC_FLOAT => {
+                numeric_data::<f32, _>(var, var.fill_value()?, |x| format_g(x.into(), fdigits))?
+            }
+            NC_DOUBLE => numeric_data::<f64, _>(var, var.fill_value()?, |x| format_g(x, ddigits))?,
+            NC_STRING => {
+                if shape.contains(&0) {
+                    Vec::new()
+                } else {
+                    var.string_values(None, None)?
+                        .iter()
+                        .map(|s| format!("\"{}\"", escape_str(s)))
+                        .collect()
+                }
+            }
+            NC_CHAR => char_data(var, &shape)?,
+            x => return Err(error::Error::TypeUnknown(x)),
+        }
+    };
+
+    // Characters of the innermost dimension are joined to strings
+    let rowlen = if var.vartype() == NC_CHAR {
+        shape.iter().rev().nth(1)
+    } else {
+        shape.last()
+    };
+    let rank = if var.vartype() == NC_CHAR {
+        shape.len().saturating_sub(1)
+    } else {
+        shape.len()
+    };
+
+    write!(out, "{} {} =", indent, escape_name(&var.name())).unwrap();
+    if rank < 2 {
+        write!(out, " {}", values.join(", ")).unwrap();
+    } else {
+        let rowlen = rowlen.copied().unwrap_or(1).max(1);
+        let rows = values.chunks(rowlen).collect::<Vec<_>>();
+        for (i, row) in rows.iter().enumerate() {
+            let sep = if i + 1 == rows.len() { "" } else { "," };
+            write!(out, "\n{}  {}{}", indent, row.join(", "), sep).unwrap();
+        }
+    }
+    writeln!(out, " ;").unwrap();
+    Ok(())
+}
+
+/// CF time units of a numeric variable
+fn time_units(var: &Variable) -> error::Result<Option<time::Units>> {
+    if matches!(var.vartype(), NC_CHAR | NC_STRING) {
+        return Ok(None);
+    }
+    let text = |name| -> error::Result<Option<String>> {
+        let value = var.attribute(name).map(|a| a.value()).transpose()?;
+        Ok(value.and_then(|value| std::convert::TryFrom::try_from(value).ok()))
+    };
+    let calendar = text("calendar")?.unwrap_or_else(|| "standard".into());
+    Ok(text("units")?.and_then(|units| time::Units::parse(&units, &calendar)))
+}
+
+/// Fill value of a numeric variable as a double, read in the type of the variable
+#[allow(clippy::cast_precision_loss)]
+fn fill_value_f64(var: &Variable) -> error::Result<Option<f64>> {
+    Ok(match var.vartype() {
+        NC_BYTE => var.fill_value::<i8>()?.map(f64::from),
+        NC_UBYTE => var.fill_value::<u8>()?.map(f64::from),
+        NC_SHORT => var.fill_value::<i16>()?.map(f64::from),
+        NC_USHORT => var.fill_value::<u16>()?.map(f64::from),
+        NC_INT => var.fill_value::<i32>()?.map(f64::from),
+        NC_UINT => var.fill_value::<u32>()?.map(f64::from),
+        NC_INT64 => var.fill_value::<i64>()?.map(|x| x as f64),
+        NC_UINT64 => var.fill_value::<u64>()?.map(|x| x as f64),
+        NC_FLOAT => var.fill_value::<f32>()?.map(f64::from),
+        NC_DOUBLE => var.fill_value::<f64>()?,
+        x => return Err(error::Error::TypeUnknown(x)),
+    })
+}
+
+/// All values of `var` formatted with `f`, with `fill` as `_`
+fn numeric_data<T, F>(var: &Variable, fill: Option<T>, f: F) -> error::Result<Vec<String>>
+where
+    T: Numeric + Copy + Default + PartialEq,
+    F: Fn(T) -> String,
+{
+    if var.dimensions().iter().any(|d| d.len() == 0) {
+        return Ok(Vec::new());
+    }
+    let mut values = vec![T::default(); var.len()];
+    var.values_to(&mut values, None, None)?;
+    Ok(values
+        .into_iter()
+        .map(|x| if Some(x) == fill { "_".into() } else { f(x) })
+        .collect())
+}
+
+/// Strings along the innermost dimension of a `NC_CHAR` variable
+fn char_data(var: &Variable, shape: &[usize]) -> error::Result<Vec<String>> {
+    let len = shape.iter().product::<usize>();
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let mut buf = vec![0_u8; len];
+    let start = vec![0; shape.len()];
+    unsafe {
+        let _l = LOCK.lock();
+        error::checked(nc_get_vara_text(
+            var.ncid,
+            var.varid,
+            start.as_ptr(),
+            shape.as_ptr(),
+            buf.as_mut_ptr() as *mut _,
+        ))?;
+    }
+    let strlen = shape.last().copied().unwrap_or(1);
+    Ok(buf
+        .chunks(strlen)
+        .map(|s| {
+            let end = s.iter().rposition(|&c| c != 0).map_or(0, |p| p + 1);
+            format!("\"{}\"", escape_str(&String::from_utf8_lossy(&s[..end])))
+        })
+        .collect())
+}
+
+#[test]
+fn printf_g() {
+    assert_eq!(format_g(1.0, 7), "1");
+    assert_eq!(format_g(0.5, 7), "0.5");
+    assert_eq!(format_g(f64::from(0.1_f32), 7), "0.1");
+    assert_eq!(format_g(123_456_789.0, 7), "1.234568e+08");
+    assert_eq!(format_g(0.000_012_5, 15), "1.25e-05");
+    assert_eq!(format_g(-273.15, 15), "-273.15");
+    assert_eq!(float_attr(1.0, 7), "1.f");
+    assert_eq!(double_attr(1e20, 15), "1e+20");
+    assert_eq!(double_attr(0.123_456_789, 4), "0.1235");
+    assert_eq!(escape_name("a b"), "a\\ b");
+    assert_eq!(escape_name("2d"), "\\2d");
+    assert_eq!(escape_str("say \"hi\"\n"), "say \\\"hi\\\"\\n");
+}
@@ -0,0 +1,998 @@
+//! Parse CDL into a dataset definition, and create files from it
+
+use crate::attribute::{AttrValue, Attribute};
+use crate::dimension::{self, Dimension};
+use crate::error;
+use crate::file::{MutableFile, RawFile};
+use crate::group::{self, GroupMut};
+use crate::variable::{Numeric, Variable, VariableMut};
+use crate::LOCK;
+use netcdf_sys::*;
+use std::convert::TryFrom;
+
+/// A dataset parsed from CDL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    /// Name following the `netcdf` keyword
+    pub name: String,
+    /// Contents of the root group
+    pub root: GroupDef,
+}
+
+/// Definition of a group
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupDef {
+    /// Name of the group (empty for the root group)
+    pub name: String,
+    /// Dimensions defined in this group
+    pub dimensions: Vec<DimensionDef>,
+    /// Variables defined in this group
+    pub variables: Vec<VariableDef>,
+    /// Attributes of the group
+    pub attributes: Vec<AttributeDef>,
+    /// Subgroups
+    pub groups: Vec<GroupDef>,
+}
+
+/// Definition of a dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionDef {
+    /// Name of the dimension
+    pub name: String,
+    /// Length of the dimension, `None` when unlimited
+    pub len: Option<usize>,
+}
+
+/// Definition of a variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDef {
+    /// Name of the variable
+    pub name: String,
+    /// Type of the variable, such as `NC_FLOAT`
+    pub vartype: nc_type,
+    /// Names of the dimensions
+    pub dimensions: Vec<String>,
+    /// Attributes of the variable
+    pub attributes: Vec<AttributeDef>,
+    /// Values from the data section
+    pub data: Option<Vec<Literal>>,
+}
+
+/// Definition of an attribute
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeDef {
+    /// Name of the attribute
+    pub name: String,
+    /// Type given in front of the attribute, otherwise
+    /// the type is inferred from the values
+    pub attrtype: Option<nc_type>,
+    /// Values of the attribute
+    pub values: Vec<Literal>,
+}
+
+/// A constant in CDL
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    /// Integer, with the type given by a suffix (e.g. `NC_SHORT` for `1s`)
+    Int(i128, Option<nc_type>),
+    /// Floating point number, with the type given by a suffix
+    /// (`NC_FLOAT` for `1.f`)
+    Float(f64, Option<nc_type>),
+    /// String or characters
+    Str(String),
+    /// The fill value of the variable (`_`)
+    Fill,
+}
+
+/// Parses a CDL description of a dataset, as written by `ncdump`
+///
+/// # Errors
+///
+/// Invalid or unsupported CDL (such as user defined types)
+pub fn parse(cdl: &str) -> error::Result<Dataset> {
+    let mut parser = Parser {
+        tokens: lex(cdl)?,
+        pos: 0,
+    };
+    parser.dataset()
+}
+
+/// Creates a new file at `path` with the contents
+/// described by `cdl` (equivalent to `ncgen`)
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let file = netcdf::cdl::create(
+///     "simple.nc",
+///     "netcdf simple {
+///     dimensions:
+///         x = 3 ;
+///     variables:
+///         float x(x) ;
+///             x:units = \"m\" ;
+///     data:
+///         x = 0.5, 1, 1.5 ;
+///     }",
+/// )?;
+/// # Ok(()) }
+/// ```
+///
+/// # Errors
+///
+/// Invalid CDL, or the contents could not be written to the file
+pub fn create<P>(path: P, cdl: &str) -> error::Result<MutableFile>
+where
+    P: AsRef<std::path::Path>,
+{
+    parse(cdl)?.create(path)
+}
+
+impl Dataset {
+    /// Creates a new `netCDF-4` file with the dimensions,
+    /// variables, attributes, groups, and data of this definition
+    ///
+    /// # Errors
+    ///
+    /// Values not representable in the type of the variable or
+    /// attribute, mismatch between data and dimensions, or netcdf errors
+    pub fn create<P>(&self, path: P) -> error::Result<MutableFile>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let file = RawFile::create(path.as_ref())?;
+        {
+            let _l = LOCK.lock();
+            define_group(&self.root, file.ncid())?;
+        }
+        put_group_data(&self.root, file.ncid())?;
+        Ok(file)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(Literal),
+    Str(String),
+    Punct(char),
+}
+
+fn parse_error<T>(line: usize, msg: &str) -> error::Result<T> {
+    Err(error::Error::Str(format!("CDL line {}: {}", line, msg)))
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '\\' || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || "_.@+-\\".contains(c) || !c.is_ascii()
+}
+
+#[allow(clippy::too_many_lines)]
+fn lex(cdl: &str) -> error::Result<Vec<(Token, usize)>> {
+    let chars = cdl.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if "{}(),;=:".contains(c) {
+            tokens.push((Token::Punct(c), line));
+            i += 1;
+        } else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return parse_error(line, "unterminated string"),
+                    Some('"') => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some('r') => s.push('\r'),
+                            Some(d) if d.is_digit(8) => {
+                                let mut code = 0;
+                                let mut n = 0;
+                                while n < 3 && chars.get(i).is_some_and(|d| d.is_digit(8)) {
+                                    code = code * 8 + chars[i].to_digit(8).unwrap();
+                                    i += 1;
+                                    n += 1;
+                                }
+                                i -= 1;
+                                s.push(std::char::from_u32(code).unwrap_or('\u{fffd}'));
+                            }
+                            Some(&d) => s.push(d),
+                            None => return parse_error(line, "unterminated string"),
+                        }
+                    }
+                    Some(&d) => {
+                        if d == '\n' {
+                            line += 1;
+                        }
+                        s.push(d);
+                    }
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(s), line));
+        } else if c.is_ascii_digit()
+            || ((c == '-' || c == '+' || c == '.')
+                && next.is_some_and(|n| n.is_ascii_digit() || n == 'I' || n == 'N'))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len() {
+                let d = chars[i];
+                let exponent_sign = (d == '-' || d == '+')
+                    && (chars[i - 1] == 'e' || chars[i - 1] == 'E')
+                    && chars[start..i - 1].iter().all(|c| !c.is_alphabetic());
+                if d.is_alphanumeric() || d == '.' || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let text = chars[start..i].iter().collect::<String>();
+            tokens.push((Token::Number(number(&text, line)?), line));
+        } else if is_ident_start(c) {
+            let mut s = String::new();
+            while i < chars.len() && is_ident_char(chars[i]) {
+                if chars[i] == '\\' {
+                    i += 1;
+                    match chars.get(i) {
+                        Some(&d) => s.push(d),
+                        None => return parse_error(line, "unterminated name"),
+                    }
+                } else {
+                    s.push(chars[i]);
+                }
+                i += 1;
+            }
+            tokens.push((Token::Ident(s), line));
+        } else {
+            return parse_error(line, &format!("unexpected character {:?}", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses a numeric constant including the type suffix
+fn number(text: &str, line: usize) -> error::Result<Literal> {
+    let special = match text.trim_start_matches(['-', '+']) {
+        "NaN" => Some((f64::NAN, NC_DOUBLE)),
+        "NaNf" => Some((f64::NAN, NC_FLOAT)),
+        "Infinity" => Some((f64::INFINITY, NC_DOUBLE)),
+        "Infinityf" => Some((f64::INFINITY, NC_FLOAT)),
+        _ => None,
+    };
+    if let Some((x, typ)) = special {
+        let x = if text.starts_with('-') { -x } else { x };
+        return Ok(Literal::Float(x, Some(typ)));
+    }
+
+    let split = text
+        .char_indices()
+        .skip(1)
+        .find(|&(i, c)| {
+            c.is_alphabetic()
+                && !((c == 'e' || c == 'E')
+                    && text[i + 1..]
+                        .starts_with(|d: char| d.is_ascii_digit() || d == '-' || d == '+'))
+        })
+        .map_or(text.len(), |(i, _)| i);
+    let (digits, suffix) = text.split_at(split);
+    let is_float = digits.contains(['.', 'e', 'E']);
+
+    let typ = match suffix.to_ascii_lowercase().as_str() {
+        "" => None,
+        "f" => Some(NC_FLOAT),
+        "d" => Some(NC_DOUBLE),
+        "b" => Some(NC_BYTE),
+        "s" => Some(NC_SHORT),
+        "l" => Some(NC_INT),
+        "ll" => Some(NC_INT64),
+        "ub" => Some(NC_UBYTE),
+        "us" => Some(NC_USHORT),
+        "u" | "ul" => Some(NC_UINT),
+        "ull" => Some(NC_UINT64),
+        _ => return parse_error(line, &format!("invalid number {}", text)),
+    };
+    match typ {
+        Some(NC_FLOAT) | Some(NC_DOUBLE) => match digits.parse() {
+            Ok(x) => Ok(Literal::Float(x, typ)),
+            Err(_) => parse_error(line, &format!("invalid number {}", text)),
+        },
+        _ if is_float => {
+            if typ.is_some() {
+                return parse_error(line, &format!("invalid number {}", text));
+            }
+            match digits.parse() {
+                Ok(x) => Ok(Literal::Float(x, None)),
+                Err(_) => parse_error(line, &format!("invalid number {}", text)),
+            }
+        }
+        _ => match digits.parse() {
+            Ok(x) => Ok(Literal::Int(x, typ)),
+            Err(_) => parse_error(line, &format!("invalid number {}", text)),
+        },
+    }
+}
+
+/// Type from a type name in CDL
+fn type_from_name(name: &str) -> Option<nc_type> {
+    Some(match name {
+        "char" => NC_CHAR,
+        "byte" => NC_BYTE,
+        "short" => NC_SHORT,
+        "int" | "long" | "integer" => NC_INT,
+        "float" | "real" => NC_FLOAT,
+        "double" => NC_DOUBLE,
+        "ubyte" => NC_UBYTE,
+        "ushort" => NC_USHORT,
+        "uint" => NC_UINT,
+        "int64" => NC_INT64,
+        "uint64" => NC_UINT64,
+        "string" => NC_STRING,
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Section {
+    None,
+    Dimensions,
+    Variables,
+    Data,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.0)
+    }
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|t| &t.0)
+    }
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |t| t.1)
+    }
+    fn error<T>(&self, msg: &str) -> error::Result<T> {
+        parse_error(self.line(), msg)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).map(|t| t.0.clone());
+        self.pos += 1;
+        t
+    }
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+    fn expect(&mut self, c: char) -> error::Result<()> {
+        if self.is_punct(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", c))
+        }
+    }
+    fn ident(&mut self) -> error::Result<String> {
+        match self.peek() {
+            Some(Token::Ident(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => self.error("expected a name"),
+        }
+    }
+    /// Checks for a keyword followed by ':'
+    fn is_section(&self, keyword: &str) -> bool {
+        self.peek() == Some(&Token::Ident(keyword.into()))
+            && self.peek_at(1) == Some(&Token::Punct(':'))
+    }
+
+    fn dataset(&mut self) -> error::Result<Dataset> {
+        if self.ident()? != "netcdf" {
+            return self.error("expected 'netcdf'");
+        }
+        let name = self.ident()?;
+        self.expect('{')?;
+        let root = self.group_body(String::new())?;
+        self.expect('}')?;
+        if self.peek().is_some() {
+            return self.error("unexpected content after the dataset");
+        }
+        Ok(Dataset { name, root })
+    }
+
+    fn group_body(&mut self, name: String) -> error::Result<GroupDef> {
+        let mut group = GroupDef {
+            name,
+            ..GroupDef::default()
+        };
+        let mut section = Section::None;
+        loop {
+            if self.peek().is_none() || self.is_punct('}') {
+                return Ok(group);
+            }
+            if self.is_section("types") {
+                return self.error("user defined types are not supported");
+            }
+            if self.is_section("dimensions") {
+                section = Section::Dimensions;
+                self.pos += 2;
+                continue;
+            }
+            if self.is_section("variables") {
+                section = Section::Variables;
+                self.pos += 2;
+                continue;
+            }
+            if self.is_section("data") {
+                section = Section::Data;
+                self.pos += 2;
+                continue;
+            }
+            if self.is_section("group") {
+                self.pos += 2;
+                let name = self.ident()?;
+                self.expect('{')?;
+                let subgroup = self.group_body(name)?;
+                self.expect('}')?;
+                group.groups.push(subgroup);
+                section = Section::None;
+                continue;
+            }
+            match section {
+                Section::Dimensions => self.dimensions(&mut group)?,
+                Section::Data => self.data(&mut group)?,
+                Section::Variables | Section::None => self.variable_or_attribute(&mut group)?,
+            }
+        }
+    }
+
+    fn dimensions(&mut self, group: &mut GroupDef) -> error::Result<()> {
+        loop {
+            let name = self.ident()?;
+            self.expect('=')?;
+            let len = match self.next() {
+                Some(Token::Number(Literal::Int(x, _))) => match usize::try_from(x) {
+                    Ok(x) => Some(x),
+                    Err(_) => return self.error("invalid dimension length"),
+                },
+                Some(Token::Ident(ref s))
+                    if s == "UNLIMITED" || s == "unlimited" || s == "NC_UNLIMITED" =>
+                {
+                    None
+                }
+                _ => return self.error("expected a dimension length"),
+            };
+            group.dimensions.push(DimensionDef { name, len });
+            if self.is_punct(',') {
+                self.pos += 1;
+            } else {
+                return self.expect(';');
+            }
+        }
+    }
+
+    fn variable_or_attribute(&mut self, group: &mut GroupDef) -> error::Result<()> {
+        let typ = match self.peek() {
+            // `int:units` is an attribute of a variable named `int`,
+            // but `int :units` is a global attribute of type int
+            Some(Token::Ident(s))
+                if self.peek_at(1) != Some(&Token::Punct(':'))
+                    || group.variables.iter().all(|v| &v.name != s) =>
+            {
+                type_from_name(s)
+            }
+            _ => None,
+        };
+        if typ.is_some() {
+            self.pos += 1;
+        }
+
+        if self.is_punct(':') {
+            self.pos += 1;
+            let att = self.attribute(typ)?;
+            group.attributes.push(att);
+            return Ok(());
+        }
+        let name = self.ident()?;
+        if self.is_punct(':') {
+            self.pos += 1;
+            let att = self.attribute(typ)?;
+            return match group.variables.iter_mut().find(|v| v.name == name) {
+                Some(var) => {
+                    var.attributes.push(att);
+                    Ok(())
+                }
+                None => self.error(&format!("attribute for undeclared variable {}", name)),
+            };
+        }
+
+        let vartype = match typ {
+            Some(t) => t,
+            None => return self.error("expected a type"),
+        };
+        let mut name = name;
+        loop {
+            let mut dimensions = Vec::new();
+            if self.is_punct('(') {
+                self.pos += 1;
+                loop {
+                    dimensions.push(self.ident()?);
+                    if self.is_punct(',') {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(')')?;
+            }
+            group.variables.push(VariableDef {
+                name,
+                vartype,
+                dimensions,
+                attributes: Vec::new(),
+                data: None,
+            });
+            if self.is_punct(',') {
+                self.pos += 1;
+                name = self.ident()?;
+            } else {
+                return self.expect(';');
+            }
+        }
+    }
+
+    fn attribute(&mut self, attrtype: Option<nc_type>) -> error::Result<AttributeDef> {
+        let name = self.ident()?;
+        self.expect('=')?;
+        let values = self.literals()?;
+        Ok(AttributeDef {
+            name,
+            attrtype,
+            values,
+        })
+    }
+
+    fn data(&mut self, group: &mut GroupDef) -> error::Result<()> {
+        let name = self.ident()?;
+        self.expect('=')?;
+        let values = self.literals()?;
+        match group.variables.iter_mut().find(|v| v.name == name) {
+            Some(var) => {
+                var.data = Some(values);
+                Ok(())
+            }
+            None => self.error(&format!("data for undeclared variable {}", name)),
+        }
+    }
+
+    /// Comma separated constants, ended by ';'
+    fn literals(&mut self) -> error::Result<Vec<Literal>> {
+        let mut values = Vec::new();
+        if self.is_punct(';') {
+            self.pos += 1;
+            return Ok(values);
+        }
+        loop {
+            let value = match self.next() {
+                Some(Token::Number(x)) => x,
+                Some(Token::Str(s)) => Literal::Str(s),
+                Some(Token::Ident(ref s)) if s == "_" => Literal::Fill,
+                Some(Token::Ident(ref s)) => number(s, self.line()).or_else(|_| {
+                    self.pos -= 1;
+                    self.error(&format!("unexpected {}", s))
+                })?,
+                Some(Token::Punct('{')) => {
+                    self.pos -= 1;
+                    return self.error("compound values are not supported");
+                }
+                _ => {
+                    self.pos -= 1;
+                    return self.error("expected a value");
+                }
+            };
+            values.push(value);
+            if self.is_punct(',') {
+                self.pos += 1;
+            } else {
+                self.expect(';')?;
+                return Ok(values);
+            }
+        }
+    }
+}
+
+/// Values which can be converted from CDL constants
+trait FromLiteral: Numeric + Copy + Default {
+    fn from_literal(x: &Literal) -> error::Result<Self>;
+}
+
+macro_rules! impl_from_literal_int {
+    ($($t: ty),*) => {
+        $(
+        impl FromLiteral for $t {
+            fn from_literal(x: &Literal) -> error::Result<Self> {
+                match *x {
+                    Literal::Int(x, _) => Ok(Self::try_from(x)?),
+                    _ => Err(error::Error::TypeMismatch),
+                }
+            }
+        }
+        )*
+    };
+}
+macro_rules! impl_from_literal_float {
+    ($($t: ty),*) => {
+        $(
+        impl FromLiteral for $t {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+            fn from_literal(x: &Literal) -> error::Result<Self> {
+                match *x {
+                    Literal::Int(x, _) => Ok(x as Self),
+                    Literal::Float(x, _) => Ok(x as Self),
+                    _ => Err(error::Error::TypeMismatch),
+                }
+            }
+        }
+        )*
+    };
+}
+impl_from_literal_int!(i8, u8, i16, u16, i32, u32, i64, u64);
+impl_from_literal_float!(f32, f64);
+
+fn from_literals<T: FromLiteral>(values: &[Literal]) -> error::Result<Vec<T>> {
+    values.iter().map(T::from_literal).collect()
+}
+
+/// Type of an attribute without an explicit type
+fn infer_type(values: &[Literal]) -> error::Result<nc_type> {
+    if values.iter().all(|x| matches!(x, Literal::Str(_))) {
+        return Ok(NC_CHAR);
+    }
+    let types = values
+        .iter()
+        .map(|x| match *x {
+            Literal::Int(_, Some(t)) | Literal::Float(_, Some(t)) => Ok(t),
+            Literal::Int(x, None) if i32::try_from(x).is_ok() => Ok(NC_INT),
+            Literal::Int(_, None) => Ok(NC_INT64),
+            Literal::Float(_, None) => Ok(NC_DOUBLE),
+            Literal::Str(_) | Literal::Fill => Err(error::Error::TypeMismatch),
+        })
+        .collect::<error::Result<Vec<_>>>()?;
+    if types.iter().all(|&t| t == types[0]) {
+        Ok(types[0])
+    } else if types.iter().any(|&t| t == NC_FLOAT || t == NC_DOUBLE) {
+        Ok(NC_DOUBLE)
+    } else {
+        Ok(NC_INT64)
+    }
+}
+
+fn attribute_value(typ: nc_type, values: &[Literal]) -> error::Result<AttrValue> {
+    macro_rules! numeric {
+        ($t: ty, $scalar: ident, $vec: ident) => {{
+            let mut values = from_literals::<$t>(values)?;
+            if values.len() == 1 {
+                AttrValue::$scalar(values.remove(0))
+            } else {
+                AttrValue::$vec(values)
+            }
+        }};
+    }
+    Ok(match typ {
+        NC_CHAR => {
+            let mut s = String::new();
+            for x in values {
+                match x {
+                    Literal::Str(x) => s.push_str(x),
+                    _ => return Err(error::Error::TypeMismatch),
+                }
+            }
+            AttrValue::Str(s)
+        }
+        NC_BYTE => numeric!(i8, Schar, Schars),
+        NC_UBYTE => numeric!(u8, Uchar, Uchars),
+        NC_SHORT => numeric!(i16, Short, Shorts),
+        NC_USHORT => numeric!(u16, Ushort, Ushorts),
+        NC_INT => numeric!(i32, Int, Ints),
+        NC_UINT => numeric!(u32, Uint, Uints),
+        NC_INT64 => numeric!(i64, Longlong, Longlongs),
+        NC_UINT64 => numeric!(u64, Ulonglong, Ulonglongs),
+        NC_FLOAT => numeric!(f32, Float, Floats),
+        NC_DOUBLE => numeric!(f64, Double, Doubles),
+        x => return Err(error::Error::TypeUnknown(x)),
+    })
+}
+
+fn put_attribute(
+    ncid: nc_type,
+    varid: nc_type,
+    vartype: Option<nc_type>,
+    att: &AttributeDef,
+) -> error::Result<()> {
+    let typ = match (att.attrtype, vartype) {
+        (Some(t), _) => t,
+        // The fill value must have the type of the variable
+        (None, Some(t)) if att.name == "_FillValue" => t,
+        (None, _) => infer_type(&att.values)?,
+    };
+    if typ == NC_STRING {
+        let strings = att
+            .values
+            .iter()
+            .map(|x| match x {
+                Literal::Str(s) => Ok(s.as_str()),
+                _ => Err(error::Error::TypeMismatch),
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        Attribute::put_strings(ncid, varid, &att.name, &strings)?;
+    } else {
+        Attribute::put(ncid, varid, &att.name, attribute_value(typ, &att.values)?)?;
+    }
+    Ok(())
+}
+
+/// Defines dimensions, variables and attributes of `group` in `ncid`
+fn define_group(group: &GroupDef, ncid: nc_type) -> error::Result<()> {
+    for d in &group.dimensions {
+        dimension::add_dimension_at(ncid, &d.name, d.len.unwrap_or(0))?;
+    }
+    for v in &group.variables {
+        let dims = v.dimensions.iter().map(String::as_str).collect::<Vec<_>>();
+        let var = VariableMut::add_from_str(ncid, v.vartype, &v.name, &dims)?;
+        for att in &v.attributes {
+            put_attribute(ncid, var.varid, Some(v.vartype), att)?;
+        }
+    }
+    for att in &group.attributes {
+        put_attribute(ncid, NC_GLOBAL, None, att)?;
+    }
+    for g in &group.groups {
+        let subgroup = GroupMut::add_group_at(ncid, &g.name)?;
+        define_group(g, subgroup.ncid)?;
+    }
+    Ok(())
+}
+
+/// Writes the data sections of `group`, defined in `ncid`
+fn put_group_data(group: &GroupDef, ncid: nc_type) -> error::Result<()> {
+    for v in &group.variables {
+        if let Some(data) = &v.data {
+            let var = match Variable::find_from_name(ncid, &v.name)? {
+                Some(var) => var,
+                None => return Err(error::Error::NotFound(format!("variable {}", v.name))),
+            };
+            put_data(&mut VariableMut(var, std::marker::PhantomData), data)?;
+        }
+    }
+    for g in &group.groups {
+        match group::group_from_name(ncid, &g.name)? {
+            Some(subgroup) => put_group_data(g, subgroup.ncid)?,
+            None => return Err(error::Error::NotFound(format!("group {}", g.name))),
+        }
+    }
+    Ok(())
+}
+
+fn put_data(var: &mut VariableMut, data: &[Literal]) -> error::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    match var.vartype() {
+        NC_BYTE => put_numeric::<i8>(var, data),
+        NC_UBYTE => put_numeric::<u8>(var, data),
+        NC_SHORT => put_numeric::<i16>(var, data),
+        NC_USHORT => put_numeric::<u16>(var, data),
+        NC_INT => put_numeric::<i32>(var, data),
+        NC_UINT => put_numeric::<u32>(var, data),
+        NC_INT64 => put_numeric::<i64>(var, data),
+        NC_UINT64 => put_numeric::<u64>(var, data),
+        NC_FLOAT => put_numeric::<f32>(var, data),
+        NC_DOUBLE => put_numeric::<f64>(var, data),
+        NC_STRING => {
+            let strings = strings(data)?;
+            let (indices, count) = data_slice(var.dimensions(), strings.len())?;
+            var.put_string_values(&strings, Some(&indices), Some(&count))
+        }
+        NC_CHAR => {
+            let mut strings = strings(data)?
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            let dims = var.dimensions();
+            if dims.len() <= 1 {
+                // Characters are concatenated into a single string
+                strings = vec![strings.concat()];
+            }
+            let rows = &dims[..dims.len().saturating_sub(1)];
+            let (indices, count) = data_slice(rows, strings.len())?;
+            var.put_text_values(&strings, Some(&indices), Some(&count))
+        }
+        x => Err(error::Error::TypeUnknown(x)),
+    }
+}
+
+fn strings(data: &[Literal]) -> error::Result<Vec<&str>> {
+    data.iter()
+        .map(|x| match x {
+            Literal::Str(s) => Ok(s.as_str()),
+            _ => Err(error::Error::TypeMismatch),
+        })
+        .collect()
+}
+
+fn put_numeric<T: FromLiteral>(var: &mut VariableMut, data: &[Literal]) -> error::Result<()> {
+    let fill = if data.contains(&Literal::Fill) {
+        var.fill_value::<T>()?
+    } else {
+        None
+    };
+    let values = data
+        .iter()
+        .map(|x| match x {
+            Literal::Fill => fill.ok_or(error::Error::TypeMismatch),
+            x => T::from_literal(x),
+        })
+        .collect::<error::Result<Vec<_>>>()?;
+    let (indices, count) = data_slice(var.dimensions(), values.len())?;
+    var.put_values(&values, Some(&indices), Some(&count))
+}
+
+/// Hyperslab covered by `len` values, which may extend
+/// a leading unlimited dimension
+fn data_slice(dims: &[Dimension], len: usize) -> error::Result<(Vec<usize>, Vec<usize>)> {
+    let indices = vec![0; dims.len()];
+    let mut count = Vec::with_capacity(dims.len());
+    for d in dims {
+        if dimension::is_unlimited_at(d.id.ncid, d.id.dimid)? {
+            count.push(0);
+        } else {
+            count.push(d.len());
+        }
+    }
+    match count.first() {
+        Some(0) => {
+            let inner = count[1..].iter().product::<usize>();
+            if inner == 0 || !len.is_multiple_of(inner) {
+                return Err(error::Error::BufferLen(len, inner));
+            }
+            count[0] = len / inner;
+        }
+        _ => {
+            let total = count.iter().product::<usize>();
+            if total != len {
+                return Err(error::Error::BufferLen(len, total));
+            }
+        }
+    }
+    Ok((indices, count))
+}
+
+#[test]
+fn parse_cdl() {
+    let cdl = r#"netcdf test {
+dimensions:
+	time = UNLIMITED ; // (2 currently)
+	x = 3 ;
+variables:
+	short v(time, x) ;
+		v:scale_factor = 0.5 ;
+		v:valid_range = 0s, 100s ;
+	string s ;
+	int64 \2d(x) ;
+
+// global attributes:
+		:title = "a \"quoted\"\ttitle" ;
+		string :names = "a", "b" ;
+		:f = 1.f, NaNf ;
+data:
+
+ v =
+  1, 2, 3,
+  4, _, -6 ;
+
+group: g {
+  dimensions:
+  	y = 2 ;
+  } // group g
+}
+"#;
+    let dataset = parse(cdl).unwrap();
+    assert_eq!(dataset.name, "test");
+    let root = &dataset.root;
+    assert_eq!(
+        root.dimensions,
+        vec![
+            DimensionDef {
+                name: "time".into(),
+                len: None
+            },
+            DimensionDef {
+                name: "x".into(),
+                len: Some(3)
+            }
+        ]
+    );
+    assert_eq!(root.variables.len(), 3);
+    let v = &root.variables[0];
+    assert_eq!(v.vartype, NC_SHORT);
+    assert_eq!(v.dimensions, vec!["time", "x"]);
+    assert_eq!(v.attributes[0].values, vec![Literal::Float(0.5, None)]);
+    assert_eq!(
+        v.attributes[1].values,
+        vec![
+            Literal::Int(0, Some(NC_SHORT)),
+            Literal::Int(100, Some(NC_SHORT))
+        ]
+    );
+    assert_eq!(v.data.as_ref().unwrap()[4], Literal::Fill);
+    assert_eq!(v.data.as_ref().unwrap()[5], Literal::Int(-6, None));
+    assert_eq!(root.variables[2].name, "2d");
+    assert_eq!(root.variables[2].vartype, NC_INT64);
+
+    assert_eq!(
+        root.attributes[0].values,
+        vec![Literal::Str("a \"quoted\"\ttitle".into())]
+    );
+    assert_eq!(root.attributes[1].attrtype, Some(NC_STRING));
+    match root.attributes[2].values[1] {
+        Literal::Float(x, Some(NC_FLOAT)) => assert!(x.is_nan()),
+        ref x => panic!("unexpected {:?}", x),
+    }
+    assert_eq!(root.groups[0].name, "g");
+    assert_eq!(root.groups[0].dimensions[0].len, Some(2));
+
+    assert_eq!(infer_type(&root.attributes[2].values).unwrap(), NC_FLOAT);
+    assert!(parse("netcdf x { variables: int v ; data: w = 1 ; }").is_err());
+    assert!(parse("netcdf x { types: int(*) vlen ; }").is_err());
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(number("1", 0).unwrap(), Literal::Int(1, None));
+    assert_eq!(number("-1UB", 0).unwrap(), Literal::Int(-1, Some(NC_UBYTE)));
+    assert_eq!(number("1.", 0).unwrap(), Literal::Float(1.0, None));
+    assert_eq!(
+        number("1.f", 0).unwrap(),
+        Literal::Float(1.0, Some(NC_FLOAT))
+    );
+    assert_eq!(number("1e+20", 0).unwrap(), Literal::Float(1e20, None));
+    assert_eq!(
+        number("2.5e-3f", 0).unwrap(),
+        Literal::Float(2.5e-3, Some(NC_FLOAT))
+    );
+    assert_eq!(
+        number("10ULL", 0).unwrap(),
+        Literal::Int(10, Some(NC_UINT64))
+    );
+    assert_eq!(
+        number("-Infinity", 0).unwrap(),
+        Literal::Float(f64::NEG_INFINITY, Some(NC_DOUBLE))
+    );
+    assert!(number("1.5s", 0).is_err());
+}
@@ -0,0 +1,206 @@
+//! Dates from CF time coordinates, such as `days since 2000-01-01`
+
+/// Calendars of the CF conventions with a fixed length of years
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Calendar {
+    /// Gregorian calendar, also used before 1582 for `standard`
+    Gregorian,
+    /// Years of 365 days
+    NoLeap,
+    /// Years of 366 days
+    AllLeap,
+    /// Twelve months of 30 days
+    Day360,
+}
+
+const MONTHS: [i64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+const MONTHS_LEAP: [i64; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+impl Calendar {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "standard" | "gregorian" | "proleptic_gregorian" => Self::Gregorian,
+            "noleap" | "365_day" => Self::NoLeap,
+            "all_leap" | "366_day" => Self::AllLeap,
+            "360_day" => Self::Day360,
+            _ => return None,
+        })
+    }
+
+    /// Number of days since 0000-01-01
+    fn days(self, year: i64, month: i64, day: i64) -> i64 {
+        let before = |months: &[i64]| months[..(month - 1) as usize].iter().sum::<i64>();
+        match self {
+            Self::Gregorian => {
+                // Days from civil, with years starting in March
+                let y = if month <= 2 { year - 1 } else { year };
+                let era = y.div_euclid(400);
+                let yoe = y - era * 400;
+                let mp = (month + 9) % 12;
+                let doy = (153 * mp + 2) / 5 + day - 1;
+                let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+                era * 146_097 + doe + 60
+            }
+            Self::NoLeap => year * 365 + before(&MONTHS) + day - 1,
+            Self::AllLeap => year * 366 + before(&MONTHS_LEAP) + day - 1,
+            Self::Day360 => year * 360 + (month - 1) * 30 + day - 1,
+        }
+    }
+
+    /// Inverse of `days`
+    fn date(self, days: i64) -> (i64, i64, i64) {
+        let in_year = |days: i64, len: i64, months: &[i64]| {
+            let year = days.div_euclid(len);
+            let mut rest = days.rem_euclid(len);
+            let mut month = 1;
+            for &m in months {
+                if rest < m {
+                    break;
+                }
+                rest -= m;
+                month += 1;
+            }
+            (year, month, rest + 1)
+        };
+        match self {
+            Self::Gregorian => {
+                let z = days - 60;
+                let era = z.div_euclid(146_097);
+                let doe = z - era * 146_097;
+                let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+                let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+                let mp = (5 * doy + 2) / 153;
+                let day = doy - (153 * mp + 2) / 5 + 1;
+                let month = if mp < 10 { mp + 3 } else { mp - 9 };
+                let year = yoe + era * 400 + i64::from(month <= 2);
+                (year, month, day)
+            }
+            Self::NoLeap => in_year(days, 365, &MONTHS),
+            Self::AllLeap => in_year(days, 366, &MONTHS_LEAP),
+            Self::Day360 => in_year(days, 360, &[30; 12]),
+        }
+    }
+}
+
+/// Units of a time coordinate
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Units {
+    calendar: Calendar,
+    /// Length of one unit in seconds
+    seconds: f64,
+    /// Reference date in days since 0000-01-01
+    days: i64,
+    /// Time of day of the reference date in seconds
+    time: f64,
+}
+
+impl Units {
+    /// Units given as `<unit> since <date>`, with an optional time
+    /// of day. Returns `None` for other units or unknown calendars
+    pub(crate) fn parse(units: &str, calendar: &str) -> Option<Self> {
+        let calendar = Calendar::from_name(calendar)?;
+        let mut words = units.split_whitespace();
+        let seconds = match words.next()?.to_ascii_lowercase().as_str() {
+            "milliseconds" | "millisecond" | "msecs" | "msec" | "ms" => 0.001,
+            "seconds" | "second" | "secs" | "sec" | "s" => 1.0,
+            "minutes" | "minute" | "mins" | "min" => 60.0,
+            "hours" | "hour" | "hrs" | "hr" | "h" => 3600.0,
+            "days" | "day" | "d" => 86400.0,
+            _ => return None,
+        };
+        if !words.next()?.eq_ignore_ascii_case("since") {
+            return None;
+        }
+        let date = words.next()?;
+        // ISO 8601 dates can have the time after a `T`
+        let (date, time) = match date.split_once('T') {
+            Some((date, time)) => (date, Some(time)),
+            None => (date, words.next()),
+        };
+
+        let (negative, date) = match date.strip_prefix('-') {
+            Some(date) => (true, date),
+            None => (false, date),
+        };
+        let mut parts = date.split('-').map(str::parse::<i64>);
+        let year = parts.next()?.ok()?;
+        let year = if negative { -year } else { year };
+        let month = parts.next().unwrap_or(Ok(1)).ok()?;
+        let day = parts.next().unwrap_or(Ok(1)).ok()?;
+        if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        let time = match time {
+            // Only UTC is supported as time zone
+            Some(time) => {
+                let time = time.trim_end_matches(['Z', 'z']);
+                let time = time.strip_suffix("+00:00").unwrap_or(time);
+                let mut parts = time.split(':').map(str::parse::<f64>);
+                let hours = parts.next()?.ok()?;
+                let minutes = parts.next().unwrap_or(Ok(0.0)).ok()?;
+                let seconds = parts.next().unwrap_or(Ok(0.0)).ok()?;
+                hours * 3600.0 + minutes * 60.0 + seconds
+            }
+            None => 0.0,
+        };
+        Some(Self {
+            calendar,
+            seconds,
+            days: calendar.days(year, month, day),
+            time,
+        })
+    }
+
+    /// Date of `value`, omitting a time of day at midnight
+    /// and trailing zero minutes or seconds
+    pub(crate) fn format(&self, value: f64) -> String {
+        let seconds = self.time + value * self.seconds;
+        // Rounded to milliseconds
+        let millis = (seconds * 1000.0).round() as i64;
+        let days = self.days + millis.div_euclid(86_400_000);
+        let millis = millis.rem_euclid(86_400_000);
+        let (year, month, day) = self.calendar.date(days);
+        let mut s = if year < 0 {
+            format!("-{:04}-{:02}-{:02}", -year, month, day)
+        } else {
+            format!("{:04}-{:02}-{:02}", year, month, day)
+        };
+        if millis == 0 {
+            return s;
+        }
+        let (hours, minutes) = (millis / 3_600_000, millis / 60_000 % 60);
+        let (secs, millis) = (millis / 1000 % 60, millis % 1000);
+        s.push_str(&format!(" {:02}", hours));
+        if minutes != 0 || secs != 0 || millis != 0 {
+            s.push_str(&format!(":{:02}", minutes));
+        }
+        if secs != 0 || millis != 0 {
+            s.push_str(&format!(":{:02}", secs));
+        }
+        if millis != 0 {
+            s.push_str(format!(".{:03}", millis).trim_end_matches('0'));
+        }
+        s
+    }
+}
+
+#[test]
+fn cf_times() {
+    let units = Units::parse("days since 2000-01-01", "standard").unwrap();
+    assert_eq!(units.format(0.0), "2000-01-01");
+    assert_eq!(units.format(59.25), "2000-02-29 06");
+    assert_eq!(units.format(366.5), "2001-01-01 12");
+    assert_eq!(units.format(-1.0), "1999-12-31");
+    let units = Units::parse("hours since 1970-1-1 00:00:00", "gregorian").unwrap();
+    assert_eq!(units.format(24.0 * 365.0 + 1.5), "1971-01-01 01:30");
+    let units = Units::parse("seconds since 2020-03-01T12:00:00Z", "standard").unwrap();
+    assert_eq!(units.format(1.5), "2020-03-01 12:00:01.5");
+    let units = Units::parse("days since 2001-01-01", "noleap").unwrap();
+    assert_eq!(units.format(59.0), "2001-03-01");
+    assert_eq!(units.format(365.0 * 3.0 + 59.0), "2004-03-01");
+    let units = Units::parse("days since 2000-01-01", "360_day").unwrap();
+    assert_eq!(units.format(59.0), "2000-02-30");
+    assert!(Units::parse("m", "standard").is_none());
+    assert!(Units::parse("days since 2000-01-01", "julian").is_none());
+}
@@ -0,0 +1,616 @@
+//! Comparison of two datasets, like `nccmp`
+//!
+//! Differences in the structure (groups, dimensions, variables,
+//! attributes and types) and in the values of variables are
+//! reported as a list of [`Difference`]s
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let expected = netcdf::open("expected.nc")?;
+//! let output = netcdf::open("output.nc")?;
+//! let options = netcdf::compare::Options {
+//!     absolute_tolerance: 1e-6,
+//!     exclude_attributes: vec!["history".into()],
+//!     ..Default::default()
+//! };
+//! for difference in netcdf::compare::compare(&expected, &output, &options)? {
+//!     println!("{}", difference);
+//! }
+//! # Ok(()) }
+//! ```
+
+use super::attribute::{AttrValue, Attribute, AttributeIterator};
+use super::cdl;
+use super::convert;
+use super::dimension::{self, Dimension};
+use super::error;
+use super::file::File;
+use super::group;
+use super::variable::{self, Numeric, Variable};
+use super::LOCK;
+use netcdf_sys::*;
+
+/// Number of values read at once from each variable
+const BLOCK_LEN: usize = 1 << 20;
+
+/// Options for [`compare`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Compare the values of variables, and not only the metadata
+    pub data: bool,
+    /// Values differing by at most this are equal
+    pub absolute_tolerance: f64,
+    /// Values differing by at most this fraction of
+    /// the larger magnitude of the two are equal
+    pub relative_tolerance: f64,
+    /// Only compare these variables, all variables if empty
+    pub variables: Vec<String>,
+    /// Attributes which are not compared, such as `history`
+    pub exclude_attributes: Vec<String>,
+    /// Report at most this many differing values per variable
+    pub max_value_differences: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            data: true,
+            absolute_tolerance: 0.0,
+            relative_tolerance: 0.0,
+            variables: Vec::new(),
+            exclude_attributes: Vec::new(),
+            max_value_differences: None,
+        }
+    }
+}
+
+impl Options {
+    /// Whether the values are equal within the tolerances
+    fn equal(&self, a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Number(a), Value::Number(b)) => {
+                if a == b || (a.is_nan() && b.is_nan()) {
+                    return true;
+                }
+                let diff = (a - b).abs();
+                diff <= self.absolute_tolerance
+                    || diff <= self.relative_tolerance * a.abs().max(b.abs())
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// One of the compared datasets
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    /// The first dataset
+    First,
+    /// The second dataset
+    Second,
+}
+
+/// Value of a single element of a variable
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    /// Equal to the fill value of the variable
+    Fill,
+    /// Numeric value, converted to `f64`
+    Number(f64),
+    /// Character or string value
+    Text(String),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Fill => write!(f, "_"),
+            Self::Number(x) => write!(f, "{}", x),
+            Self::Text(x) => write!(f, "{:?}", x),
+        }
+    }
+}
+
+/// A difference between the datasets.
+///
+/// Paths are given as `/group/name`, and attributes are appended
+/// after a colon, as `/group/variable:units` or `/group:title`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difference {
+    /// Group, dimension, variable or attribute only in one of the datasets
+    Missing {
+        /// Path of the missing item
+        path: String,
+        /// Dataset containing the item
+        only_in: Side,
+    },
+    /// Dimensions of different length
+    DimensionLength {
+        /// Path of the dimension
+        path: String,
+        /// Length in the first dataset
+        first: usize,
+        /// Length in the second dataset
+        second: usize,
+    },
+    /// Dimension which is unlimited in only one of the datasets
+    Unlimited {
+        /// Path of the dimension
+        path: String,
+        /// Unlimited in the first dataset
+        first: bool,
+        /// Unlimited in the second dataset
+        second: bool,
+    },
+    /// Variables of different types
+    VariableType {
+        /// Path of the variable
+        path: String,
+        /// Type in the first dataset
+        first: nc_type,
+        /// Type in the second dataset
+        second: nc_type,
+    },
+    /// Variables defined over different dimensions
+    VariableDimensions {
+        /// Path of the variable
+        path: String,
+        /// Dimension names in the first dataset
+        first: Vec<String>,
+        /// Dimension names in the second dataset
+        second: Vec<String>,
+    },
+    /// Attributes of different value
+    AttributeValue {
+        /// Path of the attribute
+        path: String,
+        /// Value in the first dataset
+        first: AttrValue,
+        /// Value in the second dataset
+        second: AttrValue,
+    },
+    /// Attributes which are displayed the same but have different
+    /// types, such as text stored as `NC_CHAR` and as `NC_STRING`
+    AttributeType {
+        /// Path of the attribute
+        path: String,
+        /// Type in the first dataset
+        first: nc_type,
+        /// Type in the second dataset
+        second: nc_type,
+    },
+    /// Values of a variable differing by more than the tolerances
+    Value {
+        /// Path of the variable
+        path: String,
+        /// Index of the element
+        index: Vec<usize>,
+        /// Value in the first dataset
+        first: Value,
+        /// Value in the second dataset
+        second: Value,
+    },
+}
+
+impl Difference {
+    /// Path of the group, dimension, variable or attribute
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Missing { path, .. }
+            | Self::DimensionLength { path, .. }
+            | Self::Unlimited { path, .. }
+            | Self::VariableType { path, .. }
+            | Self::VariableDimensions { path, .. }
+            | Self::AttributeValue { path, .. }
+            | Self::AttributeType { path, .. }
+            | Self::Value { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let type_name = |x: nc_type| cdl::type_name(x).map_or_else(|_| x.to_string(), Into::into);
+        match self {
+            Self::Missing { path, only_in } => {
+                let side = match only_in {
+                    Side::First => "first",
+                    Side::Second => "second",
+                };
+                write!(f, "{}: only in {} dataset", path, side)
+            }
+            Self::DimensionLength {
+                path,
+                first,
+                second,
+            } => write!(f, "{}: length {} != {}", path, first, second),
+            Self::Unlimited {
+                path,
+                first,
+                second,
+            } => write!(f, "{}: unlimited {} != {}", path, first, second),
+            Self::VariableType {
+                path,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}: type {} != {}",
+                path,
+                type_name(*first),
+                type_name(*second)
+            ),
+            Self::VariableDimensions {
+                path,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}: dimensions ({}) != ({})",
+                path,
+                first.join(", "),
+                second.join(", ")
+            ),
+            Self::AttributeValue {
+                path,
+                first,
+                second,
+            } => write!(f, "{}: {} != {}", path, first, second),
+            Self::AttributeType {
+                path,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}: type {} != {}",
+                path,
+                type_name(*first),
+                type_name(*second)
+            ),
+            Self::Value {
+                path,
+                index,
+                first,
+                second,
+            } => write!(f, "{}{:?}: {} != {}", path, index, first, second),
+        }
+    }
+}
+
+/// Compares two files, including all groups
+///
+/// # Errors
+///
+/// Reading from either file failed
+pub fn compare(first: &File, second: &File, options: &Options) -> error::Result<Vec<Difference>> {
+    let mut differences = Vec::new();
+    compare_groups(first.ncid(), second.ncid(), "/", options, &mut differences)?;
+    Ok(differences)
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", path, name)
+    }
+}
+
+/// Calls `f` with the items with the same name, and
+/// reports the items which are only in one of the lists
+fn pair_up<T, F>(
+    first: &[T],
+    second: &[T],
+    name: impl Fn(&T) -> String,
+    path: impl Fn(&str) -> String,
+    differences: &mut Vec<Difference>,
+    mut f: F,
+) -> error::Result<()>
+where
+    F: FnMut(&T, &T, &str, &mut Vec<Difference>) -> error::Result<()>,
+{
+    for a in first {
+        let n = name(a);
+        match second.iter().find(|b| name(b) == n) {
+            Some(b) => f(a, b, &path(&n), differences)?,
+            None => differences.push(Difference::Missing {
+                path: path(&n),
+                only_in: Side::First,
+            }),
+        }
+    }
+    for b in second {
+        let n = name(b);
+        if first.iter().all(|a| name(a) != n) {
+            differences.push(Difference::Missing {
+                path: path(&n),
+                only_in: Side::Second,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn compare_groups(
+    first: nc_type,
+    second: nc_type,
+    path: &str,
+    options: &Options,
+    differences: &mut Vec<Difference>,
+) -> error::Result<()> {
+    let dims = |ncid| -> error::Result<Vec<Dimension>> {
+        dimension::dimensions_from_location(ncid)?.collect()
+    };
+    pair_up(
+        &dims(first)?,
+        &dims(second)?,
+        Dimension::name,
+        |name| join(path, name),
+        differences,
+        |a, b, path, differences| {
+            if a.len() != b.len() {
+                differences.push(Difference::DimensionLength {
+                    path: path.into(),
+                    first: a.len(),
+                    second: b.len(),
+                });
+            }
+            let unlimited_a = dimension::is_unlimited_at(a.id.ncid, a.id.dimid)?;
+            let unlimited_b = dimension::is_unlimited_at(b.id.ncid, b.id.dimid)?;
+            if unlimited_a != unlimited_b {
+                differences.push(Difference::Unlimited {
+                    path: path.into(),
+                    first: unlimited_a,
+                    second: unlimited_b,
+                });
+            }
+            Ok(())
+        },
+    )?;
+
+    compare_attributes(first, second, None, path, options, differences)?;
+
+    let vars = |ncid| -> error::Result<Vec<Variable>> {
+        variable::variables_at_ncid(ncid)?
+            .filter(|v| {
+                v.as_ref().map_or(true, |v| {
+                    options.variables.is_empty() || options.variables.contains(&v.name())
+                })
+            })
+            .collect()
+    };
+    pair_up(
+        &vars(first)?,
+        &vars(second)?,
+        Variable::name,
+        |name| join(path, name),
+        differences,
+        |a, b, path, differences| compare_variables(a, b, path, options, differences),
+    )?;
+
+    let groups = |ncid| -> error::Result<Vec<group::Group>> {
+        if convert::format_at(ncid)? == convert::Format::Netcdf4 {
+            Ok(group::groups_at_ncid(ncid)?.collect())
+        } else {
+            Ok(Vec::new())
+        }
+    };
+    pair_up(
+        &groups(first)?,
+        &groups(second)?,
+        group::Group::name,
+        |name| join(path, name),
+        differences,
+        |a, b, path, differences| compare_groups(a.ncid, b.ncid, path, options, differences),
+    )
+}
+
+/// Compares the attributes of groups, or of variables if `varids` are given
+fn compare_attributes(
+    first: nc_type,
+    second: nc_type,
+    varids: Option<(nc_type, nc_type)>,
+    path: &str,
+    options: &Options,
+    differences: &mut Vec<Difference>,
+) -> error::Result<()> {
+    let atts = |ncid, varid| -> error::Result<Vec<Attribute>> {
+        // Need to lock when reading the first attribute
+        let _l = LOCK.lock();
+        AttributeIterator::new(ncid, varid)?
+            .filter(|a| {
+                a.as_ref().map_or(true, |a| {
+                    !options.exclude_attributes.iter().any(|x| x == a.name())
+                })
+            })
+            .collect()
+    };
+    pair_up(
+        &atts(first, varids.map(|v| v.0))?,
+        &atts(second, varids.map(|v| v.1))?,
+        |a| a.name().into(),
+        |name| format!("{}:{}", path, name),
+        differences,
+        |a, b, path, differences| {
+            let (types, values) = ((a.typ()?, b.typ()?), (a.value()?, b.value()?));
+            // The same text or number in another type,
+            // such as `NC_CHAR` and a single `NC_STRING`
+            let same_text = values.0.to_string() == values.1.to_string();
+            if types.0 != types.1 && same_text {
+                differences.push(Difference::AttributeType {
+                    path: path.into(),
+                    first: types.0,
+                    second: types.1,
+                });
+            } else if values.0 != values.1 || types.0 != types.1 {
+                differences.push(Difference::AttributeValue {
+                    path: path.into(),
+                    first: values.0,
+                    second: values.1,
+                });
+            }
+            Ok(())
+        },
+    )
+}
+
+fn compare_variables(
+    a: &Variable,
+    b: &Variable,
+    path: &str,
+    options: &Options,
+    differences: &mut Vec<Difference>,
+) -> error::Result<()> {
+    let mut comparable = true;
+    if a.vartype() != b.vartype() {
+        comparable = false;
+        differences.push(Difference::VariableType {
+            path: path.into(),
+            first: a.vartype(),
+            second: b.vartype(),
+        });
+    }
+    let names = |v: &Variable| v.dimensions().iter().map(Dimension::name).collect();
+    let (dims_a, dims_b): (Vec<String>, Vec<String>) = (names(a), names(b));
+    if dims_a != dims_b {
+        comparable = false;
+        differences.push(Difference::VariableDimensions {
+            path: path.into(),
+            first: dims_a,
+            second: dims_b,
+        });
+    }
+    compare_attributes(
+        a.ncid,
+        b.ncid,
+        Some((a.varid, b.varid)),
+        path,
+        options,
+        differences,
+    )?;
+
+    let shape = |v: &Variable| v.dimensions().iter().map(Dimension::len).collect();
+    let shape_a: Vec<usize> = shape(a);
+    if !options.data || !comparable || shape_a != shape(b) {
+        return Ok(());
+    }
+    let max = options.max_value_differences.unwrap_or(usize::MAX);
+    let mut found = 0;
+    convert::for_each_block(&shape_a, BLOCK_LEN, |start, count| {
+        if found >= max {
+            return Ok(());
+        }
+        let values = read_block(a, start, count)?
+            .into_iter()
+            .zip(read_block(b, start, count)?);
+        for (i, (x, y)) in values.enumerate() {
+            if options.equal(&x, &y) {
+                continue;
+            }
+            if found >= max {
+                break;
+            }
+            found += 1;
+            differences.push(Difference::Value {
+                path: path.into(),
+                index: unravel(start, count, i),
+                first: x,
+                second: y,
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Index of the `i`th element of the hyperslab
+fn unravel(start: &[usize], count: &[usize], mut i: usize) -> Vec<usize> {
+    let mut index = start.to_vec();
+    for (x, &c) in index.iter_mut().zip(count).rev() {
+        *x += i % c;
+        i /= c;
+    }
+    index
+}
+
+fn read_block(var: &Variable, start: &[usize], count: &[usize]) -> error::Result<Vec<Value>> {
+    match var.vartype() {
+        NC_BYTE => numeric_block::<i8, _>(var, start, count, f64::from),
+        NC_UBYTE => numeric_block::<u8, _>(var, start, count, f64::from),
+        NC_SHORT => numeric_block::<i16, _>(var, start, count, f64::from),
+        NC_USHORT => numeric_block::<u16, _>(var, start, count, f64::from),
+        NC_INT => numeric_block::<i32, _>(var, start, count, f64::from),
+        NC_UINT => numeric_block::<u32, _>(var, start, count, f64::from),
+        NC_INT64 => numeric_block::<i64, _>(var, start, count, |x| x as f64),
+        NC_UINT64 => numeric_block::<u64, _>(var, start, count, |x| x as f64),
+        NC_FLOAT => numeric_block::<f32, _>(var, start, count, f64::from),
+        NC_DOUBLE => numeric_block::<f64, _>(var, start, count, |x| x),
+        NC_STRING => Ok(var
+            .string_values(Some(start), Some(count))?
+            .into_iter()
+            .map(Value::Text)
+            .collect()),
+        NC_CHAR => {
+            let mut buf = vec![0_u8; count.iter().product()];
+            unsafe {
+                let _l = LOCK.lock();
+                error::checked(nc_get_vara_text(
+                    var.ncid,
+                    var.varid,
+                    start.as_ptr(),
+                    count.as_ptr(),
+                    buf.as_mut_ptr() as *mut _,
+                ))?;
+            }
+            Ok(buf
+                .into_iter()
+                .map(|c| Value::Text(char::from(c).to_string()))
+                .collect())
+        }
+        x => Err(error::Error::TypeUnknown(x)),
+    }
+}
+
+fn numeric_block<T, F>(
+    var: &Variable,
+    start: &[usize],
+    count: &[usize],
+    f: F,
+) -> error::Result<Vec<Value>>
+where
+    T: Numeric + Copy + Default + PartialEq,
+    F: Fn(T) -> f64,
+{
+    let mut values = vec![T::default(); count.iter().product()];
+    var.values_to(&mut values, Some(start), Some(count))?;
+    let fill = var.fill_value::<T>()?;
+    Ok(values
+        .into_iter()
+        .map(|x| {
+            if Some(x) == fill {
+                Value::Fill
+            } else {
+                Value::Number(f(x))
+            }
+        })
+        .collect())
+}
+
+#[test]
+fn tolerances() {
+    let options = Options {
+        absolute_tolerance: 0.1,
+        relative_tolerance: 0.01,
+        ..Default::default()
+    };
+    let equal = |a, b| options.equal(&Value::Number(a), &Value::Number(b));
+    assert!(equal(1.0, 1.05));
+    assert!(!equal(1.0, 1.2));
+    assert!(equal(1000.0, 1009.0));
+    assert!(!equal(1000.0, 1011.0));
+    assert!(equal(f64::NAN, f64::NAN));
+    assert!(!options.equal(&Value::Fill, &Value::Number(0.0)));
+    assert_eq!(unravel(&[1, 0], &[2, 3], 4), vec![2, 1]);
+}
@@ -0,0 +1,453 @@
+//! Copy a dataset into a new file, changing the format, chunking
+//! or compression on the way (similar to `nccopy`)
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use netcdf::convert::{Format, Options};
+//!
+//! let file = netcdf::open("input.nc")?;
+//! let options = Options {
+//!     format: Format::Cdf5,
+//!     ..Options::default()
+//! };
+//! netcdf::convert::convert(&file, "output.nc", &options)?;
+//! # Ok(()) }
+//! ```
+
+use super::attribute::AttributeIterator;
+use super::dimension::{self, Dimension};
+use super::error;
+use super::file::{File, MutableFile, RawFile};
+use super::group::{self, GroupMut};
+use super::variable::{self, Numeric, Variable, VariableMut};
+use super::LOCK;
+use netcdf_sys::*;
+use std::collections::HashMap;
+
+/// Format of a file on disk
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// The original format (`CDF-1`)
+    Classic,
+    /// Classic format with 64-bit offsets (`CDF-2`)
+    Offset64,
+    /// Classic format with 64-bit data, allowing large
+    /// variables and unsigned types (`CDF-5`)
+    Cdf5,
+    /// `netCDF-4`, based on `HDF5`
+    Netcdf4,
+    /// `netCDF-4`, restricted to the classic data model
+    Netcdf4Classic,
+}
+
+impl Format {
+    /// Mode flag for `nc_create`
+    fn cmode(self) -> nc_type {
+        match self {
+            Self::Classic => 0,
+            Self::Offset64 => NC_64BIT_OFFSET,
+            Self::Cdf5 => NC_64BIT_DATA,
+            Self::Netcdf4 => NC_NETCDF4,
+            Self::Netcdf4Classic => NC_NETCDF4 | NC_CLASSIC_MODEL,
+        }
+    }
+    /// Whether chunking and compression is supported
+    fn is_netcdf4(self) -> bool {
+        match self {
+            Self::Netcdf4 | Self::Netcdf4Classic => true,
+            Self::Classic | Self::Offset64 | Self::Cdf5 => false,
+        }
+    }
+}
+
+/// Names as given by `ncdump -k`
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Self::Classic => "classic",
+            Self::Offset64 => "64-bit offset",
+            Self::Cdf5 => "cdf5",
+            Self::Netcdf4 => "netCDF-4",
+            Self::Netcdf4Classic => "netCDF-4 classic model",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Format of the file containing `ncid`
+pub(crate) fn format_at(ncid: nc_type) -> error::Result<Format> {
+    let mut format = 0;
+    unsafe {
+        error::checked(nc_inq_format(ncid, &mut format))?;
+    }
+    Ok(match format {
+        NC_FORMAT_CLASSIC => Format::Classic,
+        NC_FORMAT_64BIT => Format::Offset64,
+        NC_FORMAT_CDF5 => Format::Cdf5,
+        NC_FORMAT_NETCDF4 => Format::Netcdf4,
+        NC_FORMAT_NETCDF4_CLASSIC => Format::Netcdf4Classic,
+        x => return Err(error::Error::Str(format!("unknown format {}", x))),
+    })
+}
+
+/// Library layer through which a file is accessed,
+/// as given by `nc_inq_format_extended`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExtendedFormat {
+    /// Classic formats, read by the `netCDF-3` library
+    Nc3,
+    /// `HDF5` files, including all `netCDF-4` files
+    Hdf5,
+    /// `HDF4` files
+    Hdf4,
+    /// Classic formats accessed in parallel through `PnetCDF`
+    Pnetcdf,
+    /// Remote datasets through `DAP2`
+    Dap2,
+    /// Remote datasets through `DAP4`
+    Dap4,
+    /// First user defined format
+    Udf0,
+    /// Second user defined format
+    Udf1,
+    /// Zarr stores through `NCZarr`
+    Nczarr,
+    /// A format unknown to this crate
+    Other(nc_type),
+}
+
+/// Names as given by `ncdump -K`
+impl std::fmt::Display for ExtendedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Nc3 => write!(f, "nc3"),
+            Self::Hdf5 => write!(f, "hdf5"),
+            Self::Hdf4 => write!(f, "hdf4"),
+            Self::Pnetcdf => write!(f, "pnetcdf"),
+            Self::Dap2 => write!(f, "dap2"),
+            Self::Dap4 => write!(f, "dap4"),
+            Self::Udf0 => write!(f, "udf0"),
+            Self::Udf1 => write!(f, "udf1"),
+            Self::Nczarr => write!(f, "nczarr"),
+            Self::Other(x) => write!(f, "unknown ({})", x),
+        }
+    }
+}
+
+/// Extended format and open mode of the file containing `ncid`
+pub(crate) fn format_extended_at(ncid: nc_type) -> error::Result<(ExtendedFormat, nc_type)> {
+    let (mut format, mut mode) = (0, 0);
+    unsafe {
+        error::checked(nc_inq_format_extended(ncid, &mut format, &mut mode))?;
+    }
+    let format = match format {
+        NC_FORMATX_NC3 => ExtendedFormat::Nc3,
+        NC_FORMATX_NC_HDF5 => ExtendedFormat::Hdf5,
+        NC_FORMATX_NC_HDF4 => ExtendedFormat::Hdf4,
+        NC_FORMATX_PNETCDF => ExtendedFormat::Pnetcdf,
+        NC_FORMATX_DAP2 => ExtendedFormat::Dap2,
+        NC_FORMATX_DAP4 => ExtendedFormat::Dap4,
+        NC_FORMATX_UDF0 => ExtendedFormat::Udf0,
+        NC_FORMATX_UDF1 => ExtendedFormat::Udf1,
+        NC_FORMATX_NCZARR => ExtendedFormat::Nczarr,
+        x => ExtendedFormat::Other(x),
+    };
+    Ok((format, mode))
+}
+
+/// Options for [`convert`]
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Format of the new file
+    pub format: Format,
+    /// Chunk length along dimensions, given by name. Dimensions not
+    /// in this map are not split (unlimited dimensions use a length of 1).
+    /// Variables without any of these dimensions keep the default chunking
+    pub chunking: HashMap<String, usize>,
+    /// Deflate level (1-9) for all variables
+    pub deflate: Option<nc_type>,
+    /// Enable the shuffle filter, improving compression
+    pub shuffle: bool,
+    /// Maximum number of values of a variable held in memory at once
+    pub buffer_len: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            format: Format::Netcdf4,
+            chunking: HashMap::new(),
+            deflate: None,
+            shuffle: false,
+            buffer_len: 1 << 20,
+        }
+    }
+}
+
+/// Copies all dimensions, attributes, variables and groups of `file`
+/// into a new file at `path`. Data is copied in blocks of at most
+/// `options.buffer_len` values.
+///
+/// Chunking and compression are only applied when the new
+/// file is in one of the `netCDF-4` formats.
+///
+/// # Errors
+///
+/// The contents can not be represented in the new format (e.g. groups
+/// or strings in a classic file), invalid chunking or netcdf errors
+pub fn convert<P>(file: &File, path: P, options: &Options) -> error::Result<MutableFile>
+where
+    P: AsRef<std::path::Path>,
+{
+    let out = RawFile::create_with(path.as_ref(), options.format.cmode())?;
+    {
+        let _l = LOCK.lock();
+        define(file.ncid(), out.ncid(), options)?;
+        // Leave define mode, required for the classic formats
+        unsafe {
+            error::checked(nc_enddef(out.ncid()))?;
+        }
+    }
+    copy_data(file.ncid(), out.ncid(), options.buffer_len)?;
+    Ok(out)
+}
+
+/// Defines the contents of the group `src` in `dst`
+fn define(src: nc_type, dst: nc_type, options: &Options) -> error::Result<()> {
+    for dim in dimension::dimensions_from_location(src)? {
+        dimension::copy_to(&dim?, dst, false)?;
+    }
+    for att in AttributeIterator::new(src, None)? {
+        att?.copy_to(dst, NC_GLOBAL)?;
+    }
+    for var in variable::variables_at_ncid(src)? {
+        let var = var?;
+        let dims = var
+            .dimensions()
+            .iter()
+            .map(Dimension::name)
+            .collect::<Vec<_>>();
+        let dims = dims.iter().map(String::as_str).collect::<Vec<_>>();
+        let mut newvar = VariableMut::add_from_str(dst, var.vartype(), &var.name(), &dims)?;
+        for att in AttributeIterator::new(var.ncid, Some(var.varid))? {
+            att?.copy_to(newvar.ncid, newvar.varid)?;
+        }
+        if options.format.is_netcdf4() {
+            define_storage(&var, &mut newvar, options)?;
+        }
+    }
+    for g in group::groups_at_ncid(src)? {
+        let newgroup = GroupMut::add_group_at(dst, &g.name())?;
+        define(g.ncid, newgroup.ncid, options)?;
+    }
+    Ok(())
+}
+
+/// Sets chunking and compression of `var`
+fn define_storage(src: &Variable, var: &mut VariableMut, options: &Options) -> error::Result<()> {
+    if var.dimensions().is_empty() {
+        return Ok(());
+    }
+    let names = src
+        .dimensions()
+        .iter()
+        .map(Dimension::name)
+        .collect::<Vec<_>>();
+    if names.iter().any(|n| options.chunking.contains_key(n)) {
+        let chunks = src
+            .dimensions()
+            .iter()
+            .zip(&names)
+            .map(|(d, name)| -> error::Result<usize> {
+                let mut len = 0;
+                unsafe {
+                    error::checked(nc_inq_dimlen(d.id.ncid, d.id.dimid, &mut len))?;
+                }
+                let unlimited = dimension::is_unlimited_at(d.id.ncid, d.id.dimid)?;
+                Ok(match options.chunking.get(name) {
+                    Some(&chunk) if unlimited || len == 0 => chunk.max(1),
+                    Some(&chunk) => chunk.max(1).min(len),
+                    None if unlimited || len == 0 => 1,
+                    None => len,
+                })
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        var.chunking(&chunks)?;
+    }
+    if let Some(level) = options.deflate {
+        unsafe {
+            error::checked(nc_def_var_deflate(
+                var.ncid,
+                var.varid,
+                options.shuffle as _,
+                true as _,
+                level,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the data of all variables in `src` to `dst`, which must
+/// contain variables with the same names
+fn copy_data(src: nc_type, dst: nc_type, buffer_len: usize) -> error::Result<()> {
+    for var in variable::variables_at_ncid(src)? {
+        let var = var?;
+        let name = var.name();
+        let mut newvar = match Variable::find_from_name(dst, &name)? {
+            Some(v) => VariableMut(v, std::marker::PhantomData),
+            None => return Err(error::Error::NotFound(format!("variable {}", name))),
+        };
+        copy_values(&var, &mut newvar, buffer_len)?;
+    }
+    for g in group::groups_at_ncid(src)? {
+        let name = g.name();
+        match group::group_from_name(dst, &name)? {
+            Some(newgroup) => copy_data(g.ncid, newgroup.ncid, buffer_len)?,
+            None => return Err(error::Error::NotFound(format!("group {}", name))),
+        }
+    }
+    Ok(())
+}
+
+/// Copies all values of `src` into `dst`, using the type of `src`
+fn copy_values(src: &Variable, dst: &mut VariableMut, buffer_len: usize) -> error::Result<()> {
+    match src.vartype() {
+        NC_BYTE => copy_numeric::<i8>(src, dst, buffer_len),
+        NC_UBYTE => copy_numeric::<u8>(src, dst, buffer_len),
+        NC_SHORT => copy_numeric::<i16>(src, dst, buffer_len),
+        NC_USHORT => copy_numeric::<u16>(src, dst, buffer_len),
+        NC_INT => copy_numeric::<i32>(src, dst, buffer_len),
+        NC_UINT => copy_numeric::<u32>(src, dst, buffer_len),
+        NC_INT64 => copy_numeric::<i64>(src, dst, buffer_len),
+        NC_UINT64 => copy_numeric::<u64>(src, dst, buffer_len),
+        NC_FLOAT => copy_numeric::<f32>(src, dst, buffer_len),
+        NC_DOUBLE => copy_numeric::<f64>(src, dst, buffer_len),
+        NC_STRING => for_each_block(&shape(src), buffer_len, |start, count| {
+            let values = src.string_values(Some(start), Some(count))?;
+            dst.put_string_values(&values, Some(start), Some(count))
+        }),
+        NC_CHAR => {
+            let mut buffer = Vec::new();
+            for_each_block(&shape(src), buffer_len, |start, count| {
+                buffer.resize(count.iter().product(), 0_u8);
+                unsafe {
+                    let _l = LOCK.lock();
+                    error::checked(nc_get_vara_text(
+                        src.ncid,
+                        src.varid,
+                        start.as_ptr(),
+                        count.as_ptr(),
+                        buffer.as_mut_ptr() as *mut _,
+                    ))?;
+                    error::checked(nc_put_vara_text(
+                        dst.ncid,
+                        dst.varid,
+                        start.as_ptr(),
+                        count.as_ptr(),
+                        buffer.as_ptr() as *const _,
+                    ))
+                }
+            })
+        }
+        x => Err(error::Error::TypeUnknown(x)),
+    }
+}
+
+fn copy_numeric<T>(src: &Variable, dst: &mut VariableMut, buffer_len: usize) -> error::Result<()>
+where
+    T: Numeric + Copy + Default,
+{
+    let mut buffer = Vec::new();
+    for_each_block(&shape(src), buffer_len, |start, count| {
+        buffer.resize(count.iter().product(), T::default());
+        src.values_to(&mut buffer, Some(start), Some(count))?;
+        dst.put_values(&buffer, Some(start), Some(count))
+    })
+}
+
+/// Current length of all dimensions of `var`
+fn shape(var: &Variable) -> Vec<usize> {
+    var.dimensions().iter().map(Dimension::len).collect()
+}
+
+/// Splits a hyperslab of `shape` into blocks of at most `max_len` values
+/// and calls `f` with the start and count of each block.
+/// Blocks span the innermost dimensions completely whenever possible
+pub(crate) fn for_each_block<F>(shape: &[usize], max_len: usize, mut f: F) -> error::Result<()>
+where
+    F: FnMut(&[usize], &[usize]) -> error::Result<()>,
+{
+    if shape.contains(&0) {
+        return Ok(());
+    }
+    let max_len = max_len.max(1);
+
+    // Find the outermost axis where all inner axes fit in a block
+    let mut inner = 1_usize;
+    let mut axis = shape.len();
+    while axis > 0 && inner.saturating_mul(shape[axis - 1]) <= max_len {
+        axis -= 1;
+        inner *= shape[axis];
+    }
+    if axis == 0 {
+        return f(&vec![0; shape.len()], shape);
+    }
+    // This axis is split into steps, all outer axes are taken one at a time
+    let axis = axis - 1;
+    let step = max_len / inner;
+
+    let mut start = vec![0; shape.len()];
+    let mut count = shape.to_vec();
+    for c in &mut count[..axis] {
+        *c = 1;
+    }
+    loop {
+        count[axis] = step.min(shape[axis] - start[axis]);
+        f(&start, &count)?;
+
+        start[axis] += count[axis];
+        let mut i = axis;
+        while start[i] >= shape[i] {
+            if i == 0 {
+                return Ok(());
+            }
+            start[i] = 0;
+            i -= 1;
+            start[i] += 1;
+        }
+    }
+}
+
+#[test]
+fn blocks() {
+    let mut blocks = Vec::new();
+    for_each_block(&[3, 4, 5], 10, |start, count| {
+        blocks.push((start.to_vec(), count.to_vec()));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(blocks.len(), 3 * 2);
+    assert_eq!(blocks[0], (vec![0, 0, 0], vec![1, 2, 5]));
+    assert_eq!(blocks[1], (vec![0, 2, 0], vec![1, 2, 5]));
+    assert_eq!(blocks[2], (vec![1, 0, 0], vec![1, 2, 5]));
+
+    let mut blocks = Vec::new();
+    for_each_block(&[3, 7], 4, |start, count| {
+        blocks.push((start.to_vec(), count.to_vec()));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(blocks.len(), 3 * 2);
+    assert_eq!(blocks[1], (vec![0, 4], vec![1, 3]));
+
+    let mut n = 0;
+    for_each_block(&[], 4, |start, count| {
+        assert!(start.is_empty() && count.is_empty());
+        n += 1;
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(n, 1);
+    for_each_block(&[0, 3], 4, |_, _| panic!("empty variable")).unwrap();
+}
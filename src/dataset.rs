@@ -0,0 +1,384 @@
+//! Reading and writing whole datasets as structs
+//!
+//! With the `derive` feature, [`NetcdfDataset`] can be derived for
+//! structs where each field is a variable, an attribute or a dimension:
+//!
+//! ```ignore
+//! use netcdf::NetcdfDataset;
+//!
+//! #[derive(NetcdfDataset)]
+//! struct Forecast {
+//!     #[netcdf(dimension, unlimited)]
+//!     time: usize,
+//!     #[netcdf(dimension)]
+//!     x: usize,
+//!     #[netcdf(attribute)]
+//!     title: String,
+//!     #[netcdf(attribute, name = "version")]
+//!     version: Option<i32>,
+//!     #[netcdf(dimensions("time"), units = "hours since 2020-01-01")]
+//!     hours: Vec<f64>,
+//!     #[netcdf(name = "t2m", group = "surface", dimensions("time", "x"))]
+//!     #[netcdf(units = "K", standard_name = "air_temperature", compression = 4)]
+//!     temperature: ndarray::ArrayD<f32>,
+//!     #[netcdf(skip)]
+//!     cache: Vec<u8>,
+//! }
+//!
+//! let forecast = Forecast::from_file(&netcdf::open("forecast.nc")?)?;
+//! forecast.write_to(&mut netcdf::create("copy.nc")?)?;
+//! ```
+//!
+//! Fields are variables by default, read from and written to the
+//! variable of the same name. The field attributes are
+//!
+//! * `name = "..."`: name in the file, instead of the field name
+//! * `group = "a/b"`: path of the group holding the item, which
+//!   can also be given for the whole struct
+//! * `attribute`: the field is an attribute of the group, of any type
+//!   which can be converted to and from [`AttrValue`]
+//! * `dimension`: the field is the length of a dimension, which is
+//!   `unlimited` when given
+//! * `dimensions("a", "b")`: dimensions of a variable, needed for writing
+//! * `units`, `long_name`, `standard_name`: CF attributes added when
+//!   writing a variable
+//! * `compression = level`: deflate level of a written variable
+//! * `skip`: the field is not read or written, and is set to its default
+//!
+//! Variables can be `Vec`s or arrays of numbers, `Vec<String>`,
+//! single numbers or strings, and `Option`s of these for variables
+//! which may be missing. Dimensions are written before attributes,
+//! which are written before variables.
+
+use super::attribute::{AttrValue, Attribute};
+use super::error;
+use super::file::{File, MutableFile};
+use super::group;
+use super::variable::{Numeric, Variable, VariableMut};
+use super::LOCK;
+#[cfg(feature = "ndarray")]
+use ndarray::ArrayD;
+use netcdf_sys::*;
+
+/// A struct which can be read from and written to a file,
+/// usually derived with `#[derive(NetcdfDataset)]`
+pub trait NetcdfDataset: Sized {
+    /// Reads all fields from the file
+    ///
+    /// # Errors
+    ///
+    /// Required items are missing or of the wrong type
+    fn from_file(file: &File) -> error::Result<Self>;
+    /// Defines and writes all fields to the file
+    ///
+    /// # Errors
+    ///
+    /// Items already exist, or the values do not fit the dimensions
+    fn write_to(&self, file: &mut MutableFile) -> error::Result<()>;
+}
+
+/// Types of fields holding the values of a variable
+pub trait VariableField: Sized {
+    /// Type of a newly defined variable
+    fn nctype() -> nc_type;
+    /// Reads the values of the variable
+    ///
+    /// # Errors
+    ///
+    /// The values can not be converted to this type
+    fn read(var: &Variable) -> error::Result<Self>;
+    /// Value of the field when the variable does not exist
+    ///
+    /// # Errors
+    ///
+    /// The variable is required
+    fn missing(name: &str) -> error::Result<Self> {
+        Err(error::Error::NotFound(format!("variable {}", name)))
+    }
+    /// Writes the values to a newly defined variable
+    ///
+    /// # Errors
+    ///
+    /// The values do not fit the dimensions of the variable
+    fn write(&self, var: &mut VariableMut) -> error::Result<()>;
+    /// Whether the variable should be written at all
+    fn is_present(&self) -> bool {
+        true
+    }
+}
+
+/// Types of fields holding the value of an attribute
+pub trait AttributeField: Sized {
+    /// Reads the value of the attribute
+    ///
+    /// # Errors
+    ///
+    /// The value can not be converted to this type
+    fn read(att: &Attribute) -> error::Result<Self>;
+    /// Value of the field when the attribute does not exist
+    ///
+    /// # Errors
+    ///
+    /// The attribute is required
+    fn missing(name: &str) -> error::Result<Self> {
+        Err(error::Error::NotFound(format!("attribute {}", name)))
+    }
+    /// Value to write, nothing is written for `None`
+    fn value(&self) -> Option<AttrValue>;
+}
+
+macro_rules! impl_numeric_fields {
+    ($($t: ty),*) => {$(
+        impl VariableField for $t {
+            fn nctype() -> nc_type {
+                <$t as Numeric>::NCTYPE
+            }
+            fn read(var: &Variable) -> error::Result<Self> {
+                var.value(None)
+            }
+            fn write(&self, var: &mut VariableMut) -> error::Result<()> {
+                var.put_value(*self, None)
+            }
+        }
+        impl VariableField for Vec<$t> {
+            fn nctype() -> nc_type {
+                <$t as Numeric>::NCTYPE
+            }
+            fn read(var: &Variable) -> error::Result<Self> {
+                let mut values = vec![<$t>::default(); var.len()];
+                if !values.is_empty() {
+                    var.values_to(&mut values, None, None)?;
+                }
+                Ok(values)
+            }
+            fn write(&self, var: &mut VariableMut) -> error::Result<()> {
+                if self.is_empty() {
+                    return Ok(());
+                }
+                var.put_values(self, None, None)
+            }
+        }
+        impl AttributeField for $t {
+            fn read(att: &Attribute) -> error::Result<Self> {
+                att.value_as()
+            }
+            fn value(&self) -> Option<AttrValue> {
+                Some((*self).into())
+            }
+        }
+        impl AttributeField for Vec<$t> {
+            fn read(att: &Attribute) -> error::Result<Self> {
+                att.value_as()
+            }
+            fn value(&self) -> Option<AttrValue> {
+                Some(self.clone().into())
+            }
+        }
+    )*};
+}
+impl_numeric_fields!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
+
+#[cfg(feature = "ndarray")]
+impl<T: Numeric + Clone> VariableField for ArrayD<T> {
+    fn nctype() -> nc_type {
+        T::NCTYPE
+    }
+    fn read(var: &Variable) -> error::Result<Self> {
+        var.values(None, None)
+    }
+    fn write(&self, var: &mut VariableMut) -> error::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let values = self.iter().cloned().collect::<Vec<T>>();
+        let indices = vec![0; self.ndim()];
+        var.put_values(&values, Some(&indices), Some(self.shape()))
+    }
+}
+
+impl VariableField for String {
+    fn nctype() -> nc_type {
+        NC_STRING
+    }
+    fn read(var: &Variable) -> error::Result<Self> {
+        var.string_value(None)
+    }
+    fn write(&self, var: &mut VariableMut) -> error::Result<()> {
+        var.put_string(self, None)
+    }
+}
+
+impl VariableField for Vec<String> {
+    fn nctype() -> nc_type {
+        NC_STRING
+    }
+    fn read(var: &Variable) -> error::Result<Self> {
+        match var.vartype() {
+            NC_CHAR => var.text_values(None, None),
+            _ if var.len() == 0 => Ok(Vec::new()),
+            _ => var.string_values(None, None),
+        }
+    }
+    fn write(&self, var: &mut VariableMut) -> error::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        var.put_string_values(self, None, None)
+    }
+}
+
+impl<V: VariableField> VariableField for Option<V> {
+    fn nctype() -> nc_type {
+        V::nctype()
+    }
+    fn read(var: &Variable) -> error::Result<Self> {
+        V::read(var).map(Some)
+    }
+    fn missing(_name: &str) -> error::Result<Self> {
+        Ok(None)
+    }
+    fn write(&self, var: &mut VariableMut) -> error::Result<()> {
+        match self {
+            Some(x) => x.write(var),
+            None => Ok(()),
+        }
+    }
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl AttributeField for String {
+    fn read(att: &Attribute) -> error::Result<Self> {
+        att.value_as()
+    }
+    fn value(&self) -> Option<AttrValue> {
+        Some(self.as_str().into())
+    }
+}
+
+impl AttributeField for Vec<String> {
+    fn read(att: &Attribute) -> error::Result<Self> {
+        att.value_as()
+    }
+    fn value(&self) -> Option<AttrValue> {
+        Some(self.clone().into())
+    }
+}
+
+impl AttributeField for AttrValue {
+    fn read(att: &Attribute) -> error::Result<Self> {
+        att.value()
+    }
+    fn value(&self) -> Option<AttrValue> {
+        Some(self.clone())
+    }
+}
+
+impl<A: AttributeField> AttributeField for Option<A> {
+    fn read(att: &Attribute) -> error::Result<Self> {
+        A::read(att).map(Some)
+    }
+    fn missing(_name: &str) -> error::Result<Self> {
+        Ok(None)
+    }
+    fn value(&self) -> Option<AttrValue> {
+        self.as_ref().and_then(A::value)
+    }
+}
+
+#[doc(hidden)]
+pub fn read_variable<V: VariableField>(file: &File, group: &str, name: &str) -> error::Result<V> {
+    let var = match group::group_from_path(file.ncid(), group)? {
+        Some(g) => Variable::find_from_name(g.ncid, name)?,
+        None => None,
+    };
+    match var {
+        Some(var) => V::read(&var),
+        None => V::missing(name),
+    }
+}
+
+#[doc(hidden)]
+pub fn read_attribute<A: AttributeField>(file: &File, group: &str, name: &str) -> error::Result<A> {
+    let att = match group::group_from_path(file.ncid(), group)? {
+        Some(g) => {
+            let _l = LOCK.lock();
+            Attribute::find_from_name(g.ncid, None, name)?
+        }
+        None => None,
+    };
+    match att {
+        Some(att) => A::read(&att),
+        None => A::missing(name),
+    }
+}
+
+#[doc(hidden)]
+pub fn read_dimension(file: &File, group: &str, name: &str) -> error::Result<usize> {
+    let dim = match group::group_from_path(file.ncid(), group)? {
+        Some(g) => super::dimension::from_name(g.ncid, name)?,
+        None => None,
+    };
+    dim.map(|d| d.len())
+        .ok_or_else(|| error::Error::NotFound(format!("dimension {}", name)))
+}
+
+#[doc(hidden)]
+pub fn write_dimension(
+    file: &mut MutableFile,
+    group: &str,
+    name: &str,
+    len: usize,
+    unlimited: bool,
+) -> error::Result<()> {
+    let _l = LOCK.lock();
+    let ncid = group::add_group_path_at(file.ncid(), group)?.ncid;
+    let len = if unlimited { 0 } else { len };
+    super::dimension::add_dimension_at(ncid, name, len)?;
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn write_attribute<A: AttributeField>(
+    file: &mut MutableFile,
+    group: &str,
+    name: &str,
+    value: &A,
+) -> error::Result<()> {
+    if let Some(value) = value.value() {
+        let _l = LOCK.lock();
+        let ncid = group::add_group_path_at(file.ncid(), group)?.ncid;
+        Attribute::put(ncid, NC_GLOBAL, name, value)?;
+    }
+    Ok(())
+}
+
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn write_variable<V: VariableField>(
+    file: &mut MutableFile,
+    group: &str,
+    name: &str,
+    dimensions: &[&str],
+    value: &V,
+    attributes: &[(&str, &str)],
+    compression: Option<nc_type>,
+) -> error::Result<()> {
+    if !value.is_present() {
+        return Ok(());
+    }
+    let mut var = {
+        let _l = LOCK.lock();
+        let ncid = group::add_group_path_at(file.ncid(), group)?.ncid;
+        let var = VariableMut::add_from_str(ncid, V::nctype(), name, dimensions)?;
+        for &(name, value) in attributes {
+            Attribute::put(ncid, var.varid, name, value.into())?;
+        }
+        var
+    };
+    if let Some(level) = compression {
+        var.compression(level)?;
+    }
+    value.write(&mut var)
+}
@@ -26,20 +26,27 @@ pub struct Identifier {
 
 #[allow(clippy::len_without_is_empty)]
 impl<'g> Dimension<'g> {
-    /// Get current length of this dimension
+    /// Get current length of this dimension. The length of an unlimited
+    /// dimension which can not be read is given as zero, see [`Self::try_len`]
     pub fn len(&self) -> usize {
+        self.try_len().unwrap_or(0)
+    }
+    /// Get current length of this dimension
+    ///
+    /// # Errors
+    ///
+    /// The length of an unlimited dimension could not be read
+    pub fn try_len(&self) -> error::Result<usize> {
         if let Some(x) = self.len {
-            x.get()
+            Ok(x.get())
         } else {
             let mut len = 0;
-            let err = unsafe {
+            unsafe {
                 // Must lock in case other variables adds to the dimension length
-                let _l = LOCK.lock().unwrap();
-                error::checked(nc_inq_dimlen(self.id.ncid, self.id.dimid, &mut len))
-            };
-
-            // Should log or handle this somehow...
-            err.map(|_| len).unwrap_or(0)
+                let _l = LOCK.lock();
+                error::checked(nc_inq_dimlen(self.id.ncid, self.id.dimid, &mut len))?;
+            }
+            Ok(len)
         }
     }
 
@@ -48,16 +55,36 @@ impl<'g> Dimension<'g> {
         self.len.is_none()
     }
 
-    /// Gets the name of the dimension
+    /// Gets the name of the dimension. Bytes which are not
+    /// valid utf-8 are replaced, see [`Self::try_name`]
+    ///
+    /// # Panics
+    ///
+    /// The name could not be read from the file
     pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.name_bytes().unwrap()).into_owned()
+    }
+    /// Gets the name of the dimension
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read, or is not valid utf-8
+    pub fn try_name(&self) -> error::Result<String> {
+        Ok(String::from_utf8(self.name_bytes()?)?)
+    }
+    /// Gets the name of the dimension as stored in the file
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read
+    pub fn name_bytes(&self) -> error::Result<Vec<u8>> {
         let mut name = vec![0_u8; NC_MAX_NAME as usize + 1];
         unsafe {
             error::checked(nc_inq_dimname(
                 self.id.ncid,
                 self.id.dimid,
                 name.as_mut_ptr() as *mut _,
-            ))
-            .unwrap();
+            ))?;
         }
 
         let zeropos = name
@@ -65,7 +92,84 @@ impl<'g> Dimension<'g> {
             .position(|&x| x == 0)
             .unwrap_or_else(|| name.len());
         name.resize(zeropos, 0);
-        String::from_utf8(name).expect("Dimension did not have a valid name")
+        Ok(name)
+    }
+
+    /// The group in which the dimension is defined, which may be
+    /// a parent of the group it was found through
+    ///
+    /// # Panics
+    ///
+    /// The groups could not be read from the file, see [`Self::try_defining_group`]
+    pub fn defining_group(&self) -> super::group::Group<'g> {
+        self.try_defining_group().unwrap()
+    }
+    /// The group in which the dimension is defined
+    ///
+    /// # Errors
+    ///
+    /// The groups could not be read from the file
+    pub fn try_defining_group(&self) -> error::Result<super::group::Group<'g>> {
+        let ncid = defining_group_at(self.id.ncid, self.id.dimid)?;
+        Ok(super::group::Group {
+            ncid,
+            _file: PhantomData,
+        })
+    }
+
+    /// Full path of the dimension, such as `/forecast/time`
+    ///
+    /// # Panics
+    ///
+    /// The groups or names could not be read from the file
+    pub fn full_name(&self) -> String {
+        let group = self.defining_group().full_name();
+        if group.ends_with('/') {
+            format!("{}{}", group, self.name())
+        } else {
+            format!("{}/{}", group, self.name())
+        }
+    }
+
+    /// Variables using this dimension, in the group defining it
+    /// and all groups below
+    ///
+    /// # Panics
+    ///
+    /// The groups or variables could not be read from the file,
+    /// see [`Self::try_variables`]
+    pub fn variables(&self) -> impl Iterator<Item = super::variable::Variable<'g>> {
+        self.try_variables().unwrap().into_iter()
+    }
+    /// Variables using this dimension, in the group defining it
+    /// and all groups below
+    ///
+    /// # Errors
+    ///
+    /// The groups or variables could not be read from the file
+    pub fn try_variables(&self) -> error::Result<Vec<super::variable::Variable<'g>>> {
+        let ncid = defining_group_at(self.id.ncid, self.id.dimid)?;
+        variables_using(ncid, self.id.dimid)
+    }
+
+    /// The coordinate variable of the dimension, a one-dimensional
+    /// variable of the same name in the group defining the dimension
+    ///
+    /// # Panics
+    ///
+    /// The variable could not be read from the file,
+    /// see [`Self::try_coordinate_variable`]
+    pub fn coordinate_variable(&self) -> Option<super::variable::Variable<'g>> {
+        self.try_coordinate_variable().unwrap()
+    }
+    /// The coordinate variable of the dimension
+    ///
+    /// # Errors
+    ///
+    /// The variable could not be read from the file
+    pub fn try_coordinate_variable(&self) -> error::Result<Option<super::variable::Variable<'g>>> {
+        let ncid = defining_group_at(self.id.ncid, self.id.dimid)?;
+        coordinate_variable_at(ncid, self.id.dimid)
     }
 
     /// Grabs the unique identifier for this dimension, which
@@ -75,6 +179,9 @@ impl<'g> Dimension<'g> {
     }
 }
 
+/// Looks up a dimension by name, following the netCDF scoping rules:
+/// a dimension in a group hides dimensions of the same name in
+/// parent groups, and dimensions of all parent groups are visible
 pub(crate) fn from_name_toid(loc: nc_type, name: &str) -> error::Result<Option<nc_type>> {
     let mut dimid = 0;
     let cname = super::utils::short_name_to_bytes(name)?;
@@ -142,6 +249,99 @@ pub(crate) fn dimensions_from_location<'g>(
     }))
 }
 
+/// Dimensions defined in the group `ncid` or any of its parents,
+/// nearest groups first. Dimensions hidden by a dimension of the
+/// same name in a nearer group are left out
+pub(crate) fn visible_dimensions_at<'g>(
+    ncid: nc_type,
+) -> error::Result<impl Iterator<Item = Dimension<'g>>> {
+    let mut dimensions = Vec::new();
+    let mut names = std::collections::HashSet::new();
+    let mut group = Some(ncid);
+    while let Some(ncid) = group {
+        for dim in dimensions_from_location(ncid)? {
+            let dim = dim?;
+            if names.insert(dim.name()) {
+                dimensions.push(dim);
+            }
+        }
+        group = super::group::parent_at(ncid)?.map(|g| g.ncid);
+    }
+    Ok(dimensions.into_iter())
+}
+
+/// Whether the dimension `dimid` can be used by variables of the group `ncid`
+pub(crate) fn is_visible_at(ncid: nc_type, dimid: nc_type) -> error::Result<bool> {
+    let mut ndims = 0;
+    unsafe {
+        error::checked(nc_inq_dimids(
+            ncid,
+            &mut ndims,
+            std::ptr::null_mut(),
+            true as _,
+        ))?;
+    }
+    let mut dimids = vec![0; ndims.try_into()?];
+    unsafe {
+        error::checked(nc_inq_dimids(
+            ncid,
+            std::ptr::null_mut(),
+            dimids.as_mut_ptr(),
+            true as _,
+        ))?;
+    }
+    Ok(dimids.contains(&dimid))
+}
+
+/// The group defining the dimension `dimid`, searching upwards from `ncid`
+pub(crate) fn defining_group_at(ncid: nc_type, dimid: nc_type) -> error::Result<nc_type> {
+    let mut group = Some(ncid);
+    while let Some(ncid) = group {
+        for dim in dimensions_from_location(ncid)? {
+            if dim?.id.dimid == dimid {
+                return Ok(ncid);
+            }
+        }
+        group = super::group::parent_at(ncid)?.map(|g| g.ncid);
+    }
+    Err(error::Error::NotFound(format!("dimension {}", dimid)))
+}
+
+/// Variables of the group `ncid` and all groups below using the dimension
+pub(crate) fn variables_using<'g>(
+    ncid: nc_type,
+    dimid: nc_type,
+) -> error::Result<Vec<super::variable::Variable<'g>>> {
+    let mut variables = Vec::new();
+    for group in super::group::GroupWalker::new(ncid) {
+        for var in super::variable::variables_at_ncid(group?.ncid)? {
+            let var = var?;
+            if var.dimensions.iter().any(|d| d.id.dimid == dimid) {
+                variables.push(var);
+            }
+        }
+    }
+    Ok(variables)
+}
+
+/// The variable in `ncid` with the name of the dimension
+/// and this dimension as its only dimension
+pub(crate) fn coordinate_variable_at<'g>(
+    ncid: nc_type,
+    dimid: nc_type,
+) -> error::Result<Option<super::variable::Variable<'g>>> {
+    let mut name = vec![0_u8; NC_MAX_NAME as usize + 1];
+    unsafe {
+        error::checked(nc_inq_dimname(ncid, dimid, name.as_mut_ptr() as *mut _))?;
+    }
+    let zeropos = name.iter().position(|&x| x == 0).unwrap_or(name.len());
+    name.resize(zeropos, 0);
+    let name = String::from_utf8(name)?;
+
+    Ok(super::variable::Variable::find_from_name(ncid, &name)?
+        .filter(|var| var.dimensions.len() == 1 && var.dimensions[0].id.dimid == dimid))
+}
+
 pub(crate) fn dimensions_from_variable<'g>(
     ncid: nc_type,
     varid: nc_type,
@@ -182,7 +382,7 @@ pub(crate) fn dimension_from_name<'f>(
     }
     let mut dimlen = 0;
     unsafe {
-        error::checked(nc_inq_dimlen(ncid, dimid, &mut dimlen)).unwrap();
+        error::checked(nc_inq_dimlen(ncid, dimid, &mut dimlen))?;
     }
     Ok(Some(Dimension {
         len: core::num::NonZeroUsize::new(dimlen),
@@ -212,3 +412,90 @@ pub(crate) fn add_dimension_at<'f>(
         _group: PhantomData,
     })
 }
+
+/// Checks whether the dimension can grow, independent of its current length
+pub(crate) fn is_unlimited_at(ncid: nc_type, dimid: nc_type) -> error::Result<bool> {
+    let mut ncid = ncid;
+    loop {
+        let mut nunlim = 0;
+        unsafe {
+            error::checked(nc_inq_unlimdims(ncid, &mut nunlim, std::ptr::null_mut()))?;
+        }
+        let mut unlims = vec![0; nunlim.try_into()?];
+        unsafe {
+            error::checked(nc_inq_unlimdims(
+                ncid,
+                std::ptr::null_mut(),
+                unlims.as_mut_ptr(),
+            ))?;
+        }
+        if unlims.contains(&dimid) {
+            return Ok(true);
+        }
+        // Unlimited dimensions may be defined in a parent group
+        let mut parent = 0;
+        let e = unsafe { nc_inq_grp_parent(ncid, &mut parent) };
+        if e == NC_ENOGRP || e == NC_ENOTNC4 {
+            return Ok(false);
+        }
+        error::checked(e)?;
+        ncid = parent;
+    }
+}
+
+/// Makes a dimension with the same name and length as `dim` available
+/// in the group `ncid`, which may be in another file. An existing
+/// dimension is reused if it is compatible, parent groups
+/// are only searched when `search_parents` is set.
+pub(crate) fn copy_to(
+    dim: &Dimension,
+    ncid: nc_type,
+    search_parents: bool,
+) -> error::Result<nc_type> {
+    let name = dim.name();
+    let mut len = 0;
+    unsafe {
+        error::checked(nc_inq_dimlen(dim.id.ncid, dim.id.dimid, &mut len))?;
+    }
+    let unlimited = is_unlimited_at(dim.id.ncid, dim.id.dimid)?;
+
+    let existing = match from_name_toid(ncid, &name)? {
+        Some(dimid) if !search_parents => {
+            let local = dimensions_from_location(ncid)?
+                .map(|d| d.map(|d| d.id.dimid))
+                .collect::<error::Result<Vec<_>>>()?;
+            if local.contains(&dimid) {
+                Some(dimid)
+            } else {
+                None
+            }
+        }
+        x => x,
+    };
+
+    if let Some(dimid) = existing {
+        let mut existing_len = 0;
+        unsafe {
+            error::checked(nc_inq_dimlen(ncid, dimid, &mut existing_len))?;
+        }
+        if is_unlimited_at(ncid, dimid)? || (!unlimited && existing_len == len) {
+            return Ok(dimid);
+        }
+        return Err(error::Error::Str(format!(
+            "dimension {} already exists with an incompatible length",
+            name
+        )));
+    }
+
+    let cname = super::utils::short_name_to_bytes(&name)?;
+    let mut dimid = 0;
+    unsafe {
+        error::checked(nc_def_dim(
+            ncid,
+            cname.as_ptr() as *const _,
+            if unlimited { NC_UNLIMITED as _ } else { len },
+            &mut dimid,
+        ))?;
+    }
+    Ok(dimid)
+}
@@ -5,12 +5,14 @@
 #![allow(clippy::similar_names)]
 use super::nc_type;
 use netcdf_sys::nc_strerror;
+use std::fmt;
 use std::num::TryFromIntError;
+use std::path::PathBuf;
 
 /// Various error types that can occur in this crate
 #[derive(Debug)]
 pub enum Error {
-    /// Errors from the wrapped netcdf library
+    /// Errors from the wrapped netcdf library, see [`Error::kind`]
     Netcdf(nc_type),
     /// Misc errors
     Str(String),
@@ -32,8 +34,9 @@ pub enum Error {
     TypeMismatch,
     /// Does not know the type (probably library error...)
     TypeUnknown(nc_type),
-    /// Variable/dimension already exists
-    AlreadyExists,
+    /// Variable/dimension already exists, with the status code
+    /// from the netCDF library
+    AlreadyExists(nc_type),
     /// Could not find variable/attribute/etc
     NotFound(String),
     /// Slice lengths are ambiguous
@@ -46,6 +49,28 @@ pub enum Error {
     WrongDataset,
     /// Name is not valid utf-8
     Utf8Conversion(std::string::FromUtf8Error),
+    /// Not supported by the linked netCDF library, see [`crate::library`]
+    Unsupported {
+        /// Status code from the library, if it reported the error
+        code: Option<nc_type>,
+        /// What is not supported
+        msg: String,
+    },
+    /// Values which could not be represented in the type
+    /// converted to, see [`crate::ConversionMode`]
+    Range {
+        /// Number of values out of range
+        count: usize,
+        /// Number of values converted
+        total: usize,
+    },
+    /// An error from the netCDF library, with what was being accessed
+    Context {
+        /// Where the error occurred
+        context: Box<Context>,
+        /// The error from the library
+        source: Box<Error>,
+    },
 }
 
 impl Error {
@@ -60,9 +85,70 @@ impl Error {
     }
 }
 
+impl Error {
+    /// Status code of errors from the netCDF library,
+    /// also when wrapped in a [`Error::Context`]
+    pub fn kind(&self) -> Option<ErrorKind> {
+        self.code().map(ErrorKind::from_code)
+    }
+    /// Status code returned by the netCDF library, also when
+    /// wrapped in a [`Error::Context`]. Use this instead of
+    /// matching on [`Error::Netcdf`], which may carry context
+    pub fn code(&self) -> Option<nc_type> {
+        match self {
+            Self::Netcdf(code)
+            | Self::AlreadyExists(code)
+            | Self::Unsupported {
+                code: Some(code), ..
+            } => Some(*code),
+            Self::Range { .. } => Some(netcdf_sys::NC_ERANGE),
+            Self::Context { source, .. } => source.code(),
+            _ => None,
+        }
+    }
+    /// What was being accessed, for errors from the netCDF library
+    pub fn context(&self) -> Option<&Context> {
+        match self {
+            Self::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Attaches the context to errors from the netCDF library,
+    /// or fills in the context already attached. Other errors
+    /// describe themselves, and are returned unchanged
+    pub(crate) fn with_context(self, context: impl FnOnce() -> Context) -> Self {
+        match self {
+            Self::Netcdf(_)
+            | Self::Range { .. }
+            | Self::AlreadyExists(_)
+            | Self::Unsupported { .. } => Self::Context {
+                context: Box::new(context()),
+                source: Box::new(self),
+            },
+            Self::Context {
+                context: mut existing,
+                source,
+            } => {
+                existing.merge(context());
+                Self::Context {
+                    context: existing,
+                    source,
+                }
+            }
+            e => e,
+        }
+    }
+}
+
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        None
+        match self {
+            Self::Context { source, .. } => Some(source.as_ref()),
+            Self::Conversion(e) => Some(e),
+            Self::Utf8Conversion(e) => Some(e),
+            _ => None,
+        }
     }
 }
 
@@ -84,7 +170,17 @@ impl From<nc_type> for Error {
             || nc == netcdf_sys::NC_EATTEXISTS
             || nc == netcdf_sys::NC_ENAMEINUSE
         {
-            Self::AlreadyExists
+            Self::AlreadyExists(nc)
+        } else if nc == netcdf_sys::NC_ENOTBUILT {
+            Self::Unsupported {
+                code: Some(nc),
+                msg: "this feature was not built into the netCDF library".into(),
+            }
+        } else if nc == netcdf_sys::NC_ENOFILTER {
+            Self::Unsupported {
+                code: Some(nc),
+                msg: "the filter is not available to the netCDF library".into(),
+            }
         } else {
             Self::Netcdf(nc)
         }
@@ -103,7 +199,6 @@ impl From<std::string::FromUtf8Error> for Error {
     }
 }
 
-use std::fmt;
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -121,23 +216,352 @@ impl fmt::Display for Error {
             ),
             Self::TypeMismatch => write!(f, "netcdf types does not correspond to what is defined"),
             Self::TypeUnknown(t) => write!(f, "netcdf type {} is not known", t),
-            Self::AlreadyExists => write!(f, "variable/group/dimension already exists"),
+            Self::AlreadyExists(_) => write!(f, "variable/group/dimension already exists"),
             Self::NotFound(x) => write!(f, "Could not find {}", x),
-            Self::Netcdf(x) => {
-                let msg;
-                unsafe {
-                    let cmsg = nc_strerror(*x);
-                    msg = std::ffi::CStr::from_ptr(cmsg);
-                }
-
-                write!(f, "netcdf error({}): {}", x, msg.to_string_lossy())
-            }
+            Self::Netcdf(x) => write!(f, "netcdf error({}): {}", x, ErrorKind::from_code(*x)),
             Self::Ambiguous => write!(f, "Could not find an appropriate length of the slices"),
             Self::Overflow => write!(f, "slice would exceed maximum size of possible buffers"),
             Self::Conversion(e) => e.fmt(f),
             Self::WrongDataset => write!(f, "This identifier does not belong in this dataset"),
             Self::Utf8Conversion(e) => write!(f, "{}", e),
+            Self::Range { count, total } => write!(
+                f,
+                "{} of {} values are out of range of the type converted to",
+                count, total
+            ),
+            Self::Unsupported { msg: x, .. } => {
+                let version;
+                unsafe {
+                    version = std::ffi::CStr::from_ptr(netcdf_sys::nc_inq_libvers());
+                }
+                let version = version.to_string_lossy();
+                let version = version.split(' ').next().unwrap_or_default();
+                write!(f, "unsupported by the linked netCDF {}: {}", version, x)
+            }
+            Self::Context { context, source } => write!(f, "{} ({})", source, context),
+        }
+    }
+}
+
+macro_rules! error_kinds {
+    ($($(#[$meta:meta])* $name:ident = $code:ident,)*) => {
+        /// Status codes returned by the netCDF library, see [`Error::kind`]
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum ErrorKind {
+            $($(#[$meta])* $name,)*
+            /// Error from the operating system, given by `errno`
+            Os(i32),
+            /// A status code unknown to this crate
+            Unknown(nc_type),
+        }
+
+        impl ErrorKind {
+            /// The kind of a status code
+            pub fn from_code(code: nc_type) -> Self {
+                match code {
+                    $(netcdf_sys::$code => Self::$name,)*
+                    code if code > 0 => Self::Os(code),
+                    code => Self::Unknown(code),
+                }
+            }
+            /// The status code used by the netCDF library
+            pub fn code(self) -> nc_type {
+                match self {
+                    $(Self::$name => netcdf_sys::$code,)*
+                    Self::Os(code) | Self::Unknown(code) => code,
+                }
+            }
+        }
+    };
+}
+
+error_kinds! {
+    /// Error in the external data representation (`NC_EXDR`)
+    Xdr = NC_EXDR,
+    /// Operating system error (`NC_SYSERR`)
+    SysErr = NC_SYSERR,
+    /// Not a netCDF id (`NC_EBADID`)
+    BadId = NC_EBADID,
+    /// Too many netCDF files open (`NC_ENFILE`)
+    NFile = NC_ENFILE,
+    /// The file exists and may not be overwritten (`NC_EEXIST`)
+    Exist = NC_EEXIST,
+    /// Invalid argument (`NC_EINVAL`)
+    Inval = NC_EINVAL,
+    /// Write to a file opened read-only (`NC_EPERM`)
+    Perm = NC_EPERM,
+    /// Operation not allowed in data mode (`NC_ENOTINDEFINE`)
+    NotInDefine = NC_ENOTINDEFINE,
+    /// Operation not allowed in define mode (`NC_EINDEFINE`)
+    InDefine = NC_EINDEFINE,
+    /// Index exceeds the dimension bound (`NC_EINVALCOORDS`)
+    InvalCoords = NC_EINVALCOORDS,
+    /// Too many dimensions (`NC_EMAXDIMS`)
+    MaxDims = NC_EMAXDIMS,
+    /// The name is already in use (`NC_ENAMEINUSE`)
+    NameInUse = NC_ENAMEINUSE,
+    /// Attribute not found (`NC_ENOTATT`)
+    NotAtt = NC_ENOTATT,
+    /// Too many attributes (`NC_EMAXATTS`)
+    MaxAtts = NC_EMAXATTS,
+    /// Not a valid data type (`NC_EBADTYPE`)
+    BadType = NC_EBADTYPE,
+    /// Invalid dimension id or name (`NC_EBADDIM`)
+    BadDim = NC_EBADDIM,
+    /// The unlimited dimension is not first (classic formats) (`NC_EUNLIMPOS`)
+    UnlimPos = NC_EUNLIMPOS,
+    /// Too many variables (`NC_EMAXVARS`)
+    MaxVars = NC_EMAXVARS,
+    /// Variable not found (`NC_ENOTVAR`)
+    NotVar = NC_ENOTVAR,
+    /// Action prohibited on the global variable id (`NC_EGLOBAL`)
+    Global = NC_EGLOBAL,
+    /// Not a netCDF file (`NC_ENOTNC`)
+    NotNc = NC_ENOTNC,
+    /// String too short in Fortran (`NC_ESTS`)
+    Sts = NC_ESTS,
+    /// The name is too long (`NC_EMAXNAME`)
+    MaxName = NC_EMAXNAME,
+    /// More than one unlimited dimension (classic formats) (`NC_EUNLIMIT`)
+    Unlimit = NC_EUNLIMIT,
+    /// No record variables (`NC_ENORECVARS`)
+    NoRecVars = NC_ENORECVARS,
+    /// Conversion between text and numbers (`NC_ECHAR`)
+    Char = NC_ECHAR,
+    /// Start and count exceed the dimension bound (`NC_EEDGE`)
+    Edge = NC_EEDGE,
+    /// Illegal stride (`NC_ESTRIDE`)
+    Stride = NC_ESTRIDE,
+    /// The name contains illegal characters (`NC_EBADNAME`)
+    BadName = NC_EBADNAME,
+    /// Numeric value out of range of the type (`NC_ERANGE`)
+    Range = NC_ERANGE,
+    /// Out of memory (`NC_ENOMEM`)
+    NoMem = NC_ENOMEM,
+    /// One or more variables exceed the size limit of the format (`NC_EVARSIZE`)
+    VarSize = NC_EVARSIZE,
+    /// Invalid dimension size (`NC_EDIMSIZE`)
+    DimSize = NC_EDIMSIZE,
+    /// The file is likely truncated or corrupt (`NC_ETRUNC`)
+    Trunc = NC_ETRUNC,
+    /// Unknown axis type (`NC_EAXISTYPE`)
+    AxisType = NC_EAXISTYPE,
+    /// Generic DAP error (`NC_EDAP`)
+    Dap = NC_EDAP,
+    /// Generic libcurl error (`NC_ECURL`)
+    Curl = NC_ECURL,
+    /// Generic I/O error (`NC_EIO`)
+    Io = NC_EIO,
+    /// Attempt to access variable with no data (`NC_ENODATA`)
+    NoData = NC_ENODATA,
+    /// DAP server error (`NC_EDAPSVC`)
+    DapSvc = NC_EDAPSVC,
+    /// Malformed or inaccessible DAS (`NC_EDAS`)
+    Das = NC_EDAS,
+    /// Malformed or inaccessible DDS (`NC_EDDS`)
+    Dds = NC_EDDS,
+    /// Malformed or inaccessible DATADDS (`NC_EDATADDS`)
+    DataDds = NC_EDATADDS,
+    /// Malformed URL (`NC_EDAPURL`)
+    DapUrl = NC_EDAPURL,
+    /// Malformed or unexpected constraint (`NC_EDAPCONSTRAINT`)
+    DapConstraint = NC_EDAPCONSTRAINT,
+    /// Untranslatable construct (`NC_ETRANSLATION`)
+    Translation = NC_ETRANSLATION,
+    /// Access failure (`NC_EACCESS`)
+    Access = NC_EACCESS,
+    /// Authorization failure (`NC_EAUTH`)
+    Auth = NC_EAUTH,
+    /// No such file (`NC_ENOTFOUND`)
+    NotFound = NC_ENOTFOUND,
+    /// The file can not be deleted (`NC_ECANTREMOVE`)
+    CantRemove = NC_ECANTREMOVE,
+    /// Internal error in the netCDF library (`NC_EINTERNAL`)
+    Internal = NC_EINTERNAL,
+    /// Error in the PnetCDF library (`NC_EPNETCDF`)
+    Pnetcdf = NC_EPNETCDF,
+    /// Error in the HDF5 layer (`NC_EHDFERR`)
+    HdfErr = NC_EHDFERR,
+    /// Can not read (`NC_ECANTREAD`)
+    CantRead = NC_ECANTREAD,
+    /// Can not write (`NC_ECANTWRITE`)
+    CantWrite = NC_ECANTWRITE,
+    /// Can not create (`NC_ECANTCREATE`)
+    CantCreate = NC_ECANTCREATE,
+    /// Problem with file metadata (`NC_EFILEMETA`)
+    FileMeta = NC_EFILEMETA,
+    /// Problem with dimension metadata (`NC_EDIMMETA`)
+    DimMeta = NC_EDIMMETA,
+    /// Problem with attribute metadata (`NC_EATTMETA`)
+    AttMeta = NC_EATTMETA,
+    /// Problem with variable metadata (`NC_EVARMETA`)
+    VarMeta = NC_EVARMETA,
+    /// Not a compound type (`NC_ENOCOMPOUND`)
+    NoCompound = NC_ENOCOMPOUND,
+    /// The attribute already exists (`NC_EATTEXISTS`)
+    AttExists = NC_EATTEXISTS,
+    /// Operation needs a netCDF-4 file (`NC_ENOTNC4`)
+    NotNc4 = NC_ENOTNC4,
+    /// Operation not allowed in the classic model (`NC_ESTRICTNC3`)
+    StrictNc3 = NC_ESTRICTNC3,
+    /// Operation needs a classic file (`NC_ENOTNC3`)
+    NotNc3 = NC_ENOTNC3,
+    /// Parallel operation on a file not opened for parallel access (`NC_ENOPAR`)
+    NoPar = NC_ENOPAR,
+    /// Error initializing parallel access (`NC_EPARINIT`)
+    ParInit = NC_EPARINIT,
+    /// Bad group id (`NC_EBADGRPID`)
+    BadGrpId = NC_EBADGRPID,
+    /// Bad type id (`NC_EBADTYPID`)
+    BadTypId = NC_EBADTYPID,
+    /// The type is already defined (`NC_ETYPDEFINED`)
+    TypDefined = NC_ETYPDEFINED,
+    /// Bad field id (`NC_EBADFIELD`)
+    BadField = NC_EBADFIELD,
+    /// Bad class (`NC_EBADCLASS`)
+    BadClass = NC_EBADCLASS,
+    /// Mapped access for atomic types only (`NC_EMAPTYPE`)
+    MapType = NC_EMAPTYPE,
+    /// Fill value set after writing data (`NC_ELATEFILL`)
+    LateFill = NC_ELATEFILL,
+    /// Variable properties set after writing data (`NC_ELATEDEF`)
+    LateDef = NC_ELATEDEF,
+    /// Problem with HDF5 dimension scales (`NC_EDIMSCALE`)
+    DimScale = NC_EDIMSCALE,
+    /// Group not found (`NC_ENOGRP`)
+    NoGrp = NC_ENOGRP,
+    /// Can not set both contiguous and chunked storage (`NC_ESTORAGE`)
+    Storage = NC_ESTORAGE,
+    /// Bad chunk sizes (`NC_EBADCHUNK`)
+    BadChunk = NC_EBADCHUNK,
+    /// Not built into the netCDF library (`NC_ENOTBUILT`)
+    NotBuilt = NC_ENOTBUILT,
+    /// Error in an in-memory file (`NC_EDISKLESS`)
+    Diskless = NC_EDISKLESS,
+    /// Can not extend the dimension in parallel access (`NC_ECANTEXTEND`)
+    CantExtend = NC_ECANTEXTEND,
+    /// MPI error (`NC_EMPI`)
+    Mpi = NC_EMPI,
+    /// Filter operation failed (`NC_EFILTER`)
+    Filter = NC_EFILTER,
+    /// Error in the runtime configuration file (`NC_ERCFILE`)
+    RcFile = NC_ERCFILE,
+    /// Header bytes not padded with nulls (`NC_ENULLPAD`)
+    NullPad = NC_ENULLPAD,
+    /// Error in an in-memory file (`NC_EINMEMORY`)
+    InMemory = NC_EINMEMORY,
+    /// Filter not available (`NC_ENOFILTER`)
+    NoFilter = NC_ENOFILTER,
+    /// Error in NCZarr (`NC_ENCZARR`)
+    NcZarr = NC_ENCZARR,
+    /// Error in accessing S3 storage (`NC_ES3`)
+    S3 = NC_ES3,
+    /// Attempt to read an empty NCZarr map key (`NC_EEMPTY`)
+    Empty = NC_EEMPTY,
+    /// Some object exists when it should not (`NC_EOBJECT`)
+    Object = NC_EOBJECT,
+    /// Some object not found (`NC_ENOOBJECT`)
+    NoObject = NC_ENOOBJECT,
+    /// Failed to load a plugin (`NC_EPLUGIN`)
+    Plugin = NC_EPLUGIN,
+}
+
+/// Message from `nc_strerror`
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg;
+        unsafe {
+            msg = std::ffi::CStr::from_ptr(nc_strerror(self.code()));
+        }
+        write!(f, "{}", msg.to_string_lossy())
+    }
+}
+
+/// What was being accessed when an error occurred. Attached
+/// to errors from the netCDF library by [`Error::Context`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    /// Path of the file
+    pub path: Option<PathBuf>,
+    /// Full name of the group, if not the root group
+    pub group: Option<String>,
+    /// Name of the variable
+    pub variable: Option<String>,
+    /// Name of the attribute
+    pub attribute: Option<String>,
+    /// Start of the hyperslab being read or written
+    pub start: Option<Vec<usize>>,
+    /// Count of the hyperslab being read or written
+    pub count: Option<Vec<usize>>,
+}
+
+impl Context {
+    /// Context with the path and group of `ncid`
+    pub(crate) fn at(ncid: nc_type) -> Self {
+        let mut pathlen = 0;
+        let mut path = None;
+        if unsafe { netcdf_sys::nc_inq_path(ncid, &mut pathlen, std::ptr::null_mut()) } == 0 {
+            // Room for the terminating NUL
+            let mut name = vec![0_u8; pathlen + 1];
+            let e = unsafe {
+                netcdf_sys::nc_inq_path(ncid, std::ptr::null_mut(), name.as_mut_ptr() as *mut _)
+            };
+            let len = name.iter().position(|&x| x == 0).unwrap_or(pathlen);
+            name.truncate(len);
+            if e == 0 && !name.is_empty() {
+                path = Some(String::from_utf8_lossy(&name).into_owned().into());
+            }
+        }
+        let group = crate::group::Group {
+            ncid,
+            _file: std::marker::PhantomData,
+        };
+        let group = if crate::group::parent_at(ncid).ok().flatten().is_some() {
+            Some(group.full_name())
+        } else {
+            None
+        };
+        Self {
+            path,
+            group,
+            ..Self::default()
+        }
+    }
+
+    /// Fills in the fields missing from `self`
+    fn merge(&mut self, other: Self) {
+        self.path = self.path.take().or(other.path);
+        self.group = self.group.take().or(other.group);
+        self.variable = self.variable.take().or(other.variable);
+        self.attribute = self.attribute.take().or(other.attribute);
+        self.start = self.start.take().or(other.start);
+        self.count = self.count.take().or(other.count);
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(path) = &self.path {
+            parts.push(format!("file {}", path.display()));
+        }
+        if let Some(group) = &self.group {
+            parts.push(format!("group {}", group));
+        }
+        if let Some(variable) = &self.variable {
+            parts.push(format!("variable {}", variable));
+        }
+        if let Some(attribute) = &self.attribute {
+            parts.push(format!("attribute {}", attribute));
+        }
+        if let Some(start) = &self.start {
+            parts.push(format!("start {:?}", start));
+        }
+        if let Some(count) = &self.count {
+            parts.push(format!("count {:?}", count));
         }
+        write!(f, "{}", parts.join(", "))
     }
 }
 
@@ -12,6 +12,14 @@ use std::ffi::CString;
 use std::marker::PhantomData;
 use std::path;
 
+/// Context of errors when opening the file at `path`
+fn in_file(path: &path::Path) -> error::Context {
+    error::Context {
+        path: Some(path.into()),
+        ..error::Context::default()
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct RawFile {
     ncid: nc_type,
@@ -20,7 +28,7 @@ pub(crate) struct RawFile {
 impl Drop for RawFile {
     fn drop(&mut self) {
         unsafe {
-            let _g = LOCK.lock().unwrap();
+            let _g = LOCK.lock();
             // Can't really do much with an error here
             let _err = error::checked(nc_close(self.ncid));
         }
@@ -37,8 +45,9 @@ impl RawFile {
         let f = CString::new(path.to_str().unwrap()).unwrap();
         let mut ncid: nc_type = 0;
         unsafe {
-            let _l = LOCK.lock().unwrap();
-            error::checked(nc_open(f.as_ptr(), NC_NOWRITE, &mut ncid))?;
+            let _l = LOCK.lock();
+            error::checked(nc_open(f.as_ptr(), NC_NOWRITE, &mut ncid))
+                .map_err(|e| e.with_context(|| in_file(path)))?;
         }
         Ok(File(Self { ncid }))
     }
@@ -50,8 +59,9 @@ impl RawFile {
         let f = CString::new(path.to_str().unwrap()).unwrap();
         let mut ncid: nc_type = -1;
         unsafe {
-            let _g = LOCK.lock().unwrap();
-            error::checked(nc_open(f.as_ptr(), NC_WRITE, &mut ncid))?;
+            let _g = LOCK.lock();
+            error::checked(nc_open(f.as_ptr(), NC_WRITE, &mut ncid))
+                .map_err(|e| e.with_context(|| in_file(path)))?;
         }
 
         Ok(MutableFile(File(Self { ncid })))
@@ -61,16 +71,98 @@ impl RawFile {
     ///
     /// Will overwrite existing file if any
     pub(crate) fn create(path: &path::Path) -> error::Result<MutableFile> {
+        Self::create_with(path, NC_NETCDF4)
+    }
+    /// Open a `netCDF` file in creation mode, with the format
+    /// given by `cmode` (e.g. `NC_64BIT_OFFSET`).
+    ///
+    /// Will overwrite existing file if any
+    pub(crate) fn create_with(path: &path::Path, cmode: nc_type) -> error::Result<MutableFile> {
         let f = CString::new(path.to_str().unwrap()).unwrap();
         let mut ncid: nc_type = -1;
         unsafe {
-            let _g = LOCK.lock().unwrap();
-            error::checked(nc_create(f.as_ptr(), NC_NETCDF4 | NC_CLOBBER, &mut ncid))?;
+            let _g = LOCK.lock();
+            error::checked(nc_create(f.as_ptr(), cmode | NC_CLOBBER, &mut ncid))
+                .map_err(|e| e.with_context(|| in_file(path)))?;
         }
 
         Ok(MutableFile(File(Self { ncid })))
     }
 
+    #[cfg(feature = "mpi")]
+    /// Open a `netCDF` file for parallel access, shared between all processes
+    /// in `communicator`
+    pub(crate) fn open_par<C>(path: &path::Path, communicator: &C) -> error::Result<File>
+    where
+        C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+    {
+        let f = CString::new(path.to_str().unwrap()).unwrap();
+        let mut ncid: nc_type = -1;
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_open_par(
+                f.as_ptr(),
+                NC_NOWRITE,
+                communicator.as_raw(),
+                mpi::ffi::RSMPI_INFO_NULL,
+                &mut ncid,
+            ))
+            .map_err(|e| e.with_context(|| in_file(path)))?;
+        }
+        Ok(File(Self { ncid }))
+    }
+
+    #[cfg(feature = "mpi")]
+    /// Open a `netCDF` file in append mode for parallel access
+    pub(crate) fn append_par<C>(path: &path::Path, communicator: &C) -> error::Result<MutableFile>
+    where
+        C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+    {
+        let f = CString::new(path.to_str().unwrap()).unwrap();
+        let mut ncid: nc_type = -1;
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_open_par(
+                f.as_ptr(),
+                NC_WRITE,
+                communicator.as_raw(),
+                mpi::ffi::RSMPI_INFO_NULL,
+                &mut ncid,
+            ))
+            .map_err(|e| e.with_context(|| in_file(path)))?;
+        }
+        Ok(MutableFile(File(Self { ncid })))
+    }
+
+    #[cfg(feature = "mpi")]
+    /// Create a file for parallel access, in the format given by
+    /// the flags of `cmode`.
+    ///
+    /// Will overwrite existing file if any
+    pub(crate) fn create_par<C>(
+        path: &path::Path,
+        communicator: &C,
+        cmode: nc_type,
+    ) -> error::Result<MutableFile>
+    where
+        C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+    {
+        let f = CString::new(path.to_str().unwrap()).unwrap();
+        let mut ncid: nc_type = -1;
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_create_par(
+                f.as_ptr(),
+                cmode | NC_CLOBBER,
+                communicator.as_raw(),
+                mpi::ffi::RSMPI_INFO_NULL,
+                &mut ncid,
+            ))
+            .map_err(|e| e.with_context(|| in_file(path)))?;
+        }
+        Ok(MutableFile(File(Self { ncid })))
+    }
+
     #[cfg(feature = "memory")]
     pub(crate) fn open_from_memory<'buffer>(
         name: Option<&str>,
@@ -79,7 +171,7 @@ impl RawFile {
         let cstr = std::ffi::CString::new(name.unwrap_or("/")).unwrap();
         let mut ncid = 0;
         unsafe {
-            let _l = LOCK.lock().unwrap();
+            let _l = LOCK.lock();
             error::checked(nc_open_mem(
                 cstr.as_ptr(),
                 NC_NOWRITE,
@@ -111,7 +203,8 @@ impl File {
             unsafe {
                 error::checked(nc_inq_path(self.0.ncid, &mut pathlen, std::ptr::null_mut()))?;
             }
-            let mut name = vec![0_u8; pathlen as _];
+            // Room for the terminating NUL
+            let mut name = vec![0_u8; pathlen + 1];
             unsafe {
                 error::checked(nc_inq_path(
                     self.0.ncid,
@@ -119,6 +212,8 @@ impl File {
                     name.as_mut_ptr() as *mut _,
                 ))?;
             }
+            let len = name.iter().position(|&x| x == 0).unwrap_or(pathlen);
+            name.truncate(len);
             name
         };
 
@@ -126,20 +221,31 @@ impl File {
     }
 
     /// Main entrypoint for interacting with the netcdf file.
+    /// Gives `None` for files without groups, and if the
+    /// format could not be read, see [`Self::try_root`]
     pub fn root(&self) -> Option<Group> {
+        self.try_root().ok().flatten()
+    }
+
+    /// Root group of the file, `None` for formats without groups
+    ///
+    /// # Errors
+    ///
+    /// The format of the file could not be read
+    pub fn try_root(&self) -> error::Result<Option<Group<'_>>> {
         let mut format = 0;
-        unsafe { error::checked(nc_inq_format(self.ncid(), &mut format)) }.unwrap();
+        unsafe { error::checked(nc_inq_format(self.ncid(), &mut format)) }?;
 
-        match format {
+        Ok(match format {
             NC_FORMAT_NETCDF4 | NC_FORMAT_NETCDF4_CLASSIC => Some(Group {
                 ncid: self.ncid(),
                 _file: PhantomData,
             }),
             _ => None,
-        }
+        })
     }
 
-    fn ncid(&self) -> nc_type {
+    pub(crate) fn ncid(&self) -> nc_type {
         self.0.ncid
     }
 
@@ -156,12 +262,12 @@ impl File {
 
     /// Get a single attribute
     pub fn attribute<'f>(&'f self, name: &str) -> Option<Attribute<'f>> {
-        let _l = super::LOCK.lock().unwrap();
+        let _l = super::LOCK.lock();
         Attribute::find_from_name(self.ncid(), None, name).unwrap()
     }
     /// Get all attributes in the root group
     pub fn attributes(&self) -> impl Iterator<Item = Attribute> {
-        let _l = super::LOCK.lock().unwrap();
+        let _l = super::LOCK.lock();
         crate::attribute::AttributeIterator::new(self.0.ncid, None)
             .unwrap()
             .map(Result::unwrap)
@@ -186,6 +292,15 @@ impl File {
     pub fn group<'f>(&'f self, name: &str) -> error::Result<Option<Group<'f>>> {
         super::group::group_from_name(self.ncid(), name)
     }
+    /// Get a group by its path from the root group,
+    /// such as `/forecast/member_03`
+    ///
+    /// # Errors
+    ///
+    /// Not a `netCDF-4` file
+    pub fn group_by_path<'f>(&'f self, path: &str) -> error::Result<Option<Group<'f>>> {
+        super::group::group_from_path(self.ncid(), path)
+    }
     /// Iterator over all subgroups in the root group
     ///
     /// # Errors
@@ -194,6 +309,124 @@ impl File {
     pub fn groups<'f>(&'f self) -> error::Result<impl Iterator<Item = Group<'f>>> {
         super::group::groups_at_ncid(self.ncid())
     }
+
+    /// Iterator over the root group and all groups below it,
+    /// depth first, see [`Group::walk`]
+    ///
+    /// # Panics
+    ///
+    /// The groups could not be read from the file, see [`Self::try_walk_groups`]
+    pub fn walk_groups(&self) -> impl Iterator<Item = Group<'_>> {
+        super::group::GroupWalker::new(self.ncid()).map(Result::unwrap)
+    }
+    /// Iterator over the root group and all groups below it,
+    /// depth first, see [`Group::try_walk`]
+    pub fn try_walk_groups(&self) -> impl Iterator<Item = error::Result<Group<'_>>> {
+        super::group::GroupWalker::new(self.ncid())
+    }
+
+    /// Format of the file on disk, as shown by `ncdump -k`
+    pub fn format(&self) -> error::Result<super::convert::Format> {
+        super::convert::format_at(self.ncid())
+    }
+
+    /// Library layer used to access the file (such as `HDF5` or `DAP`)
+    /// and the mode flags it was opened with, as shown by `ncdump -K`
+    pub fn format_extended(&self) -> error::Result<(super::convert::ExtendedFormat, nc_type)> {
+        super::convert::format_extended_at(self.ncid())
+    }
+    /// Special attributes describing how a `netCDF-4` file was written.
+    /// All fields are `None` for other formats
+    pub fn provenance(&self) -> error::Result<Provenance> {
+        provenance_at(self.ncid())
+    }
+
+    /// Describes the file in CDL, equivalent to `ncdump -h`
+    pub fn to_cdl(&self) -> error::Result<String> {
+        self.to_cdl_with(&super::cdl::Options::default())
+    }
+    /// Describes the file in CDL, optionally with data
+    /// (equivalent to `ncdump -c` or `ncdump -v`)
+    pub fn to_cdl_with(&self, options: &super::cdl::Options) -> error::Result<String> {
+        super::cdl::file_to_cdl(self.ncid(), &self.path()?, options)
+    }
+}
+
+/// Special attributes of `netCDF-4` files recording how the file
+/// was written. These are hidden from [`File::attributes`], but
+/// shown by `ncdump -s`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Provenance {
+    /// `_NCProperties`, versions of the libraries which created the file
+    pub properties: Option<String>,
+    /// `_IsNetcdf4`, whether the `HDF5` file follows the netCDF-4 conventions
+    pub is_netcdf4: Option<bool>,
+    /// `_SuperblockVersion`, version of the `HDF5` superblock
+    pub superblock_version: Option<i32>,
+}
+
+/// Reads the special attributes of the root group of `ncid`
+pub(crate) fn provenance_at(ncid: nc_type) -> error::Result<Provenance> {
+    Ok(Provenance {
+        properties: special_attribute_text(ncid, NCPROPS)?,
+        is_netcdf4: special_attribute_int(ncid, ISNETCDF4ATT)?.map(|x| x != 0),
+        superblock_version: special_attribute_int(ncid, SUPERBLOCKATT)?,
+    })
+}
+
+/// Length of the special global attribute `name`, `None` if not present
+fn special_attribute_len(ncid: nc_type, name: &[u8]) -> error::Result<Option<usize>> {
+    let mut len = 0;
+    let e = unsafe {
+        nc_inq_att(
+            ncid,
+            NC_GLOBAL,
+            name.as_ptr() as *const _,
+            std::ptr::null_mut(),
+            &mut len,
+        )
+    };
+    if e == NC_ENOTATT {
+        return Ok(None);
+    }
+    error::checked(e)?;
+    Ok(Some(len))
+}
+
+fn special_attribute_text(ncid: nc_type, name: &[u8]) -> error::Result<Option<String>> {
+    let len = match special_attribute_len(ncid, name)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    let mut text = vec![0_u8; len];
+    unsafe {
+        error::checked(nc_get_att_text(
+            ncid,
+            NC_GLOBAL,
+            name.as_ptr() as *const _,
+            text.as_mut_ptr() as *mut _,
+        ))?;
+    }
+    let zeropos = text.iter().position(|&x| x == 0).unwrap_or(text.len());
+    text.truncate(zeropos);
+    Ok(Some(String::from_utf8(text)?))
+}
+
+fn special_attribute_int(ncid: nc_type, name: &[u8]) -> error::Result<Option<i32>> {
+    if special_attribute_len(ncid, name)?.is_none() {
+        return Ok(None);
+    }
+    let mut value = 0;
+    unsafe {
+        error::checked(nc_get_att_int(
+            ncid,
+            NC_GLOBAL,
+            name.as_ptr() as *const _,
+            &mut value,
+        ))?;
+    }
+    Ok(Some(value))
 }
 
 /// Mutable access to file
@@ -245,6 +478,15 @@ impl MutableFile {
         self.group(name)
             .map(|g| g.map(|g| GroupMut(g, PhantomData)))
     }
+    /// Mutable access to a group by its path, see [`File::group_by_path`]
+    ///
+    /// # Errors
+    ///
+    /// File does not support groups
+    pub fn group_mut_by_path<'f>(&'f mut self, path: &str) -> error::Result<Option<GroupMut<'f>>> {
+        self.group_by_path(path)
+            .map(|g| g.map(|g| GroupMut(g, PhantomData)))
+    }
     /// Iterator over all groups (mutable access)
     ///
     /// # Errors
@@ -259,13 +501,26 @@ impl MutableFile {
     where
         T: Into<AttrValue>,
     {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         Attribute::put(self.ncid(), NC_GLOBAL, name, val.into())
     }
+    /// Adds an attribute to the root group, stored as `NC_STRING`
+    /// (instead of `NC_CHAR` as `add_attribute` does for a single string)
+    pub fn add_string_attribute<'a, S>(
+        &'a mut self,
+        name: &str,
+        values: &[S],
+    ) -> error::Result<Attribute<'a>>
+    where
+        S: AsRef<str>,
+    {
+        let _l = LOCK.lock();
+        Attribute::put_strings(self.ncid(), NC_GLOBAL, name, values)
+    }
 
     /// Adds a dimension with the given name and size. A size of zero gives an unlimited dimension
     pub fn add_dimension<'f>(&'f mut self, name: &str, len: usize) -> error::Result<Dimension<'f>> {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         super::dimension::add_dimension_at(self.ncid(), name, len)
     }
     /// Adds a dimension with unbounded size
@@ -275,9 +530,24 @@ impl MutableFile {
 
     /// Add an empty group to the dataset
     pub fn add_group<'f>(&'f mut self, name: &str) -> error::Result<GroupMut<'f>> {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         GroupMut::add_group_at(self.ncid(), name)
     }
+    /// Gets the group at `path`, adding it and any missing groups
+    /// along the way (like `mkdir -p`)
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut file = netcdf::create("ensemble.nc")?;
+    /// let mut member = file.add_group_path("/forecast/member_03")?;
+    /// member.add_dimension("x", 10)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn add_group_path<'f>(&'f mut self, path: &str) -> error::Result<GroupMut<'f>> {
+        let _l = LOCK.lock();
+        super::group::add_group_path_at(self.ncid(), path)
+    }
 
     /// Create a Variable into the dataset, with no data written into it
     ///
@@ -291,8 +561,8 @@ impl MutableFile {
     where
         T: Numeric,
     {
-        let _l = LOCK.lock().unwrap();
-        VariableMut::add_from_str(self.ncid(), T::NCTYPE, name, dims)
+        let _l = LOCK.lock();
+        VariableMut::add_from_str(self.ncid(), T::variable_type(self.ncid())?, name, dims)
     }
     /// Adds a variable with a basic type of string
     pub fn add_string_variable<'f>(
@@ -300,9 +570,40 @@ impl MutableFile {
         name: &str,
         dims: &[&str],
     ) -> error::Result<VariableMut<'f>> {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         VariableMut::add_from_str(self.ncid(), NC_STRING, name, dims)
     }
+    /// Adds a variable with a basic type of character. The last dimension
+    /// holds the characters of each string, see [`VariableMut::put_text_values`]
+    pub fn add_char_variable<'f>(
+        &'f mut self,
+        name: &str,
+        dims: &[&str],
+    ) -> error::Result<VariableMut<'f>> {
+        let _l = LOCK.lock();
+        VariableMut::add_from_str(self.ncid(), NC_CHAR, name, dims)
+    }
+    /// Copies an attribute, possibly from another file, to the root group
+    pub fn copy_attribute<'a>(&'a mut self, att: &Attribute) -> error::Result<Attribute<'a>> {
+        let _l = LOCK.lock();
+        att.copy_to(self.ncid(), NC_GLOBAL)
+    }
+    /// Copies the definition, attributes and data of a variable, possibly
+    /// from another file. Dimensions which can not be found by name
+    /// are added to the root group
+    pub fn copy_variable<'f>(&'f mut self, var: &Variable) -> error::Result<VariableMut<'f>> {
+        let _l = LOCK.lock();
+        super::variable::copy_variable_to(var, self.ncid())
+    }
+    /// Copies a group, possibly from another file, into a new subgroup
+    /// of the root group. All dimensions, attributes, variables and
+    /// subgroups are copied recursively
+    pub fn copy_group<'f>(&'f mut self, group: &Group) -> error::Result<GroupMut<'f>> {
+        let _l = LOCK.lock();
+        let subgroup = GroupMut::add_group_at(self.ncid(), &group.name())?;
+        super::group::copy_group_contents(group.ncid, subgroup.ncid)?;
+        Ok(subgroup)
+    }
     /// Adds a variable from a set of unique identifiers, recursing upwards
     /// from the current group if necessary.
     pub fn add_variable_from_identifiers<'f, T>(
@@ -313,8 +614,13 @@ impl MutableFile {
     where
         T: Numeric,
     {
-        let _l = LOCK.lock().unwrap();
-        super::variable::add_variable_from_identifiers(self.ncid(), name, dims, T::NCTYPE)
+        let _l = LOCK.lock();
+        super::variable::add_variable_from_identifiers(
+            self.ncid(),
+            name,
+            dims,
+            T::variable_type(self.ncid())?,
+        )
     }
 }
 
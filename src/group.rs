@@ -36,19 +36,97 @@ impl<'f> std::ops::Deref for GroupMut<'f> {
 }
 
 impl<'f> Group<'f> {
-    /// Name of the current group
+    /// Name of the current group. Bytes which are not valid
+    /// utf-8 are replaced, see [`Self::try_name`]
+    ///
+    /// # Panics
+    ///
+    /// The name could not be read from the file
     pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.name_bytes().unwrap()).into_owned()
+    }
+    /// Name of the current group
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read, or is not valid utf-8
+    pub fn try_name(&self) -> error::Result<String> {
+        Ok(String::from_utf8(self.name_bytes()?)?)
+    }
+    /// Name of the current group as stored in the file
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read
+    pub fn name_bytes(&self) -> error::Result<Vec<u8>> {
         let mut name = vec![0_u8; NC_MAX_NAME as usize + 1];
         unsafe {
-            error::checked(nc_inq_grpname(self.ncid, name.as_mut_ptr() as *mut _)).unwrap();
+            error::checked(nc_inq_grpname(self.ncid, name.as_mut_ptr() as *mut _))?;
         }
         let zeropos = name
             .iter()
             .position(|&x| x == 0)
             .unwrap_or_else(|| name.len());
         name.resize(zeropos, 0);
-
-        String::from_utf8(name).expect("Group did not have a valid name")
+        Ok(name)
+    }
+    /// Full path of the group from the root, such as `/forecast/member_03`.
+    /// Bytes which are not valid utf-8 are replaced, see [`Self::try_full_name`]
+    ///
+    /// # Panics
+    ///
+    /// The name could not be read from the file
+    pub fn full_name(&self) -> String {
+        String::from_utf8_lossy(&self.full_name_bytes().unwrap()).into_owned()
+    }
+    /// Full path of the group from the root
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read, or is not valid utf-8
+    pub fn try_full_name(&self) -> error::Result<String> {
+        Ok(String::from_utf8(self.full_name_bytes()?)?)
+    }
+    /// Full path of the group from the root as stored in the file
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read
+    pub fn full_name_bytes(&self) -> error::Result<Vec<u8>> {
+        let mut len = 0;
+        unsafe {
+            error::checked(nc_inq_grpname_full(
+                self.ncid,
+                &mut len,
+                std::ptr::null_mut(),
+            ))?;
+        }
+        let mut name = vec![0_u8; len + 1];
+        unsafe {
+            error::checked(nc_inq_grpname_full(
+                self.ncid,
+                std::ptr::null_mut(),
+                name.as_mut_ptr() as *mut _,
+            ))?;
+        }
+        name.truncate(len);
+        Ok(name)
+    }
+    /// The group containing this group, `None` for the root group
+    ///
+    /// # Panics
+    ///
+    /// The parent could not be read from the file, see [`Self::try_parent`]
+    pub fn parent(&self) -> Option<Group<'f>> {
+        self.try_parent().unwrap()
+    }
+    /// The group containing this group, `None` for the root group
+    ///
+    /// # Errors
+    ///
+    /// The parent could not be read from the file
+    pub fn try_parent(&self) -> error::Result<Option<Group<'f>>> {
+        parent_at(self.ncid)
     }
     /// Internal ncid of the group
     fn id(&self) -> nc_type {
@@ -71,22 +149,60 @@ impl<'f> Group<'f> {
             .unwrap()
             .map(Result::unwrap)
     }
+    /// Iterate over the variables in this group using the dimension
+    /// `name`, which may be defined in a parent group
+    ///
+    /// # Panics
+    ///
+    /// The dimension or variables could not be read from the file,
+    /// see [`Self::try_variables_with_dimension`]
+    pub fn variables_with_dimension<'g>(&'g self, name: &str) -> impl Iterator<Item = Variable<'g>>
+    where
+        'f: 'g,
+    {
+        self.try_variables_with_dimension(name).unwrap().into_iter()
+    }
+    /// The variables in this group using the dimension `name`,
+    /// which may be defined in a parent group
+    ///
+    /// # Errors
+    ///
+    /// The dimension or variables could not be read from the file
+    pub fn try_variables_with_dimension<'g>(
+        &'g self,
+        name: &str,
+    ) -> error::Result<Vec<Variable<'g>>>
+    where
+        'f: 'g,
+    {
+        let Some(dimid) = super::dimension::from_name_toid(self.id(), name)? else {
+            return Ok(Vec::new());
+        };
+        let mut variables = Vec::new();
+        for var in super::variable::variables_at_ncid(self.id())? {
+            let var = var?;
+            if var.dimensions.iter().any(|d| d.id.dimid == dimid) {
+                variables.push(var);
+            }
+        }
+        Ok(variables)
+    }
 
     /// Get a single attribute
     pub fn attribute<'a>(&'a self, name: &str) -> Option<Attribute<'a>> {
-        let _l = super::LOCK.lock().unwrap();
+        let _l = super::LOCK.lock();
         Attribute::find_from_name(self.ncid, None, name).unwrap()
     }
     /// Get all attributes in the group
     pub fn attributes(&self) -> impl Iterator<Item = Attribute> {
         // Need to lock when reading the first attribute (per group)
-        let _l = super::LOCK.lock().unwrap();
+        let _l = super::LOCK.lock();
         crate::attribute::AttributeIterator::new(self.ncid, None)
             .unwrap()
             .map(Result::unwrap)
     }
 
-    /// Get a single dimension
+    /// Get a single dimension, which may be defined in a parent group
     pub fn dimension<'g>(&'g self, name: &str) -> Option<Dimension<'g>>
     where
         'f: 'g,
@@ -102,6 +218,15 @@ impl<'f> Group<'f> {
             .unwrap()
             .map(Result::unwrap)
     }
+    /// Iterator over all dimensions which can be used by variables
+    /// in this group, including those defined in parent groups. A
+    /// dimension hides dimensions of the same name further up
+    pub fn visible_dimensions<'g>(&'g self) -> impl Iterator<Item = Dimension<'g>>
+    where
+        'f: 'g,
+    {
+        super::dimension::visible_dimensions_at(self.id()).unwrap()
+    }
 
     /// Get a group
     pub fn group<'g>(&'g self, name: &str) -> Option<Group<'g>>
@@ -111,6 +236,14 @@ impl<'f> Group<'f> {
         // We are in a group, must support netCDF-4
         group_from_name(self.id(), name).unwrap()
     }
+    /// Get a group by its path, such as `member_03/surface`. Paths
+    /// starting with `/` are taken from the root group
+    pub fn group_by_path<'g>(&'g self, path: &str) -> Option<Group<'g>>
+    where
+        'f: 'g,
+    {
+        group_from_path(self.id(), path).unwrap()
+    }
     /// Iterator over all subgroups in this group
     pub fn groups<'g>(&'g self) -> impl Iterator<Item = Group<'g>>
     where
@@ -118,6 +251,49 @@ impl<'f> Group<'f> {
     {
         groups_at_ncid(self.id()).unwrap()
     }
+    /// Iterator over this group and all groups below it, depth first
+    /// with each group before its subgroups
+    ///
+    /// # Examples
+    /// Listing all variables in a group hierarchy
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let file = netcdf::open("ensemble.nc")?;
+    /// for group in file.walk_groups() {
+    ///     for var in group.variables() {
+    ///         println!("{}/{}", group.full_name(), var.name());
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// The subgroups could not be read from the file, see [`Self::try_walk`]
+    pub fn walk<'g>(&'g self) -> impl Iterator<Item = Group<'g>>
+    where
+        'f: 'g,
+    {
+        GroupWalker::new(self.id()).map(Result::unwrap)
+    }
+    /// Iterator over this group and all groups below it, depth first.
+    /// The subgroups of a group which gives an error are skipped
+    pub fn try_walk<'g>(&'g self) -> impl Iterator<Item = error::Result<Group<'g>>>
+    where
+        'f: 'g,
+    {
+        GroupWalker::new(self.id())
+    }
+
+    /// Describes the group in CDL, as it appears in the
+    /// output of `ncdump -h` for the whole file
+    pub fn to_cdl(&self) -> error::Result<String> {
+        self.to_cdl_with(&super::cdl::Options::default())
+    }
+    /// Describes the group in CDL, optionally with data
+    pub fn to_cdl_with(&self, options: &super::cdl::Options) -> error::Result<String> {
+        super::cdl::group_to_cdl(self.id(), options)
+    }
 }
 
 impl<'f> GroupMut<'f> {
@@ -136,6 +312,13 @@ impl<'f> GroupMut<'f> {
         self.variables().map(|var| VariableMut(var, PhantomData))
     }
 
+    /// Mutable access to a group by its path, see [`Group::group_by_path`]
+    pub fn group_mut_by_path<'g>(&'g mut self, path: &str) -> Option<GroupMut<'g>>
+    where
+        'f: 'g,
+    {
+        self.group_by_path(path).map(|g| GroupMut(g, PhantomData))
+    }
     /// Mutable access to subgroup
     pub fn group_mut<'g>(&'g mut self, name: &str) -> Option<GroupMut<'g>>
     where
@@ -156,13 +339,26 @@ impl<'f> GroupMut<'f> {
     where
         T: Into<AttrValue>,
     {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         Attribute::put(self.ncid, NC_GLOBAL, name, val.into())
     }
+    /// Adds an attribute to the group, stored as `NC_STRING`
+    /// (instead of `NC_CHAR` as `add_attribute` does for a single string)
+    pub fn add_string_attribute<'a, S>(
+        &'a mut self,
+        name: &str,
+        values: &[S],
+    ) -> error::Result<Attribute<'a>>
+    where
+        S: AsRef<str>,
+    {
+        let _l = LOCK.lock();
+        Attribute::put_strings(self.ncid, NC_GLOBAL, name, values)
+    }
 
     /// Adds a dimension with the given name and size. A size of zero gives an unlimited dimension
     pub fn add_dimension<'g>(&'g mut self, name: &str, len: usize) -> error::Result<Dimension<'g>> {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         super::dimension::add_dimension_at(self.id(), name, len)
     }
 
@@ -192,10 +388,20 @@ impl<'f> GroupMut<'f> {
     where
         'f: 'g,
     {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         Self::add_group_at(self.id(), name)
     }
 
+    /// Gets the group at `path`, adding it and any missing groups
+    /// along the way (like `mkdir -p`)
+    pub fn add_group_path<'g>(&'g mut self, path: &str) -> error::Result<GroupMut<'g>>
+    where
+        'f: 'g,
+    {
+        let _l = LOCK.lock();
+        add_group_path_at(self.id(), path)
+    }
+
     /// Create a Variable into the dataset, with no data written into it
     ///
     /// Dimensions are identified using the name of the dimension, and will recurse upwards
@@ -209,8 +415,8 @@ impl<'f> GroupMut<'f> {
         T: Numeric,
         'f: 'g,
     {
-        let _l = LOCK.lock().unwrap();
-        VariableMut::add_from_str(self.id(), T::NCTYPE, name, dims)
+        let _l = LOCK.lock();
+        VariableMut::add_from_str(self.id(), T::variable_type(self.id())?, name, dims)
     }
     /// Adds a variable with a basic type of string
     pub fn add_string_variable<'g>(
@@ -218,9 +424,52 @@ impl<'f> GroupMut<'f> {
         name: &str,
         dims: &[&str],
     ) -> error::Result<VariableMut<'g>> {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         VariableMut::add_from_str(self.id(), NC_STRING, name, dims)
     }
+    /// Adds a variable with a basic type of character. The last dimension
+    /// holds the characters of each string, see [`VariableMut::put_text_values`]
+    pub fn add_char_variable<'g>(
+        &mut self,
+        name: &str,
+        dims: &[&str],
+    ) -> error::Result<VariableMut<'g>> {
+        let _l = LOCK.lock();
+        VariableMut::add_from_str(self.id(), NC_CHAR, name, dims)
+    }
+    /// Copies an attribute, possibly from another file, to this group
+    pub fn copy_attribute<'a>(&'a mut self, att: &Attribute) -> error::Result<Attribute<'a>> {
+        let _l = LOCK.lock();
+        att.copy_to(self.id(), NC_GLOBAL)
+    }
+    /// Copies the definition, attributes and data of a variable, possibly
+    /// from another file. Dimensions which can not be found by name
+    /// are added to this group
+    pub fn copy_variable<'g>(&'g mut self, var: &Variable) -> error::Result<VariableMut<'g>>
+    where
+        'f: 'g,
+    {
+        let _l = LOCK.lock();
+        super::variable::copy_variable_to(var, self.id())
+    }
+    /// Copies a group, possibly from another file, into a new subgroup
+    /// with the same name. All dimensions, attributes, variables and
+    /// subgroups are copied recursively
+    pub fn copy_group<'g>(&'g mut self, group: &Group) -> error::Result<GroupMut<'g>>
+    where
+        'f: 'g,
+    {
+        let _l = LOCK.lock();
+        let subgroup = Self::add_group_at(self.id(), &group.name())?;
+        copy_group_contents(group.ncid, subgroup.ncid)?;
+        Ok(subgroup)
+    }
+    /// Recursively copies all dimensions, attributes, variables and
+    /// subgroups of a group, possibly from another file, into this group
+    pub fn copy_group_contents(&mut self, group: &Group) -> error::Result<()> {
+        let _l = LOCK.lock();
+        copy_group_contents(group.ncid, self.id())
+    }
     /// Adds a variable from a set of unique identifiers, recursing upwards
     /// from the current group if necessary.
     pub fn add_variable_from_identifiers<'g, T>(
@@ -231,8 +480,60 @@ impl<'f> GroupMut<'f> {
     where
         T: Numeric,
     {
-        let _l = LOCK.lock().unwrap();
-        super::variable::add_variable_from_identifiers(self.id(), name, dims, T::NCTYPE)
+        let _l = LOCK.lock();
+        super::variable::add_variable_from_identifiers(
+            self.id(),
+            name,
+            dims,
+            T::variable_type(self.id())?,
+        )
+    }
+}
+
+/// Recursively copies the dimensions, attributes, variables
+/// and subgroups of the group `src` into the group `dst`
+pub(crate) fn copy_group_contents(src: nc_type, dst: nc_type) -> error::Result<()> {
+    for dim in super::dimension::dimensions_from_location(src)? {
+        super::dimension::copy_to(&dim?, dst, false)?;
+    }
+    for att in crate::attribute::AttributeIterator::new(src, None)? {
+        att?.copy_to(dst, NC_GLOBAL)?;
+    }
+    for var in super::variable::variables_at_ncid(src)? {
+        super::variable::copy_variable_to(&var?, dst)?;
+    }
+    for group in groups_at_ncid(src)? {
+        let subgroup = GroupMut::add_group_at(dst, &group.name())?;
+        copy_group_contents(group.ncid, subgroup.ncid)?;
+    }
+    Ok(())
+}
+
+/// Files and groups which attributes and variables can be copied
+/// into, see [`Attribute::copy_attribute_to`] and [`Variable::copy_variable_to`]
+pub trait CopyTarget {
+    /// Copies an attribute, possibly from another file
+    fn copy_attribute<'a>(&'a mut self, att: &Attribute) -> error::Result<Attribute<'a>>;
+    /// Copies the definition, attributes and data of a variable,
+    /// possibly from another file
+    fn copy_variable<'a>(&'a mut self, var: &Variable) -> error::Result<VariableMut<'a>>;
+}
+
+impl<'f> CopyTarget for GroupMut<'f> {
+    fn copy_attribute<'a>(&'a mut self, att: &Attribute) -> error::Result<Attribute<'a>> {
+        GroupMut::copy_attribute(self, att)
+    }
+    fn copy_variable<'a>(&'a mut self, var: &Variable) -> error::Result<VariableMut<'a>> {
+        GroupMut::copy_variable(self, var)
+    }
+}
+
+impl CopyTarget for super::file::MutableFile {
+    fn copy_attribute<'a>(&'a mut self, att: &Attribute) -> error::Result<Attribute<'a>> {
+        super::file::MutableFile::copy_attribute(self, att)
+    }
+    fn copy_variable<'a>(&'a mut self, var: &Variable) -> error::Result<VariableMut<'a>> {
+        super::file::MutableFile::copy_variable(self, var)
     }
 }
 
@@ -265,3 +566,94 @@ pub(crate) fn group_from_name<'f>(ncid: nc_type, name: &str) -> error::Result<Op
         _file: PhantomData,
     }))
 }
+
+pub(crate) fn parent_at<'f>(ncid: nc_type) -> error::Result<Option<Group<'f>>> {
+    let mut parent = 0;
+    let e = unsafe { nc_inq_grp_parent(ncid, &mut parent) };
+    if e == NC_ENOGRP {
+        return Ok(None);
+    } else {
+        error::checked(e)?;
+    }
+    Ok(Some(Group {
+        ncid: parent,
+        _file: PhantomData,
+    }))
+}
+
+/// Group at `path` below `ncid`, or below the root group
+/// if the path starts with `/`
+pub(crate) fn group_from_path<'f>(ncid: nc_type, path: &str) -> error::Result<Option<Group<'f>>> {
+    let mut ncid = ncid;
+    if path.starts_with('/') {
+        while let Some(parent) = parent_at(ncid)? {
+            ncid = parent.ncid;
+        }
+    }
+    for name in path.split('/').filter(|s| !s.is_empty()) {
+        match group_from_name(ncid, name)? {
+            Some(g) => ncid = g.ncid,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(Group {
+        ncid,
+        _file: PhantomData,
+    }))
+}
+
+/// Group at `path` below `ncid`, adding the missing groups
+pub(crate) fn add_group_path_at<'f>(ncid: nc_type, path: &str) -> error::Result<GroupMut<'f>> {
+    let mut ncid = ncid;
+    if path.starts_with('/') {
+        while let Some(parent) = parent_at(ncid)? {
+            ncid = parent.ncid;
+        }
+    }
+    for name in path.split('/').filter(|s| !s.is_empty()) {
+        ncid = match group_from_name(ncid, name)? {
+            Some(g) => g.ncid,
+            None => GroupMut::add_group_at(ncid, name)?.ncid,
+        };
+    }
+    Ok(GroupMut(
+        Group {
+            ncid,
+            _file: PhantomData,
+        },
+        PhantomData,
+    ))
+}
+
+/// Depth first iterator over a group and its subgroups
+pub(crate) struct GroupWalker<'f> {
+    stack: Vec<nc_type>,
+    _file: PhantomData<&'f nc_type>,
+}
+
+impl<'f> GroupWalker<'f> {
+    pub(crate) fn new(ncid: nc_type) -> Self {
+        Self {
+            stack: vec![ncid],
+            _file: PhantomData,
+        }
+    }
+}
+
+impl<'f> Iterator for GroupWalker<'f> {
+    type Item = error::Result<Group<'f>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let ncid = self.stack.pop()?;
+        let children = match groups_at_ncid(ncid) {
+            Ok(children) => children.map(|g| g.ncid),
+            Err(e) => return Some(Err(e)),
+        };
+        let len = self.stack.len();
+        self.stack.extend(children);
+        self.stack[len..].reverse();
+        Some(Ok(Group {
+            ncid,
+            _file: PhantomData,
+        }))
+    }
+}
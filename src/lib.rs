@@ -69,16 +69,29 @@ use netcdf_sys::nc_type;
 use std::sync::Mutex;
 
 pub mod attribute;
+pub mod cdl;
+pub mod compare;
+pub mod convert;
+pub mod dataset;
 pub mod dimension;
 pub mod error;
 pub mod file;
 pub mod group;
+pub mod library;
+pub mod multi;
+#[cfg(feature = "ncml")]
+pub mod ncml;
+pub mod schema;
 pub mod variable;
 
 pub use attribute::*;
+pub use dataset::NetcdfDataset;
 pub use dimension::*;
 pub use file::*;
 pub use group::*;
+pub use library::library;
+#[cfg(feature = "derive")]
+pub use netcdf_derive::NetcdfDataset;
 pub use variable::*;
 
 /// Open a netcdf file in create mode
@@ -107,15 +120,114 @@ where
     RawFile::open(name.as_ref())
 }
 
+/// Open several netcdf files as one read-only dataset, where variables
+/// having `dim` as their first dimension are concatenated along it
+///
+/// # Errors
+///
+/// Any file could not be opened, or the other dimensions
+/// or the variables do not agree between the files
+pub fn open_multi<P>(names: &[P], dim: &str) -> error::Result<multi::Dataset>
+where
+    P: AsRef<std::path::Path>,
+{
+    let files = names.iter().map(open).collect::<error::Result<Vec<_>>>()?;
+    if files.is_empty() {
+        return Err(error::Error::Str("no files to aggregate".into()));
+    }
+    multi::Dataset::from_files(files, Some(dim))
+}
+
 #[cfg(feature = "memory")]
 /// Open a netcdf file from a buffer
 pub fn open_mem<'a>(name: Option<&str>, mem: &'a [u8]) -> error::Result<MemFile<'a>> {
     RawFile::open_from_memory(name, mem)
 }
 
+#[cfg(feature = "mpi")]
+/// Open a `netCDF-4` file in create mode for parallel access.
+/// Must be called collectively by all processes in `communicator`
+///
+/// Will overwrite exising file
+pub fn create_par<P, C>(name: P, communicator: &C) -> error::Result<MutableFile>
+where
+    P: AsRef<std::path::Path>,
+    C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+{
+    RawFile::create_par(name.as_ref(), communicator, netcdf_sys::NC_NETCDF4)
+}
+
+#[cfg(feature = "mpi")]
+/// Open a netcdf file in create mode for parallel access, in the
+/// format given by the mode flags of `nc_create`. These are
+/// `NC_NETCDF4`, or `0`, `NC_64BIT_OFFSET` and `NC_64BIT_DATA` for
+/// the classic formats, which requires the library to be built
+/// with `PnetCDF`. Must be called collectively by all processes
+/// in `communicator`
+///
+/// Will overwrite exising file
+pub fn create_par_with<P, C>(
+    name: P,
+    communicator: &C,
+    cmode: nc_type,
+) -> error::Result<MutableFile>
+where
+    P: AsRef<std::path::Path>,
+    C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+{
+    RawFile::create_par(name.as_ref(), communicator, cmode)
+}
+
+#[cfg(feature = "mpi")]
+/// Open a netcdf file in append mode for parallel access.
+/// Must be called collectively by all processes in `communicator`
+pub fn append_par<P, C>(name: P, communicator: &C) -> error::Result<MutableFile>
+where
+    P: AsRef<std::path::Path>,
+    C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+{
+    RawFile::append_par(name.as_ref(), communicator)
+}
+
+#[cfg(feature = "mpi")]
+/// Open a netcdf file in read mode for parallel access.
+/// Must be called collectively by all processes in `communicator`
+pub fn open_par<P, C>(name: P, communicator: &C) -> error::Result<File>
+where
+    P: AsRef<std::path::Path>,
+    C: mpi::raw::AsRaw<Raw = mpi::ffi::MPI_Comm>,
+{
+    RawFile::open_par(name.as_ref(), communicator)
+}
+
 lazy_static! {
     /// Use this when accessing netcdf functions
-    pub(crate) static ref LOCK: Mutex<()> = Mutex::new(());
+    pub(crate) static ref LOCK: Lock = Lock(Mutex::new(()));
+}
+
+/// Lock serializing calls into the netcdf library
+pub(crate) struct Lock(Mutex<()>);
+
+impl Lock {
+    /// Takes the lock. A panic while it was held elsewhere does not
+    /// leave the library in an unknown state, so poisoning is ignored
+    pub(crate) fn lock(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[test]
+fn lock_after_panic() {
+    let poisoned = std::thread::spawn(|| {
+        let _l = LOCK.lock();
+        panic!("poisoning the lock");
+    })
+    .join();
+    assert!(poisoned.is_err());
+    assert!(LOCK.0.is_poisoned());
+    drop(LOCK.lock());
 }
 
 pub(crate) mod utils {
@@ -0,0 +1,291 @@
+//! Version and capabilities of the linked netCDF library
+//!
+//! The same crate may be linked against builds of `libnetcdf`
+//! with very different features. These can be checked before
+//! relying on them:
+//!
+//! ```no_run
+//! let library = netcdf::library();
+//! println!("netCDF {}", library.version);
+//! if !library.features.nczarr {
+//!     eprintln!("Zarr stores can not be read");
+//! }
+//! ```
+
+use super::error;
+use super::LOCK;
+use netcdf_sys::*;
+use std::ffi::{CStr, CString};
+
+/// Version of the netCDF library
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    /// Major version
+    pub major: u32,
+    /// Minor version
+    pub minor: u32,
+    /// Patch version
+    pub patch: u32,
+}
+
+impl Version {
+    /// A version from its parts
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses the leading `major.minor.patch` of a version string
+    /// such as `4.9.2-development of Mar 14 2023`
+    pub fn parse(s: &str) -> Option<Self> {
+        let numbers = s
+            .trim()
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?;
+        let mut parts = numbers.split('.').map(str::parse::<u32>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Optional parts of the netCDF library
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Features {
+    /// `netCDF-4` files, through `HDF5`
+    pub netcdf4: bool,
+    /// The `CDF-5` format, with 64-bit data
+    pub cdf5: bool,
+    /// Remote datasets through `DAP2`
+    pub dap2: bool,
+    /// Remote datasets through `DAP4`
+    pub dap4: bool,
+    /// Zarr stores through `NCZarr`
+    pub nczarr: bool,
+    /// Parallel I/O through MPI. The crate can only use this
+    /// with the `mpi` feature, and it is always `false` without
+    pub parallel: bool,
+}
+
+/// Description of the linked netCDF library
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Library {
+    /// Version string as given by `nc_inq_libvers`
+    pub version_string: String,
+    /// Parsed version, `0.0.0` if the string could not be parsed
+    pub version: Version,
+    /// Optional parts included in the library
+    pub features: Features,
+}
+
+impl Library {
+    /// Checks whether an `HDF5` filter, such as `32015` for `zstd`, can be
+    /// used for compressing variables. Only reliable for netCDF 4.8 and
+    /// later, as earlier versions do not check the filter when it is defined
+    pub fn has_filter(&self, id: u32, params: &[u32]) -> bool {
+        self.features.netcdf4 && has_filter(id, params).unwrap_or(false)
+    }
+
+    /// Gives an [`error::Error::Unsupported`] unless
+    /// the library is at least the given version
+    ///
+    /// # Errors
+    ///
+    /// The library is older than `version`
+    pub fn require(&self, version: Version, what: &str) -> error::Result<()> {
+        if self.version >= version {
+            Ok(())
+        } else {
+            Err(error::Error::Unsupported {
+                code: None,
+                msg: format!(
+                    "{} requires netCDF {}, but {} is linked",
+                    what, version, self.version
+                ),
+            })
+        }
+    }
+}
+
+/// Well known `HDF5` filter identifiers for [`Library::has_filter`]
+pub mod filters {
+    /// Deflate (zlib), always present in `netCDF-4`
+    pub const DEFLATE: u32 = 1;
+    /// Shuffle, always present in `netCDF-4`
+    pub const SHUFFLE: u32 = 2;
+    /// Fletcher32 checksums
+    pub const FLETCHER32: u32 = 3;
+    /// Szip compression
+    pub const SZIP: u32 = 4;
+    /// Bzip2 compression
+    pub const BZIP2: u32 = 307;
+    /// Blosc compression
+    pub const BLOSC: u32 = 32001;
+    /// Zstandard compression
+    pub const ZSTD: u32 = 32015;
+}
+
+lazy_static::lazy_static! {
+    static ref LIBRARY: Library = probe();
+}
+
+/// Version and features of the linked netCDF library. Features
+/// are found by trying them out the first time this is called
+pub fn library() -> &'static Library {
+    &LIBRARY
+}
+
+fn probe() -> Library {
+    let version_string = unsafe { CStr::from_ptr(nc_inq_libvers()) }
+        .to_string_lossy()
+        .into_owned();
+    let version = Version::parse(&version_string).unwrap_or_default();
+
+    let _l = LOCK.lock();
+    let features = Features {
+        netcdf4: can_create(NC_NETCDF4),
+        cdf5: can_create(NC_64BIT_DATA),
+        dap2: can_open_url("dap2"),
+        dap4: can_open_url("dap4"),
+        // Older versions do not know the mode, and would try DAP
+        nczarr: version >= Version::new(4, 8, 0) && can_open_url("nczarr,file"),
+        parallel: can_create_par(),
+    };
+
+    Library {
+        version_string,
+        version,
+        features,
+    }
+}
+
+/// Path for probing files, which are never written to disk
+fn probe_path() -> CString {
+    let path = std::env::temp_dir().join(format!("netcdf-rs-probe-{}.nc", std::process::id()));
+    CString::new(path.to_string_lossy().into_owned()).unwrap()
+}
+
+/// Whether an in-memory file can be created with the mode
+fn can_create(cmode: nc_type) -> bool {
+    let path = probe_path();
+    let mut ncid = 0;
+    let e = unsafe { nc_create(path.as_ptr(), cmode | NC_DISKLESS | NC_CLOBBER, &mut ncid) };
+    if e != NC_NOERR {
+        return false;
+    }
+    unsafe {
+        nc_abort(ncid);
+    }
+    true
+}
+
+/// Whether the library is built with parallel I/O. Without it,
+/// `nc_create_par` fails with `NC_ENOPAR` before looking at the
+/// arguments, otherwise the missing path is rejected before MPI is used
+#[cfg(feature = "mpi")]
+fn can_create_par() -> bool {
+    let mut ncid = 0;
+    let e = unsafe {
+        nc_create_par(
+            std::ptr::null(),
+            NC_NETCDF4,
+            mpi::ffi::RSMPI_COMM_SELF,
+            mpi::ffi::RSMPI_INFO_NULL,
+            &mut ncid,
+        )
+    };
+    e != NC_ENOPAR
+}
+
+#[cfg(not(feature = "mpi"))]
+fn can_create_par() -> bool {
+    false
+}
+
+/// Whether a local URL with the given mode is understood, a missing
+/// implementation gives `NC_ENOTBUILT` before anything is opened
+fn can_open_url(mode: &str) -> bool {
+    let url = format!("file:///netcdf-rs-probe-nonexistent#mode={}", mode);
+    let url = CString::new(url).unwrap();
+    let mut ncid = 0;
+    let e = unsafe { nc_open(url.as_ptr(), NC_NOWRITE, &mut ncid) };
+    if e == NC_NOERR {
+        unsafe {
+            nc_close(ncid);
+        }
+    }
+    e != NC_ENOTBUILT
+}
+
+/// Tries to define the filter on a variable in an in-memory file
+fn has_filter(id: u32, params: &[u32]) -> error::Result<bool> {
+    let _l = LOCK.lock();
+    let path = probe_path();
+    let mut ncid = 0;
+    unsafe {
+        error::checked(nc_create(
+            path.as_ptr(),
+            NC_NETCDF4 | NC_DISKLESS | NC_CLOBBER,
+            &mut ncid,
+        ))?;
+    }
+    let result = (|| {
+        let (mut dimid, mut varid) = (0, 0);
+        unsafe {
+            error::checked(nc_def_dim(
+                ncid,
+                b"x\0".as_ptr() as *const _,
+                64,
+                &mut dimid,
+            ))?;
+            error::checked(nc_def_var(
+                ncid,
+                b"v\0".as_ptr() as *const _,
+                NC_INT,
+                1,
+                &dimid,
+                &mut varid,
+            ))?;
+            error::checked(nc_def_var_chunking(ncid, varid, NC_CHUNKED, &64))?;
+        }
+        let e = unsafe { nc_def_var_filter(ncid, varid, id, params.len(), params.as_ptr()) };
+        Ok(e == NC_NOERR)
+    })();
+    unsafe {
+        nc_abort(ncid);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Version;
+
+    #[test]
+    fn parse_version() {
+        assert_eq!(
+            Version::parse("4.9.2 of Mar 14 2023 20:44:03 $"),
+            Some(Version::new(4, 9, 2))
+        );
+        assert_eq!(
+            Version::parse("4.10.0-development of Jan 1 2024"),
+            Some(Version::new(4, 10, 0))
+        );
+        assert_eq!(Version::parse("4.7"), Some(Version::new(4, 7, 0)));
+        assert_eq!(Version::parse("unknown"), None);
+        assert!(Version::new(4, 10, 0) > Version::new(4, 9, 2));
+    }
+}
@@ -0,0 +1,435 @@
+//! Virtual datasets spanning several files
+//!
+//! Files split along a record dimension, such as one file per day,
+//! can be read as a single dataset
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let dataset = netcdf::open_multi(&["day1.nc", "day2.nc"], "time")?;
+//! let var = dataset.variable("temperature").expect("Could not find variable");
+//! // Reads across both files
+//! let mut values = vec![0.0_f32; var.len()];
+//! var.values_to(&mut values, None, None)?;
+//! # Ok(()) }
+//! ```
+
+use super::attribute::AttrValue;
+use super::error;
+use super::file::File;
+use super::variable::Numeric;
+#[cfg(feature = "ndarray")]
+use ndarray::ArrayD;
+use netcdf_sys::nc_type;
+
+/// Dimension of a virtual dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dimension {
+    pub(crate) name: String,
+    pub(crate) len: usize,
+    pub(crate) unlimited: bool,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl Dimension {
+    /// Name of the dimension
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+    /// Length of the dimension, summed over all
+    /// datasets if this is the aggregation dimension
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Whether the dimension is unlimited in the underlying dataset
+    pub fn is_unlimited(&self) -> bool {
+        self.unlimited
+    }
+}
+
+/// Attribute of a virtual dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub(crate) name: String,
+    pub(crate) value: AttrValue,
+}
+
+impl Attribute {
+    /// Name of the attribute
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Value of the attribute
+    pub fn value(&self) -> error::Result<AttrValue> {
+        Ok(self.value.clone())
+    }
+}
+
+/// Part of a variable in one of the underlying files. For aggregated
+/// variables this covers `len` entries of the first dimension, starting at `offset`
+#[derive(Debug, Clone)]
+pub(crate) struct Source {
+    pub(crate) file: usize,
+    pub(crate) offset: usize,
+    pub(crate) len: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct VarDef {
+    pub(crate) name: String,
+    /// Name in the underlying files
+    pub(crate) orgname: String,
+    pub(crate) vartype: nc_type,
+    pub(crate) dimensions: Vec<Dimension>,
+    pub(crate) attributes: Vec<Attribute>,
+    pub(crate) sources: Vec<Source>,
+}
+
+/// Read-only view over one or more files
+#[derive(Debug)]
+pub struct Dataset {
+    pub(crate) files: Vec<File>,
+    pub(crate) dims: Vec<Dimension>,
+    pub(crate) vars: Vec<VarDef>,
+    pub(crate) attributes: Vec<Attribute>,
+}
+
+pub(crate) fn read_attributes<'a>(
+    attributes: impl Iterator<Item = super::Attribute<'a>>,
+) -> error::Result<Vec<Attribute>> {
+    attributes
+        .map(|a| {
+            Ok(Attribute {
+                name: a.name().into(),
+                value: a.value()?,
+            })
+        })
+        .collect()
+}
+
+fn virtual_dimension(d: &super::Dimension) -> error::Result<Dimension> {
+    Ok(Dimension {
+        name: d.name(),
+        len: d.len(),
+        unlimited: super::dimension::is_unlimited_at(d.id.ncid, d.id.dimid)?,
+    })
+}
+
+impl Dataset {
+    /// Combines the variables of `files`. Variables with `join` as their
+    /// first dimension are concatenated along this dimension, other
+    /// variables are taken from the first file containing them.
+    ///
+    /// Other dimensions must have the same length in all files, and
+    /// with `join` variables must have the same type and dimensions
+    pub(crate) fn from_files(files: Vec<File>, join: Option<&str>) -> error::Result<Self> {
+        let mut dims: Vec<Dimension> = Vec::new();
+        let mut vars: Vec<VarDef> = Vec::new();
+        let mut attributes = Vec::new();
+
+        for (i, file) in files.iter().enumerate() {
+            let mismatch = |msg: String| {
+                let path = file.path().unwrap_or_else(|_| format!("file {}", i));
+                error::Error::Str(format!("{}: {}", path, msg))
+            };
+
+            let mut has_join = false;
+            for d in file.dimensions() {
+                let d = virtual_dimension(&d)?;
+                let is_join = Some(d.name.as_str()) == join;
+                has_join |= is_join;
+                match dims.iter_mut().find(|x| x.name == d.name) {
+                    Some(x) if is_join => x.len += d.len,
+                    Some(x) if x.len != d.len => {
+                        return Err(mismatch(format!(
+                            "dimension {} has length {} instead of {}",
+                            d.name, d.len, x.len
+                        )))
+                    }
+                    Some(_) => {}
+                    None => dims.push(d),
+                }
+            }
+            if let Some(join) = join {
+                if !has_join {
+                    return Err(mismatch(format!("dimension {} is missing", join)));
+                }
+            }
+            if i == 0 {
+                attributes = read_attributes(file.attributes())?;
+            }
+
+            for var in file.variables() {
+                let name = var.name();
+                let dimensions = var
+                    .dimensions()
+                    .iter()
+                    .map(virtual_dimension)
+                    .collect::<error::Result<Vec<_>>>()?;
+                let joined = dimensions
+                    .first()
+                    .is_some_and(|d| Some(d.name.as_str()) == join);
+                if !joined && dimensions.iter().any(|d| Some(d.name.as_str()) == join) {
+                    return Err(mismatch(format!(
+                        "variable {} can only be aggregated along its first dimension",
+                        name
+                    )));
+                }
+                if let Some(existing) = vars.iter_mut().find(|v| v.name == name) {
+                    if join.is_none() {
+                        continue;
+                    }
+                    if existing.vartype != var.vartype() {
+                        return Err(mismatch(format!("variable {} has a different type", name)));
+                    }
+                    if existing
+                        .dimensions
+                        .iter()
+                        .map(|d| &d.name)
+                        .ne(dimensions.iter().map(|d| &d.name))
+                    {
+                        return Err(mismatch(format!(
+                            "variable {} has different dimensions",
+                            name
+                        )));
+                    }
+                    if joined {
+                        let offset = existing.sources.iter().map(|s| s.len).sum();
+                        existing.sources.push(Source {
+                            file: i,
+                            offset,
+                            len: dimensions[0].len,
+                        });
+                    }
+                    continue;
+                }
+                vars.push(VarDef {
+                    orgname: name.clone(),
+                    name,
+                    vartype: var.vartype(),
+                    attributes: read_attributes(var.attributes())?,
+                    sources: vec![Source {
+                        file: i,
+                        offset: 0,
+                        len: dimensions.first().map_or(1, |d| d.len),
+                    }],
+                    dimensions,
+                });
+            }
+        }
+
+        for var in &mut vars {
+            // Aggregated dimensions have the total length
+            for d in &mut var.dimensions {
+                if let Some(x) = dims.iter().find(|x| x.name == d.name) {
+                    *d = x.clone();
+                }
+            }
+            let joined = var
+                .dimensions
+                .first()
+                .is_some_and(|d| Some(d.name.as_str()) == join);
+            let len = var.sources.iter().map(|s| s.len).sum::<usize>();
+            if joined && len != var.dimensions[0].len {
+                return Err(error::Error::Str(format!(
+                    "variable {} is missing from some files",
+                    var.name
+                )));
+            }
+        }
+        Ok(Self {
+            files,
+            dims,
+            vars,
+            attributes,
+        })
+    }
+
+    /// Get a variable from the dataset
+    pub fn variable<'d>(&'d self, name: &str) -> Option<Variable<'d>> {
+        self.vars
+            .iter()
+            .find(|v| v.name == name)
+            .map(|def| Variable { dataset: self, def })
+    }
+    /// Iterate over all variables
+    pub fn variables(&self) -> impl Iterator<Item = Variable<'_>> {
+        self.vars
+            .iter()
+            .map(move |def| Variable { dataset: self, def })
+    }
+    /// Get a single global attribute
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+    /// Iterate over all global attributes
+    pub fn attributes(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.iter()
+    }
+    /// Get a single dimension
+    pub fn dimension(&self, name: &str) -> Option<&Dimension> {
+        self.dims.iter().find(|d| d.name == name)
+    }
+    /// Iterate over all dimensions
+    pub fn dimensions(&self) -> impl Iterator<Item = &Dimension> {
+        self.dims.iter()
+    }
+}
+
+/// Variable of a virtual dataset, which reads from the underlying files
+#[derive(Debug, Clone)]
+pub struct Variable<'d> {
+    dataset: &'d Dataset,
+    def: &'d VarDef,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl<'d> Variable<'d> {
+    /// Name of the variable
+    pub fn name(&self) -> String {
+        self.def.name.clone()
+    }
+    /// Dimensions of the variable
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.def.dimensions
+    }
+    /// Type of the variable, such as `NC_FLOAT`
+    pub fn vartype(&self) -> nc_type {
+        self.def.vartype
+    }
+    /// Total number of elements
+    pub fn len(&self) -> usize {
+        self.def.dimensions.iter().map(Dimension::len).product()
+    }
+    /// Get an attribute of this variable
+    pub fn attribute(&self, name: &str) -> Option<&'d Attribute> {
+        self.def.attributes.iter().find(|a| a.name == name)
+    }
+    /// Iterate over all attributes of this variable
+    pub fn attributes(&self) -> impl Iterator<Item = &'d Attribute> {
+        self.def.attributes.iter()
+    }
+
+    /// Validated start and count of a hyperslab
+    fn slice(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<(Vec<usize>, Vec<usize>)> {
+        let dims = &self.def.dimensions;
+        let indices = match indices {
+            Some(x) if x.len() != dims.len() => return Err(error::Error::IndexLen),
+            Some(x) => x.to_vec(),
+            None => vec![0; dims.len()],
+        };
+        if indices.iter().zip(dims).any(|(&i, d)| i > d.len) {
+            return Err(error::Error::IndexMismatch);
+        }
+        let slice_len = match slice_len {
+            Some(x) if x.len() != dims.len() => return Err(error::Error::SliceLen),
+            Some(x) => x.to_vec(),
+            None => indices.iter().zip(dims).map(|(&i, d)| d.len - i).collect(),
+        };
+        for ((&i, &s), d) in indices.iter().zip(&slice_len).zip(dims) {
+            match i.checked_add(s) {
+                None => return Err(error::Error::Overflow),
+                Some(end) if end > d.len => return Err(error::Error::SliceMismatch),
+                Some(_) => {}
+            }
+        }
+        Ok((indices, slice_len))
+    }
+
+    /// Reads the hyperslab from the files holding the data
+    ///
+    /// # Safety
+    ///
+    /// `values` must hold `slice_len.product()` elements, and
+    /// the hyperslab must be valid
+    unsafe fn read_to_ptr<T: Numeric>(
+        &self,
+        indices: &[usize],
+        slice_len: &[usize],
+        values: *mut T,
+    ) -> error::Result<()> {
+        if slice_len.contains(&0) {
+            return Ok(());
+        }
+        if indices.is_empty() {
+            let source = &self.def.sources[0];
+            return self.read_source(source, indices, slice_len, values);
+        }
+        let inner = slice_len[1..].iter().product::<usize>();
+        let (start, end) = (indices[0], indices[0] + slice_len[0]);
+        for source in &self.def.sources {
+            let lo = start.max(source.offset);
+            let hi = end.min(source.offset + source.len);
+            if lo >= hi {
+                continue;
+            }
+            let mut indices = indices.to_vec();
+            indices[0] = lo - source.offset;
+            let mut slice_len = slice_len.to_vec();
+            slice_len[0] = hi - lo;
+            let values = values.add((lo - start) * inner);
+            self.read_source(source, &indices, &slice_len, values)?;
+        }
+        Ok(())
+    }
+
+    unsafe fn read_source<T: Numeric>(
+        &self,
+        source: &Source,
+        indices: &[usize],
+        slice_len: &[usize],
+        values: *mut T,
+    ) -> error::Result<()> {
+        let var = self.dataset.files[source.file]
+            .variable(&self.def.orgname)
+            .ok_or_else(|| error::Error::NotFound(format!("variable {}", self.def.orgname)))?;
+        T::variable_to_ptr(&var, indices, slice_len, values)
+    }
+
+    /// Fetches one value
+    pub fn value<T: Numeric>(&self, indices: Option<&[usize]>) -> error::Result<T> {
+        let count = vec![1; self.def.dimensions.len()];
+        let (indices, slice_len) = self.slice(indices, Some(&count))?;
+        let mut value = std::mem::MaybeUninit::uninit();
+        unsafe {
+            self.read_to_ptr(&indices, &slice_len, value.as_mut_ptr())?;
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Fetches the variable into `buffer`, which must be able
+    /// to hold all the requested elements
+    pub fn values_to<T: Numeric>(
+        &self,
+        buffer: &mut [T],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let (indices, slice_len) = self.slice(indices, slice_len)?;
+        let len = slice_len.iter().product::<usize>();
+        if buffer.len() != len {
+            return Err(error::Error::BufferLen(buffer.len(), len));
+        }
+        unsafe { self.read_to_ptr(&indices, &slice_len, buffer.as_mut_ptr()) }
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Fetches the variable
+    pub fn values<T: Numeric>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<T>> {
+        let (indices, slice_len) = self.slice(indices, slice_len)?;
+        let len = slice_len.iter().product::<usize>();
+        let mut values = Vec::with_capacity(len);
+        unsafe {
+            self.read_to_ptr(&indices, &slice_len, values.as_mut_ptr())?;
+            values.set_len(len);
+        }
+        Ok(ArrayD::from_shape_vec(slice_len, values).unwrap())
+    }
+}
@@ -0,0 +1,298 @@
+//! Read-only virtual datasets described by
+//! [NcML](https://docs.unidata.ucar.edu/netcdf-java/current/userguide/ncml_overview.html)
+//!
+//! Supported are renaming of dimensions, variables and attributes,
+//! adding and removing attributes, removing variables, and the
+//! `joinExisting` and `union` aggregations of local files.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let dataset = netcdf::ncml::open("catalog.ncml")?;
+//! let var = dataset.variable("temperature").expect("Could not find variable");
+//! let mut values = vec![0.0_f32; var.len()];
+//! var.values_to(&mut values, None, None)?;
+//! # Ok(()) }
+//! ```
+
+use super::attribute::AttrValue;
+use super::error;
+use super::file::File;
+pub use super::multi::{Attribute, Dataset, Dimension, Variable};
+use std::path::{Path, PathBuf};
+
+/// Opens the NcML file at `path`. Locations of datasets
+/// are relative to the directory of this file
+///
+/// # Errors
+///
+/// Invalid or unsupported NcML, or the referenced datasets could not be opened
+pub fn open<P>(path: P) -> error::Result<Dataset>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let xml = std::fs::read_to_string(path)
+        .map_err(|e| error::Error::Str(format!("{}: {}", path.display(), e)))?;
+    let base = path.parent().unwrap_or_else(|| Path::new("."));
+    parse(&xml, base)
+}
+
+/// Creates a virtual dataset from an NcML document. Relative
+/// locations of datasets are resolved from `base`
+///
+/// # Errors
+///
+/// Invalid or unsupported NcML, or the referenced datasets could not be opened
+pub fn parse(xml: &str, base: &Path) -> error::Result<Dataset> {
+    let doc = roxmltree::Document::parse(xml)
+        .map_err(|e| error::Error::Str(format!("invalid NcML: {}", e)))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "netcdf" {
+        return Err(unsupported("root element must be <netcdf>"));
+    }
+
+    let mut dataset = match child(root, "aggregation") {
+        Some(agg) => aggregate(agg, base)?,
+        None => match root.attribute("location") {
+            Some(location) => Dataset::from_files(vec![open_location(location, base)?], None)?,
+            None => return Err(unsupported("<netcdf> needs a location or an aggregation")),
+        },
+    };
+    if root.children().any(|c| c.has_tag_name("explicit")) {
+        dataset.attributes.clear();
+        for var in &mut dataset.vars {
+            var.attributes.clear();
+        }
+    }
+
+    for node in root.children().filter(roxmltree::Node::is_element) {
+        match node.tag_name().name() {
+            "dimension" => rename_dimension(&mut dataset, node)?,
+            "attribute" => update_attribute(&mut dataset.attributes, node)?,
+            "variable" => update_variable(&mut dataset, node)?,
+            "remove" => match node.attribute("type") {
+                Some("attribute") => remove_attribute(&mut dataset.attributes, node)?,
+                Some("variable") => {
+                    let name = required(node, "name")?;
+                    dataset.vars.retain(|v| v.name != name);
+                }
+                _ => return Err(unsupported("only attributes and variables can be removed")),
+            },
+            "aggregation" | "explicit" | "readMetadata" => {}
+            x => return Err(unsupported(&format!("<{}> is not supported", x))),
+        }
+    }
+    Ok(dataset)
+}
+
+fn unsupported(msg: &str) -> error::Error {
+    error::Error::Str(format!("NcML: {}", msg))
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|c| c.has_tag_name(name))
+}
+
+fn required<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> error::Result<&'a str> {
+    node.attribute(name).ok_or_else(|| {
+        unsupported(&format!(
+            "<{}> is missing the attribute {}",
+            node.tag_name().name(),
+            name
+        ))
+    })
+}
+
+fn resolve(location: &str, base: &Path) -> PathBuf {
+    let location = location.trim_start_matches("file:");
+    let path = Path::new(location);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
+
+fn open_location(location: &str, base: &Path) -> error::Result<File> {
+    super::open(resolve(location, base))
+}
+
+/// Files in `dir` ending with `suffix`, sorted by path
+fn scan(dir: &Path, suffix: &str, subdirs: bool, found: &mut Vec<PathBuf>) -> error::Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| error::Error::Str(format!("{}: {}", dir.display(), e)))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| error::Error::Str(format!("{}: {}", dir.display(), e)))?
+            .path();
+        if path.is_dir() {
+            if subdirs {
+                scan(&path, suffix, subdirs, found)?;
+            }
+        } else if path.to_string_lossy().ends_with(suffix) {
+            found.push(path);
+        }
+    }
+    found.sort();
+    Ok(())
+}
+
+fn aggregate(agg: roxmltree::Node, base: &Path) -> error::Result<Dataset> {
+    let kind = required(agg, "type")?;
+    let mut files = Vec::new();
+    for node in agg.children().filter(roxmltree::Node::is_element) {
+        match node.tag_name().name() {
+            "netcdf" => files.push(open_location(required(node, "location")?, base)?),
+            "scan" => {
+                let dir = resolve(required(node, "location")?, base);
+                let suffix = node.attribute("suffix").unwrap_or("");
+                let subdirs = node.attribute("subdirs") != Some("false");
+                let mut paths = Vec::new();
+                scan(&dir, suffix, subdirs, &mut paths)?;
+                for path in paths {
+                    files.push(super::open(path)?);
+                }
+            }
+            "variableAgg" => {}
+            x => return Err(unsupported(&format!("<{}> is not supported", x))),
+        }
+    }
+    if files.is_empty() {
+        return Err(unsupported("aggregation without datasets"));
+    }
+    match kind {
+        "joinExisting" => Dataset::from_files(files, Some(required(agg, "dimName")?)),
+        "union" => Dataset::from_files(files, None),
+        x => Err(unsupported(&format!("aggregation of type {}", x))),
+    }
+}
+
+/// Value of an `<attribute>` element
+fn attribute_value(node: roxmltree::Node) -> error::Result<AttrValue> {
+    let value = node
+        .attribute("value")
+        .map(String::from)
+        .or_else(|| node.text().map(String::from))
+        .unwrap_or_default();
+    let typ = node.attribute("type").unwrap_or("String");
+    if typ == "String" || typ == "string" || typ == "char" {
+        return Ok(AttrValue::Str(value));
+    }
+    let items = match node.attribute("separator") {
+        Some(sep) => value.split(sep).map(str::trim).collect::<Vec<_>>(),
+        None => value.split_whitespace().collect(),
+    };
+
+    macro_rules! numeric {
+        ($t: ty, $scalar: ident, $vec: ident) => {{
+            let mut values = items
+                .iter()
+                .map(|x| {
+                    x.parse::<$t>()
+                        .map_err(|_| unsupported(&format!("{} is not a valid {} value", x, typ)))
+                })
+                .collect::<error::Result<Vec<_>>>()?;
+            if values.len() == 1 {
+                AttrValue::$scalar(values.remove(0))
+            } else {
+                AttrValue::$vec(values)
+            }
+        }};
+    }
+    Ok(match typ {
+        "byte" => numeric!(i8, Schar, Schars),
+        "ubyte" => numeric!(u8, Uchar, Uchars),
+        "short" => numeric!(i16, Short, Shorts),
+        "ushort" => numeric!(u16, Ushort, Ushorts),
+        "int" => numeric!(i32, Int, Ints),
+        "uint" => numeric!(u32, Uint, Uints),
+        "long" | "int64" => numeric!(i64, Longlong, Longlongs),
+        "ulong" | "uint64" => numeric!(u64, Ulonglong, Ulonglongs),
+        "float" => numeric!(f32, Float, Floats),
+        "double" => numeric!(f64, Double, Doubles),
+        x => return Err(unsupported(&format!("attribute type {}", x))),
+    })
+}
+
+/// Applies an `<attribute>` element, adding, renaming or changing an attribute
+fn update_attribute(attributes: &mut Vec<Attribute>, node: roxmltree::Node) -> error::Result<()> {
+    let name = required(node, "name")?;
+    let orgname = node.attribute("orgName").unwrap_or(name);
+    let has_value = node.attribute("value").is_some() || node.text().is_some();
+    let existing = attributes.iter().position(|a| a.name == orgname);
+    match existing {
+        Some(i) => {
+            attributes[i].name = name.into();
+            if has_value {
+                attributes[i].value = attribute_value(node)?;
+            }
+        }
+        None if has_value => attributes.push(Attribute {
+            name: name.into(),
+            value: attribute_value(node)?,
+        }),
+        None => {
+            return Err(error::Error::NotFound(format!("attribute {}", orgname)));
+        }
+    }
+    Ok(())
+}
+
+fn remove_attribute(attributes: &mut Vec<Attribute>, node: roxmltree::Node) -> error::Result<()> {
+    let name = required(node, "name")?;
+    attributes.retain(|a| a.name != name);
+    Ok(())
+}
+
+/// Applies a `<dimension>` element, which can only rename dimensions
+fn rename_dimension(dataset: &mut Dataset, node: roxmltree::Node) -> error::Result<()> {
+    let name = required(node, "name")?;
+    let orgname = node.attribute("orgName").unwrap_or(name);
+    if dataset.dims.iter().all(|d| d.name != orgname) {
+        return Err(unsupported(&format!(
+            "adding the dimension {} is not supported",
+            name
+        )));
+    }
+    let dims = dataset.dims.iter_mut().chain(
+        dataset
+            .vars
+            .iter_mut()
+            .flat_map(|v| v.dimensions.iter_mut()),
+    );
+    for d in dims {
+        if d.name == orgname {
+            d.name = name.into();
+        }
+    }
+    Ok(())
+}
+
+/// Applies a `<variable>` element, renaming and changing attributes
+fn update_variable(dataset: &mut Dataset, node: roxmltree::Node) -> error::Result<()> {
+    let name = required(node, "name")?;
+    let orgname = node.attribute("orgName").unwrap_or(name);
+    let var = match dataset.vars.iter_mut().find(|v| v.name == orgname) {
+        Some(var) => var,
+        None => {
+            return Err(unsupported(&format!(
+                "adding the variable {} is not supported",
+                name
+            )))
+        }
+    };
+    var.name = name.into();
+    for node in node.children().filter(roxmltree::Node::is_element) {
+        match node.tag_name().name() {
+            "attribute" => update_attribute(&mut var.attributes, node)?,
+            "remove" if node.attribute("type") == Some("attribute") => {
+                remove_attribute(&mut var.attributes, node)?;
+            }
+            x => return Err(unsupported(&format!("<{}> in <variable>", x))),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,546 @@
+//! Declarative descriptions of the layout of a dataset
+//!
+//! A [`Schema`] lists the dimensions, variables, attributes and groups
+//! a dataset must have. Files can be validated against a schema, and
+//! a schema can define the structure of a new, empty file. With the
+//! `serde` feature schemas can be read from e.g. JSON or TOML
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use netcdf::schema::{Attribute, Dimension, Schema, Type, Variable};
+//!
+//! let schema = Schema {
+//!     dimensions: vec![Dimension::unlimited("time"), Dimension::new("x", 10)],
+//!     variables: vec![Variable {
+//!         attributes: vec![Attribute::allowing("units", &["K", "degC"])],
+//!         ..Variable::new("temperature", Type::Float, &["time", "x"])
+//!     }],
+//!     attributes: vec![Attribute::new("title")],
+//!     ..Default::default()
+//! };
+//!
+//! let file = netcdf::open("model.nc")?;
+//! for violation in schema.validate(&file)? {
+//!     println!("{}", violation);
+//! }
+//! # Ok(()) }
+//! ```
+
+use super::attribute::{AttrValue, Attribute as NcAttribute};
+use super::cdl;
+use super::convert;
+use super::dimension;
+use super::error;
+use super::file::{File, MutableFile};
+use super::group;
+use super::variable::{Variable as NcVariable, VariableMut};
+use super::LOCK;
+use netcdf_sys::*;
+
+/// Basic types of variables and attributes, named as in CDL
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Type {
+    Byte,
+    Char,
+    Short,
+    Int,
+    Float,
+    Double,
+    Ubyte,
+    Ushort,
+    Uint,
+    Int64,
+    Uint64,
+    String,
+}
+
+impl Type {
+    /// Type as used by the netCDF library, such as `NC_FLOAT`
+    pub fn nc_type(self) -> nc_type {
+        match self {
+            Self::Byte => NC_BYTE,
+            Self::Char => NC_CHAR,
+            Self::Short => NC_SHORT,
+            Self::Int => NC_INT,
+            Self::Float => NC_FLOAT,
+            Self::Double => NC_DOUBLE,
+            Self::Ubyte => NC_UBYTE,
+            Self::Ushort => NC_USHORT,
+            Self::Uint => NC_UINT,
+            Self::Int64 => NC_INT64,
+            Self::Uint64 => NC_UINT64,
+            Self::String => NC_STRING,
+        }
+    }
+
+    /// Attribute value written for attributes without allowed values
+    fn default_value(self) -> AttrValue {
+        match self {
+            Self::Byte => AttrValue::Schar(0),
+            Self::Short => AttrValue::Short(0),
+            Self::Int => AttrValue::Int(0),
+            Self::Float => AttrValue::Float(0.0),
+            Self::Double => AttrValue::Double(0.0),
+            Self::Ubyte => AttrValue::Uchar(0),
+            Self::Ushort => AttrValue::Ushort(0),
+            Self::Uint => AttrValue::Uint(0),
+            Self::Int64 => AttrValue::Longlong(0),
+            Self::Uint64 => AttrValue::Ulonglong(0),
+            Self::Char | Self::String => AttrValue::Str(String::new()),
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = cdl::type_name(self.nc_type()).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", name)
+    }
+}
+
+/// Layout of a group, or of a whole file
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Schema {
+    /// Name of the group, ignored for the root group
+    pub name: String,
+    /// Dimensions of this group
+    pub dimensions: Vec<Dimension>,
+    /// Variables of this group
+    pub variables: Vec<Variable>,
+    /// Attributes of this group
+    pub attributes: Vec<Attribute>,
+    /// Subgroups
+    pub groups: Vec<Schema>,
+}
+
+/// Required dimension
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Dimension {
+    /// Name of the dimension
+    pub name: String,
+    /// Required length, any length is allowed if not given
+    pub len: Option<usize>,
+    /// Whether the dimension must be unlimited
+    pub unlimited: bool,
+}
+
+impl Dimension {
+    /// Dimension of a fixed length
+    pub fn new(name: &str, len: usize) -> Self {
+        Self {
+            name: name.into(),
+            len: Some(len),
+            unlimited: false,
+        }
+    }
+    /// Unlimited dimension of any length
+    pub fn unlimited(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            len: None,
+            unlimited: true,
+        }
+    }
+}
+
+/// Required variable
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variable {
+    /// Name of the variable
+    pub name: String,
+    /// Type of the variable
+    pub vartype: Type,
+    /// Names of the dimensions of the variable
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dimensions: Vec<String>,
+    /// Attributes of the variable
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: Vec<Attribute>,
+    /// The variable may be absent, but must conform if present
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub optional: bool,
+}
+
+impl Variable {
+    /// Required variable without attributes
+    pub fn new(name: &str, vartype: Type, dimensions: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            vartype,
+            dimensions: dimensions.iter().map(|&d| d.into()).collect(),
+            attributes: Vec::new(),
+            optional: false,
+        }
+    }
+}
+
+/// Required attribute
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Attribute {
+    /// Name of the attribute
+    pub name: String,
+    /// Required type, any type is allowed if not given
+    pub attrtype: Option<Type>,
+    /// Allowed values, any value is allowed if empty
+    pub allowed: Vec<AttrValue>,
+    /// The attribute may be absent, but must conform if present
+    pub optional: bool,
+}
+
+impl Attribute {
+    /// Required attribute of any type and value
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+    /// Required text attribute, with one of the given values
+    pub fn allowing(name: &str, values: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            attrtype: Some(Type::Char),
+            allowed: values.iter().map(|&v| v.into()).collect(),
+            optional: false,
+        }
+    }
+}
+
+/// A way in which a dataset does not conform to a schema.
+///
+/// Paths are given as `/group/name`, and attributes are appended
+/// after a colon, as `/group/variable:units` or `/group:title`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Violation {
+    /// Required group, dimension, variable or attribute is missing
+    Missing {
+        /// Path of the missing item
+        path: String,
+    },
+    /// Dimension of another length
+    DimensionLength {
+        /// Path of the dimension
+        path: String,
+        /// Length required by the schema
+        expected: usize,
+        /// Length in the dataset
+        found: usize,
+    },
+    /// Dimension is not unlimited
+    NotUnlimited {
+        /// Path of the dimension
+        path: String,
+    },
+    /// Variable or attribute of another type
+    Type {
+        /// Path of the variable or attribute
+        path: String,
+        /// Type required by the schema
+        expected: Type,
+        /// Type in the dataset
+        found: nc_type,
+    },
+    /// Variable defined over other dimensions
+    Dimensions {
+        /// Path of the variable
+        path: String,
+        /// Dimension names required by the schema
+        expected: Vec<String>,
+        /// Dimension names in the dataset
+        found: Vec<String>,
+    },
+    /// Attribute value which is not allowed
+    Value {
+        /// Path of the attribute
+        path: String,
+        /// Value in the dataset
+        found: AttrValue,
+    },
+}
+
+impl Violation {
+    /// Path of the group, dimension, variable or attribute
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Missing { path }
+            | Self::DimensionLength { path, .. }
+            | Self::NotUnlimited { path }
+            | Self::Type { path, .. }
+            | Self::Dimensions { path, .. }
+            | Self::Value { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Missing { path } => write!(f, "{}: missing", path),
+            Self::DimensionLength {
+                path,
+                expected,
+                found,
+            } => write!(f, "{}: length {} instead of {}", path, found, expected),
+            Self::NotUnlimited { path } => write!(f, "{}: not unlimited", path),
+            Self::Type {
+                path,
+                expected,
+                found,
+            } => {
+                let found = cdl::type_name(*found).map_or_else(|_| found.to_string(), Into::into);
+                write!(f, "{}: type {} instead of {}", path, found, expected)
+            }
+            Self::Dimensions {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: dimensions ({}) instead of ({})",
+                path,
+                found.join(", "),
+                expected.join(", ")
+            ),
+            Self::Value { path, found } => write!(f, "{}: {} is not allowed", path, found),
+        }
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", path, name)
+    }
+}
+
+impl Schema {
+    /// Checks `file` against the schema, returning all violations.
+    /// Items in the file which are not in the schema are allowed
+    ///
+    /// # Errors
+    ///
+    /// Reading from the file failed
+    pub fn validate(&self, file: &File) -> error::Result<Vec<Violation>> {
+        let mut violations = Vec::new();
+        self.validate_group(file.ncid(), "/", &mut violations)?;
+        Ok(violations)
+    }
+
+    fn validate_group(
+        &self,
+        ncid: nc_type,
+        path: &str,
+        violations: &mut Vec<Violation>,
+    ) -> error::Result<()> {
+        let dims = dimension::dimensions_from_location(ncid)?.collect::<error::Result<Vec<_>>>()?;
+        for d in &self.dimensions {
+            let path = join(path, &d.name);
+            let found = match dims.iter().find(|x| x.name() == d.name) {
+                Some(found) => found,
+                None => {
+                    violations.push(Violation::Missing { path });
+                    continue;
+                }
+            };
+            match d.len {
+                Some(len) if len != found.len() => violations.push(Violation::DimensionLength {
+                    path: path.clone(),
+                    expected: len,
+                    found: found.len(),
+                }),
+                _ => {}
+            }
+            if d.unlimited && !dimension::is_unlimited_at(found.id.ncid, found.id.dimid)? {
+                violations.push(Violation::NotUnlimited { path });
+            }
+        }
+
+        validate_attributes(&self.attributes, ncid, None, path, violations)?;
+
+        for v in &self.variables {
+            let path = join(path, &v.name);
+            let found = match NcVariable::find_from_name(ncid, &v.name)? {
+                Some(found) => found,
+                None => {
+                    if !v.optional {
+                        violations.push(Violation::Missing { path });
+                    }
+                    continue;
+                }
+            };
+            if found.vartype() != v.vartype.nc_type() {
+                violations.push(Violation::Type {
+                    path: path.clone(),
+                    expected: v.vartype,
+                    found: found.vartype(),
+                });
+            }
+            let dims = found
+                .dimensions()
+                .iter()
+                .map(dimension::Dimension::name)
+                .collect::<Vec<_>>();
+            if dims != v.dimensions {
+                violations.push(Violation::Dimensions {
+                    path: path.clone(),
+                    expected: v.dimensions.clone(),
+                    found: dims,
+                });
+            }
+            validate_attributes(&v.attributes, ncid, Some(found.varid), &path, violations)?;
+        }
+
+        let has_groups = convert::format_at(ncid)? == convert::Format::Netcdf4;
+        for g in &self.groups {
+            let path = join(path, &g.name);
+            let found = if has_groups {
+                group::group_from_name(ncid, &g.name)?
+            } else {
+                None
+            };
+            match found {
+                Some(found) => g.validate_group(found.ncid, &path, violations)?,
+                None => violations.push(Violation::Missing { path }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Defines the dimensions, variables, attributes and groups of
+    /// the schema in `file`. Attributes get their first allowed value,
+    /// or an empty or zero value if any value is allowed
+    ///
+    /// # Errors
+    ///
+    /// Items of the schema already exist in the file, or dimensions
+    /// which are not unlimited do not have a length
+    pub fn define(&self, file: &mut MutableFile) -> error::Result<()> {
+        let _l = LOCK.lock();
+        self.define_at(file.ncid())
+    }
+
+    /// Creates a file containing the items of the schema, see [`Schema::define`]
+    ///
+    /// # Errors
+    ///
+    /// The file could not be created, or the schema could not be defined
+    pub fn create<P>(&self, path: P) -> error::Result<MutableFile>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let mut file = super::create(path)?;
+        self.define(&mut file)?;
+        Ok(file)
+    }
+
+    fn define_at(&self, ncid: nc_type) -> error::Result<()> {
+        for d in &self.dimensions {
+            let len = match (d.unlimited, d.len) {
+                (true, _) => 0,
+                (false, Some(len)) => len,
+                (false, None) => {
+                    return Err(error::Error::Str(format!(
+                        "dimension {} needs a length",
+                        d.name
+                    )))
+                }
+            };
+            dimension::add_dimension_at(ncid, &d.name, len)?;
+        }
+        define_attributes(&self.attributes, ncid, NC_GLOBAL)?;
+        for v in &self.variables {
+            let dims = v.dimensions.iter().map(String::as_str).collect::<Vec<_>>();
+            let var = VariableMut::add_from_str(ncid, v.vartype.nc_type(), &v.name, &dims)?;
+            define_attributes(&v.attributes, ncid, var.varid)?;
+        }
+        for g in &self.groups {
+            let group = group::GroupMut::add_group_at(ncid, &g.name)?;
+            g.define_at(group.ncid)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_attributes(
+    attributes: &[Attribute],
+    ncid: nc_type,
+    varid: Option<nc_type>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) -> error::Result<()> {
+    for a in attributes {
+        let path = format!("{}:{}", path, a.name);
+        let found = {
+            // Need to lock when reading the first attribute
+            let _l = LOCK.lock();
+            NcAttribute::find_from_name(ncid, varid, &a.name)?
+        };
+        let found = match found {
+            Some(found) => found,
+            None => {
+                if !a.optional {
+                    violations.push(Violation::Missing { path });
+                }
+                continue;
+            }
+        };
+        let typ = found.typ()?;
+        match a.attrtype {
+            Some(expected) if expected.nc_type() != typ => {
+                violations.push(Violation::Type {
+                    path: path.clone(),
+                    expected,
+                    found: typ,
+                });
+            }
+            _ => {}
+        }
+        let value = found.value()?;
+        if !a.allowed.is_empty() && !a.allowed.iter().any(|x| same_value(x, &value)) {
+            violations.push(Violation::Value { path, found: value });
+        }
+    }
+    Ok(())
+}
+
+/// Whether the attribute values are equal, where
+/// a single `NC_STRING` is the same as the text
+fn same_value(allowed: &AttrValue, found: &AttrValue) -> bool {
+    match (allowed, found) {
+        (AttrValue::Str(x), AttrValue::Strs(y)) => y.len() == 1 && *x == y[0],
+        _ => allowed == found,
+    }
+}
+
+fn define_attributes(attributes: &[Attribute], ncid: nc_type, varid: nc_type) -> error::Result<()> {
+    for a in attributes {
+        let value = a.allowed.first().cloned().unwrap_or_else(|| {
+            a.attrtype
+                .map_or_else(|| AttrValue::Str(String::new()), Type::default_value)
+        });
+        match (a.attrtype, value) {
+            (Some(Type::String), AttrValue::Str(s)) => {
+                NcAttribute::put_strings(ncid, varid, &a.name, &[s])?;
+            }
+            (Some(Type::String), AttrValue::Strs(s)) => {
+                NcAttribute::put_strings(ncid, varid, &a.name, &s)?;
+            }
+            (_, value) => {
+                NcAttribute::put(ncid, varid, &a.name, value)?;
+            }
+        }
+    }
+    Ok(())
+}
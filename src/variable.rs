@@ -9,11 +9,13 @@ use super::LOCK;
 #[cfg(feature = "ndarray")]
 use ndarray::ArrayD;
 use netcdf_sys::*;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::ffi::CStr;
 use std::marker::PhantomData;
 use std::marker::Sized;
 
+mod types;
+
 #[allow(clippy::doc_markdown)]
 /// This struct defines a `netCDF` variable.
 #[derive(Debug, Clone)]
@@ -24,6 +26,8 @@ pub struct Variable<'g> {
     pub(crate) vartype: nc_type,
     pub(crate) ncid: nc_type,
     pub(crate) varid: nc_type,
+    /// How values are converted when reading and writing
+    pub(crate) conversion: ConversionMode,
     pub(crate) _group: PhantomData<&'g nc_type>,
 }
 
@@ -53,6 +57,54 @@ pub enum Endianness {
     Big,
 }
 
+#[cfg(feature = "mpi")]
+/// Access mode of a variable in a file opened for parallel I/O
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParallelAccess {
+    /// Each process reads and writes independently of the others
+    Independent,
+    /// All processes must take part in every read and write
+    Collective,
+}
+
+/// How values are converted between the type of a variable
+/// and the type used when reading or writing the variable
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ConversionMode {
+    /// Values are converted by the netCDF library. Values which do not
+    /// fit in the type converted to give [`error::Error::Range`], and
+    /// nothing is written when putting values
+    #[default]
+    Converting,
+    /// Only the type of the variable can be used,
+    /// other types give [`error::Error::TypeMismatch`]
+    Exact,
+}
+
+/// Values of a variable which are not data, see [`Variable::mask`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mask {
+    /// `_FillValue`, or the default fill value of the type
+    pub fill_value: Option<f64>,
+    /// Values of `missing_value`
+    pub missing_values: Vec<f64>,
+    /// Smallest valid value, from `valid_range` or `valid_min`
+    pub valid_min: Option<f64>,
+    /// Largest valid value, from `valid_range` or `valid_max`
+    pub valid_max: Option<f64>,
+}
+
+impl Mask {
+    /// Whether the value is not data
+    pub fn is_masked(&self, value: f64) -> bool {
+        let equal = |x: &f64| *x == value || (x.is_nan() && value.is_nan());
+        self.fill_value.as_ref().is_some_and(equal)
+            || self.missing_values.iter().any(equal)
+            || self.valid_min.is_some_and(|min| value < min)
+            || self.valid_max.is_some_and(|max| value > max)
+    }
+}
+
 #[allow(clippy::len_without_is_empty)]
 impl<'g> Variable<'g> {
     pub(crate) fn find_from_name(ncid: nc_type, name: &str) -> error::Result<Option<Variable<'g>>> {
@@ -89,44 +141,73 @@ impl<'g> Variable<'g> {
             ncid,
             varid,
             vartype: xtype,
+            conversion: ConversionMode::Converting,
             _group: PhantomData,
         }))
     }
 
-    /// Get name of variable
+    /// Get name of variable. Bytes which are not valid
+    /// utf-8 are replaced, see [`Self::try_name`]
+    ///
+    /// # Panics
+    ///
+    /// The name could not be read from the file
     pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.name_bytes().unwrap()).into_owned()
+    }
+    /// Get name of variable
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read, or is not valid utf-8
+    pub fn try_name(&self) -> error::Result<String> {
+        Ok(String::from_utf8(self.name_bytes()?)?)
+    }
+    /// Get name of variable as stored in the file
+    ///
+    /// # Errors
+    ///
+    /// The name could not be read
+    pub fn name_bytes(&self) -> error::Result<Vec<u8>> {
         let mut name = vec![0_u8; NC_MAX_NAME as usize + 1];
         unsafe {
             error::checked(nc_inq_varname(
                 self.ncid,
                 self.varid,
                 name.as_mut_ptr() as *mut _,
-            ))
-            .unwrap();
+            ))?;
         }
         let zeropos = name
             .iter()
             .position(|&x| x == 0)
             .unwrap_or_else(|| name.len());
         name.resize(zeropos, 0);
-
-        String::from_utf8(name).expect("Variable name contained invalid sequence")
+        Ok(name)
     }
     /// Get an attribute of this variable
     pub fn attribute<'a>(&'a self, name: &str) -> Option<Attribute<'a>> {
         // Need to lock when reading the first attribute (per variable)
-        let _l = super::LOCK.lock().unwrap();
+        let _l = super::LOCK.lock();
         Attribute::find_from_name(self.ncid, Some(self.varid), name)
             .expect("Could not retrieve attribute")
     }
     /// Iterator over all the attributes of this variable
     pub fn attributes(&self) -> impl Iterator<Item = Attribute> {
         // Need to lock when reading the first attribute (per variable)
-        let _l = super::LOCK.lock().unwrap();
+        let _l = super::LOCK.lock();
         crate::attribute::AttributeIterator::new(self.ncid, Some(self.varid))
             .expect("Could not get attributes")
             .map(Result::unwrap)
     }
+    /// Copies the definition, attributes and data of the variable to a
+    /// file or group, possibly in another file. Dimensions which can
+    /// not be found by name are added to the file or group
+    pub fn copy_variable_to<'t, T>(&self, target: &'t mut T) -> error::Result<VariableMut<'t>>
+    where
+        T: super::group::CopyTarget,
+    {
+        target.copy_variable(self)
+    }
     /// Dimensions for a variable
     pub fn dimensions(&self) -> &[Dimension] {
         &self.dimensions
@@ -141,6 +222,42 @@ impl<'g> Variable<'g> {
     pub fn len(&self) -> usize {
         self.dimensions.iter().map(Dimension::len).product()
     }
+    /// How values are converted when reading and writing
+    pub fn conversion(&self) -> ConversionMode {
+        self.conversion
+    }
+    /// Sets how values are converted when reading and writing
+    pub fn with_conversion(mut self, mode: ConversionMode) -> Self {
+        self.conversion = mode;
+        self
+    }
+    /// Coordinate variables of the dimensions of this variable,
+    /// in the order of the dimensions. Dimensions without a
+    /// coordinate variable are left out
+    pub fn coordinate_variables(&self) -> impl Iterator<Item = Variable<'g>> + '_ {
+        self.dimensions
+            .iter()
+            .filter_map(Dimension::coordinate_variable)
+    }
+    /// Auxiliary coordinate variables named in the CF `coordinates`
+    /// attribute. Names are searched for in the group of this variable
+    /// and then its parents, or taken from the root group when given
+    /// as a path starting with `/`. Names which can not be found are
+    /// left out
+    pub fn auxiliary_coordinates(&self) -> impl Iterator<Item = Variable<'g>> {
+        let names = match self.attribute("coordinates").map(|a| a.value()) {
+            Some(Ok(AttrValue::Str(s))) => vec![s],
+            Some(Ok(AttrValue::Strs(s))) => s,
+            _ => Vec::new(),
+        };
+        let ncid = self.ncid;
+        names
+            .iter()
+            .flat_map(|s| s.split_whitespace())
+            .filter_map(|name| find_visible(ncid, name).unwrap())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
     /// Get endianness of the variable.
     ///
     /// # Errors
@@ -158,8 +275,31 @@ impl<'g> Variable<'g> {
             _ => Err(NC_EVARMETA.into()),
         }
     }
+    #[cfg(feature = "mpi")]
+    /// Set the access mode used by this process when reading and
+    /// writing this variable. Only valid for files opened for parallel
+    /// access, and must be called collectively.
+    ///
+    /// # Errors
+    ///
+    /// File not opened for parallel access
+    pub fn par_access(&self, access: ParallelAccess) -> error::Result<()> {
+        let access = match access {
+            ParallelAccess::Independent => NC_INDEPENDENT,
+            ParallelAccess::Collective => NC_COLLECTIVE,
+        };
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_var_par_access(self.ncid, self.varid, access))
+        }
+    }
 }
 impl<'g> VariableMut<'g> {
+    /// Sets how values are converted when reading and writing
+    pub fn with_conversion(mut self, mode: ConversionMode) -> Self {
+        self.0.conversion = mode;
+        self
+    }
     /// Sets compression on the variable. Must be set before filling in data.
     ///
     /// `deflate_level` can take a value 0..=9, with 0 being no
@@ -221,35 +361,117 @@ impl<'g> VariableMut<'g> {
 }
 
 impl<'g> Variable<'g> {
-    /// Checks for array mismatch
-    fn check_indices(&self, indices: &[usize], putting: bool) -> error::Result<()> {
-        if indices.len() != self.dimensions.len() {
-            return Err(error::Error::IndexLen);
+    /// Context of errors when accessing the hyperslab, where
+    /// an empty `count` is a single value
+    fn hyperslab_context(&self, start: &[usize], count: &[usize]) -> error::Context {
+        let count = if count.is_empty() {
+            vec![1; start.len()]
+        } else {
+            count.to_vec()
+        };
+        error::Context {
+            variable: Some(self.name()),
+            start: Some(start.to_vec()),
+            count: Some(count),
+            ..error::Context::at(self.ncid)
         }
-
-        for (d, i) in self.dimensions.iter().zip(indices) {
-            if d.is_unlimited() && putting {
-                continue;
-            }
-            if *i > d.len() {
-                return Err(error::Error::IndexMismatch);
+    }
+    /// Dimensions indexed when reading or writing `T`, without
+    /// the trailing dimensions holding the parts of single values
+    fn dimensions_of<T: Numeric>(&self) -> &[Dimension<'g>] {
+        let len = self
+            .dimensions
+            .len()
+            .saturating_sub(T::trailing_dimensions(self));
+        &self.dimensions[..len]
+    }
+    /// Checks `T` can be used with the conversion mode
+    fn check_type<T: Numeric>(&self) -> error::Result<()> {
+        if self.conversion == ConversionMode::Exact && !T::is_native(self) {
+            Err(error::Error::TypeMismatch)
+        } else {
+            Ok(())
+        }
+    }
+    /// Checks values to be written fit in the type of the variable
+    fn check_range<T: Numeric>(&self, values: &[T]) -> error::Result<()> {
+        if T::is_native(self) {
+            return Ok(());
+        }
+        match out_of_range(self.vartype, values) {
+            0 => Ok(()),
+            count => Err(error::Error::Range {
+                count,
+                total: values.len(),
+            }),
+        }
+    }
+    /// Counts the values which did not fit in the type of the
+    /// variable when the library reports `NC_ERANGE`
+    fn put_range_error<T: Numeric>(&self, e: error::Error, values: &[T]) -> error::Error {
+        if !matches!(e, error::Error::Netcdf(NC_ERANGE)) {
+            return e;
+        }
+        let count = values
+            .iter()
+            .filter(|v| !value_fits_type(self.vartype, *v))
+            .count();
+        error::Error::Range {
+            count: count.max(1),
+            total: values.len(),
+        }
+    }
+    /// Counts the values which did not fit in `T` when the library
+    /// reports `NC_ERANGE`, by reading the hyperslab again as doubles
+    fn range_error<T: Numeric>(
+        &self,
+        e: error::Error,
+        indices: &[usize],
+        slice_len: &[usize],
+        strides: Option<&[isize]>,
+    ) -> error::Error {
+        if !matches!(e, error::Error::Netcdf(NC_ERANGE)) {
+            return e;
+        }
+        if T::trailing_dimensions(self) > 0 {
+            return error::Error::Range {
+                count: 1,
+                total: slice_len.iter().product(),
+            };
+        }
+        let ones;
+        let slice_len = if slice_len.is_empty() {
+            ones = vec![1; indices.len()];
+            &ones
+        } else {
+            slice_len
+        };
+        let total = slice_len.iter().product();
+        let mut values = vec![0.0_f64; total];
+        let read = unsafe {
+            match strides {
+                Some(strides) => {
+                    f64::get_values_strided(self, indices, slice_len, strides, values.as_mut_ptr())
+                }
+                None => f64::variable_to_ptr(self, indices, slice_len, values.as_mut_ptr()),
             }
+        };
+        let count = match read {
+            Ok(()) => values.iter().filter(|&&v| !fits::<T>(v)).count(),
+            Err(_) => 0,
+        };
+        error::Error::Range {
+            count: count.max(1),
+            total,
         }
-
-        Ok(())
+    }
+    /// Checks for array mismatch
+    fn check_indices(&self, indices: &[usize], putting: bool) -> error::Result<()> {
+        check_indices(&self.dimensions, indices, putting)
     }
     /// Create a default [0, 0, ..., 0] offset
     fn default_indices(&self, putting: bool) -> error::Result<Vec<usize>> {
-        self.dimensions
-            .iter()
-            .map(|d| {
-                if d.len() > 0 || putting {
-                    Ok(0)
-                } else {
-                    Err(error::Error::IndexMismatch)
-                }
-            })
-            .collect()
+        default_indices(&self.dimensions, putting)
     }
 
     /// Assumes indices is valid for this variable
@@ -260,39 +482,7 @@ impl<'g> Variable<'g> {
         sizelen: &[usize],
         putting: bool,
     ) -> error::Result<()> {
-        if sizelen.len() != self.dimensions.len() {
-            return Err(error::Error::SliceLen);
-        }
-
-        for ((i, s), d) in indices.iter().zip(sizelen).zip(&self.dimensions) {
-            if *s == 0 {
-                return Err(error::Error::ZeroSlice);
-            }
-            if i.checked_add(*s).is_none() {
-                return Err(error::Error::Overflow);
-            }
-            if i + s > d.len() {
-                if !putting {
-                    return Err(error::Error::SliceMismatch);
-                }
-                if !d.is_unlimited() {
-                    return Err(error::Error::SliceMismatch);
-                }
-            }
-        }
-
-        let thislen = sizelen
-            .iter()
-            .fold(1_usize, |acc, &x| acc.saturating_mul(x));
-        if thislen == usize::max_value() {
-            return Err(error::Error::Overflow);
-        }
-
-        if totallen != thislen {
-            return Err(error::Error::BufferLen(totallen, thislen));
-        }
-
-        Ok(())
+        check_sizelen(&self.dimensions, totallen, indices, sizelen, putting)
     }
 
     /// Assumes indices is valid for this variable
@@ -302,56 +492,212 @@ impl<'g> Variable<'g> {
         indices: &[usize],
         putting: bool,
     ) -> error::Result<Vec<usize>> {
-        let num_unlims = self
-            .dimensions
-            .iter()
-            .fold(0, |acc, x| acc + x.is_unlimited() as usize);
-        if num_unlims > 1 {
-            return Err(error::Error::Ambiguous);
-        }
+        default_sizelen(&self.dimensions, totallen, indices, putting)
+    }
+}
+
+/// Whether the value can be represented as `T`. Whole numbers
+/// are compared as integers, as the limits of the 64-bit
+/// integer types are rounded as doubles
+fn fits<T: Numeric>(value: f64) -> bool {
+    // -2^127 and 2^127
+    const I128_LIMIT: f64 = 1.701_411_834_604_692_3e38;
+    if value.is_nan() {
+        T::NCTYPE == NC_FLOAT || T::NCTYPE == NC_DOUBLE
+    } else if value.fract() == 0.0 && (-I128_LIMIT..I128_LIMIT).contains(&value) {
+        #[allow(clippy::cast_possible_truncation)]
+        T::fits_i128(value as i128)
+    } else {
+        T::MIN <= value && value <= T::MAX
+    }
+}
 
-        let mut sizelen = Vec::with_capacity(self.dimensions.len());
+/// Whether the integer can be represented in the netCDF type,
+/// types which are not numeric are left to the library
+fn fits_type_i128(vartype: nc_type, value: i128) -> bool {
+    match vartype {
+        NC_BYTE => i8::fits_i128(value),
+        NC_UBYTE => u8::fits_i128(value),
+        NC_SHORT => i16::fits_i128(value),
+        NC_USHORT => u16::fits_i128(value),
+        NC_INT => i32::fits_i128(value),
+        NC_UINT => u32::fits_i128(value),
+        NC_INT64 => i64::fits_i128(value),
+        NC_UINT64 => u64::fits_i128(value),
+        _ => true,
+    }
+}
 
-        let mut unlim_pos = None;
-        for (pos, (&i, d)) in indices.iter().zip(&self.dimensions).enumerate() {
-            if i >= d.len() {
-                if !d.is_unlimited() {
-                    return Err(error::Error::SliceMismatch);
-                }
-                if !putting {
-                    return Err(error::Error::SliceMismatch);
-                }
-                unlim_pos = Some(pos);
-                sizelen.push(1);
-            } else if putting && d.is_unlimited() {
-                unlim_pos = Some(pos);
-                sizelen.push(1);
+/// Whether the value can be represented in the netCDF type
+fn value_fits_type<T: Numeric>(vartype: nc_type, value: &T) -> bool {
+    match value.to_i128() {
+        Some(x) => fits_type_i128(vartype, x),
+        None => fits_type(vartype, value.to_f64()),
+    }
+}
+
+/// Whether the value can be represented in the netCDF type,
+/// types which are not numeric are left to the library
+fn fits_type(vartype: nc_type, value: f64) -> bool {
+    match vartype {
+        NC_BYTE => fits::<i8>(value),
+        NC_UBYTE => fits::<u8>(value),
+        NC_SHORT => fits::<i16>(value),
+        NC_USHORT => fits::<u16>(value),
+        NC_INT => fits::<i32>(value),
+        NC_UINT => fits::<u32>(value),
+        NC_INT64 => fits::<i64>(value),
+        NC_UINT64 => fits::<u64>(value),
+        NC_FLOAT => fits::<f32>(value),
+        NC_DOUBLE => fits::<f64>(value),
+        _ => true,
+    }
+}
+
+/// Values which do not fit in the variable type. Signed and unsigned
+/// bytes are reinterpreted without range checks by the library
+/// for classic files, and are left to the library
+fn out_of_range<T: Numeric>(vartype: nc_type, values: &[T]) -> usize {
+    if (T::NCTYPE == NC_BYTE && vartype == NC_UBYTE)
+        || (T::NCTYPE == NC_UBYTE && vartype == NC_BYTE)
+    {
+        return 0;
+    }
+    values
+        .iter()
+        .filter(|v| !value_fits_type(vartype, *v))
+        .count()
+}
+
+/// Checks for array mismatch
+fn check_indices(dimensions: &[Dimension], indices: &[usize], putting: bool) -> error::Result<()> {
+    if indices.len() != dimensions.len() {
+        return Err(error::Error::IndexLen);
+    }
+
+    for (d, i) in dimensions.iter().zip(indices) {
+        if d.is_unlimited() && putting {
+            continue;
+        }
+        if *i > d.len() {
+            return Err(error::Error::IndexMismatch);
+        }
+    }
+
+    Ok(())
+}
+/// Create a default [0, 0, ..., 0] offset
+fn default_indices(dimensions: &[Dimension], putting: bool) -> error::Result<Vec<usize>> {
+    dimensions
+        .iter()
+        .map(|d| {
+            if d.len() > 0 || putting {
+                Ok(0)
             } else {
-                sizelen.push(d.len() - i);
+                Err(error::Error::IndexMismatch)
+            }
+        })
+        .collect()
+}
+
+/// Assumes indices is valid for these dimensions
+fn check_sizelen(
+    dimensions: &[Dimension],
+    totallen: usize,
+    indices: &[usize],
+    sizelen: &[usize],
+    putting: bool,
+) -> error::Result<()> {
+    if sizelen.len() != dimensions.len() {
+        return Err(error::Error::SliceLen);
+    }
+
+    for ((i, s), d) in indices.iter().zip(sizelen).zip(dimensions) {
+        if *s == 0 {
+            return Err(error::Error::ZeroSlice);
+        }
+        if i.checked_add(*s).is_none() {
+            return Err(error::Error::Overflow);
+        }
+        if i + s > d.len() {
+            if !putting {
+                return Err(error::Error::SliceMismatch);
+            }
+            if !d.is_unlimited() {
+                return Err(error::Error::SliceMismatch);
             }
         }
+    }
 
-        if let Some(pos) = unlim_pos {
-            let l = sizelen
-                .iter()
-                .fold(1_usize, |acc, &x| acc.saturating_mul(x));
-            if l == usize::max_value() {
-                return Err(error::Error::Overflow);
+    let thislen = sizelen
+        .iter()
+        .fold(1_usize, |acc, &x| acc.saturating_mul(x));
+    if thislen == usize::max_value() {
+        return Err(error::Error::Overflow);
+    }
+
+    if totallen != thislen {
+        return Err(error::Error::BufferLen(totallen, thislen));
+    }
+
+    Ok(())
+}
+
+/// Assumes indices is valid for these dimensions
+fn default_sizelen(
+    dimensions: &[Dimension],
+    totallen: usize,
+    indices: &[usize],
+    putting: bool,
+) -> error::Result<Vec<usize>> {
+    let num_unlims = dimensions
+        .iter()
+        .fold(0, |acc, x| acc + x.is_unlimited() as usize);
+    if num_unlims > 1 {
+        return Err(error::Error::Ambiguous);
+    }
+
+    let mut sizelen = Vec::with_capacity(dimensions.len());
+
+    let mut unlim_pos = None;
+    for (pos, (&i, d)) in indices.iter().zip(dimensions).enumerate() {
+        if i >= d.len() {
+            if !d.is_unlimited() {
+                return Err(error::Error::SliceMismatch);
+            }
+            if !putting {
+                return Err(error::Error::SliceMismatch);
             }
-            sizelen[pos] = totallen / l;
+            unlim_pos = Some(pos);
+            sizelen.push(1);
+        } else if putting && d.is_unlimited() {
+            unlim_pos = Some(pos);
+            sizelen.push(1);
+        } else {
+            sizelen.push(d.len() - i);
         }
+    }
 
-        let wantedlen = sizelen
+    if let Some(pos) = unlim_pos {
+        let l = sizelen
             .iter()
             .fold(1_usize, |acc, &x| acc.saturating_mul(x));
-        if wantedlen == usize::max_value() {
+        if l == usize::max_value() {
             return Err(error::Error::Overflow);
         }
-        if totallen != wantedlen {
-            return Err(error::Error::BufferLen(totallen, wantedlen));
-        }
-        Ok(sizelen)
+        sizelen[pos] = totallen / l;
     }
+
+    let wantedlen = sizelen
+        .iter()
+        .fold(1_usize, |acc, &x| acc.saturating_mul(x));
+    if wantedlen == usize::max_value() {
+        return Err(error::Error::Overflow);
+    }
+    if totallen != wantedlen {
+        return Err(error::Error::BufferLen(totallen, wantedlen));
+    }
+    Ok(sizelen)
 }
 
 #[allow(clippy::doc_markdown)]
@@ -364,6 +710,46 @@ where
 {
     /// Constant corresponding to a netcdf type
     const NCTYPE: nc_type;
+    /// Smallest value of the type
+    const MIN: f64;
+    /// Largest value of the type
+    const MAX: f64;
+
+    /// The value as a double, for checking ranges
+    fn to_f64(&self) -> f64;
+    /// A double within the range of the type as the type
+    fn from_f64(value: f64) -> Self;
+    /// The value as an integer, for checking ranges without
+    /// rounding. `None` for types which are not integers
+    fn to_i128(&self) -> Option<i128> {
+        None
+    }
+    /// Whether the integer can be represented as the type
+    fn fits_i128(value: i128) -> bool {
+        #[allow(clippy::cast_precision_loss)]
+        let value = value as f64;
+        Self::MIN <= value && value <= Self::MAX
+    }
+
+    /// Whether the variable holds values of this type,
+    /// without conversion by the library
+    fn is_native(variable: &Variable) -> bool {
+        variable.vartype == Self::NCTYPE
+    }
+    /// Number of trailing dimensions of the variable which
+    /// hold the parts of single values
+    fn trailing_dimensions(_variable: &Variable) -> usize {
+        0
+    }
+    /// Type of new variables of this type in the group `ncid`,
+    /// called with the library lock held
+    ///
+    /// # Errors
+    ///
+    /// The type could not be defined in the group
+    fn variable_type(_ncid: nc_type) -> error::Result<nc_type> {
+        Ok(Self::NCTYPE)
+    }
 
     /// Returns a single indexed value of the variable as Self
     ///
@@ -448,6 +834,18 @@ where
 }
 
 #[allow(clippy::doc_markdown)]
+/// Exact range checks for integer types
+macro_rules! integer_range {
+    () => {
+        fn to_i128(&self) -> Option<i128> {
+            Some((*self).into())
+        }
+        fn fits_i128(value: i128) -> bool {
+            Self::try_from(value).is_ok()
+        }
+    };
+}
+
 /// This macro implements the trait Numeric for the type `sized_type`.
 ///
 /// The use of this macro reduce code duplication for the implementation of Numeric
@@ -464,10 +862,22 @@ macro_rules! impl_numeric {
         $nc_put_vara_type: ident,
         $nc_get_vars_type: ident,
         $nc_put_vars_type: ident,
+        $($extra: tt)*
     ) => {
         #[allow(clippy::use_self)] // False positives
         unsafe impl Numeric for $sized_type {
             const NCTYPE: nc_type = $nc_type;
+            const MIN: f64 = <$sized_type>::MIN as f64;
+            const MAX: f64 = <$sized_type>::MAX as f64;
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+            fn from_f64(value: f64) -> Self {
+                value as _
+            }
+
+            $($extra)*
 
             // fetch ONE value from variable using `$nc_get_var1`
             unsafe fn single_value_from_variable(
@@ -478,7 +888,7 @@ macro_rules! impl_numeric {
                 let mut buff: Self = 0 as _;
                 // Get a pointer to an array
                 let indices_ptr = indices.as_ptr();
-                let _g = LOCK.lock().unwrap();
+                let _g = LOCK.lock();
                 error::checked($nc_get_var1_type(
                     variable.ncid,
                     variable.varid,
@@ -494,7 +904,7 @@ macro_rules! impl_numeric {
                 slice_len: &[usize],
                 values: *mut Self,
             ) -> error::Result<()> {
-                let _l = LOCK.lock().unwrap();
+                let _l = LOCK.lock();
 
                 error::checked($nc_get_vara_type(
                     variable.ncid,
@@ -542,7 +952,7 @@ macro_rules! impl_numeric {
                 strides: &[isize],
                 values: *mut Self,
             ) -> error::Result<()> {
-                let _l = LOCK.lock().unwrap();
+                let _l = LOCK.lock();
                 error::checked($nc_get_vars_type(
                     variable.ncid,
                     variable.varid,
@@ -582,6 +992,7 @@ impl_numeric!(
     nc_put_vara_uchar,
     nc_get_vars_uchar,
     nc_put_vars_uchar,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -594,6 +1005,7 @@ impl_numeric!(
     nc_put_vara_schar,
     nc_get_vars_schar,
     nc_put_vars_schar,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -606,6 +1018,7 @@ impl_numeric!(
     nc_put_vara_short,
     nc_get_vars_short,
     nc_put_vars_short,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -618,6 +1031,7 @@ impl_numeric!(
     nc_put_vara_ushort,
     nc_get_vars_ushort,
     nc_put_vars_ushort,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -630,6 +1044,7 @@ impl_numeric!(
     nc_put_vara_int,
     nc_get_vars_int,
     nc_put_vars_int,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -642,6 +1057,7 @@ impl_numeric!(
     nc_put_vara_uint,
     nc_get_vars_uint,
     nc_put_vars_uint,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -654,6 +1070,7 @@ impl_numeric!(
     nc_put_vara_longlong,
     nc_get_vars_longlong,
     nc_put_vars_longlong,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -666,6 +1083,7 @@ impl_numeric!(
     nc_put_vara_ulonglong,
     nc_get_vars_ulonglong,
     nc_put_vars_ulonglong,
+    integer_range!();
 );
 
 impl_numeric!(
@@ -692,27 +1110,102 @@ impl_numeric!(
     nc_put_vars_double,
 );
 
-/// Holds the contents of a netcdf string. Use deref to get a `CStr`
-struct NcString {
-    data: *mut std::os::raw::c_char,
+/// Holds strings allocated by netcdf, which are
+/// released with `nc_free_string` on drop
+pub(crate) struct NcStrings {
+    data: Vec<*mut std::os::raw::c_char>,
 }
-impl NcString {
-    /// Create an `NcString`
-    unsafe fn from_ptr(ptr: *mut i8) -> Self {
-        Self { data: ptr }
+impl NcStrings {
+    /// Room for `len` strings, to be filled in by netcdf
+    pub(crate) fn with_len(len: usize) -> Self {
+        Self {
+            data: vec![std::ptr::null_mut(); len],
+        }
+    }
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut *mut std::os::raw::c_char {
+        self.data.as_mut_ptr()
+    }
+    /// Copies all strings, replacing invalid UTF-8 sequences
+    pub(crate) fn to_strings(&self) -> Vec<String> {
+        self.data
+            .iter()
+            .map(|&ptr| {
+                if ptr.is_null() {
+                    String::new()
+                } else {
+                    unsafe { CStr::from_ptr(ptr) }
+                        .to_string_lossy()
+                        .into_owned()
+                }
+            })
+            .collect()
     }
 }
-impl Drop for NcString {
+impl Drop for NcStrings {
     fn drop(&mut self) {
         unsafe {
-            error::checked(nc_free_string(1, &mut self.data)).unwrap();
+            // Can't really do much with an error here
+            let _err = error::checked(nc_free_string(self.data.len(), self.data.as_mut_ptr()));
         }
     }
 }
-impl std::ops::Deref for NcString {
-    type Target = CStr;
-    fn deref(&self) -> &Self::Target {
-        unsafe { CStr::from_ptr(self.data) }
+
+/// Strings converted for passing to netcdf
+pub(crate) struct CStrings {
+    strings: Vec<std::ffi::CString>,
+}
+impl CStrings {
+    pub(crate) fn new<S: AsRef<str>>(values: &[S]) -> error::Result<Self> {
+        let strings = values
+            .iter()
+            .map(|s| {
+                std::ffi::CString::new(s.as_ref())
+                    .map_err(|_| error::Error::Str("string contains interior NUL".into()))
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        Ok(Self { strings })
+    }
+    /// Pointers valid for the lifetime of `self`
+    pub(crate) fn as_ptrs(&self) -> Vec<*const std::os::raw::c_char> {
+        self.strings.iter().map(|s| s.as_ptr()).collect()
+    }
+}
+
+/// Encodings of character data supported through the `_Encoding` attribute
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum TextEncoding {
+    Utf8,
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Unknown encodings are interpreted as UTF-8
+    fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" => Self::Latin1,
+            _ => Self::Utf8,
+        }
+    }
+
+    fn decode(self, chars: &[u8]) -> String {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(chars).into_owned(),
+            Self::Latin1 => chars.iter().map(|&x| char::from(x)).collect(),
+        }
+    }
+
+    fn encode(self, s: &str) -> error::Result<Vec<u8>> {
+        match self {
+            Self::Utf8 => Ok(s.as_bytes().to_vec()),
+            Self::Latin1 => s
+                .chars()
+                .map(|c| {
+                    u8::try_from(u32::from(c)).map_err(|_| {
+                        error::Error::Str(format!("{:?} can not be encoded as ISO-8859-1", c))
+                    })
+                })
+                .collect(),
+        }
     }
 }
 
@@ -722,25 +1215,48 @@ impl<'g> VariableMut<'g> {
     where
         T: Into<AttrValue>,
     {
-        let _l = LOCK.lock().unwrap();
+        let _l = LOCK.lock();
         Attribute::put(self.ncid, self.varid, name, val.into())
     }
+    /// Copies an attribute, possibly from another file, to this variable
+    pub fn copy_attribute(&mut self, att: &Attribute) -> error::Result<Attribute<'_>> {
+        let _l = LOCK.lock();
+        att.copy_to(self.ncid, self.varid)
+    }
+    /// Adds an attribute to the variable, stored as `NC_STRING`
+    /// (instead of `NC_CHAR` as `add_attribute` does for a single string)
+    pub fn add_string_attribute<S>(
+        &mut self,
+        name: &str,
+        values: &[S],
+    ) -> error::Result<Attribute<'_>>
+    where
+        S: AsRef<str>,
+    {
+        let _l = LOCK.lock();
+        Attribute::put_strings(self.ncid, self.varid, name, values)
+    }
 }
 
 impl<'g> Variable<'g> {
     ///  Fetches one specific value at specific indices
     ///  indices must has the same length as self.dimensions.
     pub fn value<T: Numeric>(&self, indices: Option<&[usize]>) -> error::Result<T> {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
-            self.check_indices(x, false)?;
+            check_indices(dims, x, false)?;
             x
         } else {
-            indices_ = self.default_indices(false)?;
+            indices_ = default_indices(dims, false)?;
             &indices_
         };
 
-        unsafe { T::single_value_from_variable(self, indices) }
+        unsafe { T::single_value_from_variable(self, indices) }.map_err(|e| {
+            self.range_error::<T>(e, indices, &[], None)
+                .with_context(|| self.hyperslab_context(indices, &[]))
+        })
     }
 
     /// Reads a string variable. This involves two copies per read, and should
@@ -755,18 +1271,204 @@ impl<'g> Variable<'g> {
             &indices_
         };
 
-        let mut s: *mut std::os::raw::c_char = std::ptr::null_mut();
+        let mut strings = NcStrings::with_len(1);
         unsafe {
-            let _l = LOCK.lock().unwrap();
+            let _l = LOCK.lock();
             error::checked(nc_get_var1_string(
                 self.ncid,
                 self.varid,
                 indices.as_ptr(),
-                &mut s,
+                strings.as_mut_ptr(),
             ))?;
         }
-        let string = unsafe { NcString::from_ptr(s) };
-        Ok(string.to_string_lossy().into_owned())
+        Ok(strings.to_strings().remove(0))
+    }
+
+    /// Reads a slice of a string variable in one go, which is much
+    /// faster than reading element by element through `string_value`
+    ///
+    /// # Errors
+    ///
+    /// Not a string variable, or invalid indices or slice lengths
+    pub fn string_values(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<String>> {
+        if self.vartype != NC_STRING {
+            return Err(error::Error::TypeMismatch);
+        }
+        let indices_: Vec<usize>;
+        let indices = if let Some(x) = indices {
+            self.check_indices(x, false)?;
+            x
+        } else {
+            indices_ = self.default_indices(false)?;
+            &indices_
+        };
+        let slice_len_: Vec<usize>;
+        let full_length;
+        let slice_len = if let Some(x) = slice_len {
+            full_length = x.iter().fold(1_usize, |acc, x| acc.saturating_mul(*x));
+            if full_length == usize::MAX {
+                return Err(error::Error::Overflow);
+            }
+            self.check_sizelen(full_length, indices, x, false)?;
+            x
+        } else {
+            // The remainder of each dimension from the offset
+            full_length = self
+                .dimensions
+                .iter()
+                .zip(indices)
+                .map(|(d, &i)| d.len().saturating_sub(i))
+                .product();
+            slice_len_ = self.default_sizelen(full_length, indices, false)?;
+            &slice_len_
+        };
+
+        let mut strings = NcStrings::with_len(full_length);
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_get_vara_string(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                strings.as_mut_ptr(),
+            ))?;
+        }
+        Ok(strings.to_strings())
+    }
+
+    #[cfg(feature = "ndarray")]
+    /// Reads a slice of a string variable into an array,
+    /// see `string_values`
+    ///
+    /// # Errors
+    ///
+    /// Not a string variable, or invalid indices or slice lengths
+    pub fn string_array(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<String>> {
+        let values = self.string_values(indices, slice_len)?;
+        let shape = if let Some(x) = slice_len {
+            x.to_vec()
+        } else {
+            let indices_: Vec<usize>;
+            let indices = if let Some(x) = indices {
+                x
+            } else {
+                indices_ = self.default_indices(false)?;
+                &indices_
+            };
+            self.default_sizelen(values.len(), indices, false)?
+        };
+        Ok(ArrayD::from_shape_vec(shape, values).unwrap())
+    }
+
+    /// Reads a character (`NC_CHAR`) variable as strings. The last dimension
+    /// of the variable holds the characters of each string, and `indices`
+    /// and `slice_len` refers to all the other dimensions.
+    ///
+    /// NUL padding is removed, and the characters are decoded according to
+    /// the `_Encoding` attribute of the variable, defaulting to UTF-8
+    ///
+    /// # Errors
+    ///
+    /// Not a character variable, or invalid indices or slice lengths
+    pub fn text_values(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<Vec<String>> {
+        let (dims, strlen) = self.text_dimensions()?;
+        let indices_: Vec<usize>;
+        let indices = if let Some(x) = indices {
+            check_indices(dims, x, false)?;
+            x
+        } else {
+            indices_ = default_indices(dims, false)?;
+            &indices_
+        };
+        let slice_len_: Vec<usize>;
+        let full_length;
+        let slice_len = if let Some(x) = slice_len {
+            full_length = x.iter().fold(1_usize, |acc, x| acc.saturating_mul(*x));
+            if full_length == usize::MAX {
+                return Err(error::Error::Overflow);
+            }
+            check_sizelen(dims, full_length, indices, x, false)?;
+            x
+        } else {
+            // The remainder of each dimension from the offset
+            full_length = dims
+                .iter()
+                .zip(indices)
+                .map(|(d, &i)| d.len().saturating_sub(i))
+                .product();
+            slice_len_ = default_sizelen(dims, full_length, indices, false)?;
+            &slice_len_
+        };
+        if strlen == 0 {
+            return Ok(vec![String::new(); full_length]);
+        }
+
+        let mut start = indices.to_vec();
+        let mut count = slice_len.to_vec();
+        if !self.dimensions.is_empty() {
+            start.push(0);
+            count.push(strlen);
+        }
+        let mut buf = vec![0_u8; full_length * strlen];
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_get_vara_text(
+                self.ncid,
+                self.varid,
+                start.as_ptr(),
+                count.as_ptr(),
+                buf.as_mut_ptr() as *mut _,
+            ))?;
+        }
+
+        let encoding = self.text_encoding()?;
+        Ok(buf
+            .chunks(strlen)
+            .map(|chars| {
+                let len = chars.iter().position(|&x| x == 0).unwrap_or(chars.len());
+                encoding.decode(&chars[..len])
+            })
+            .collect())
+    }
+
+    /// Splits the dimensions of a character variable into the dimensions
+    /// indexing the strings, and the length of each string
+    fn text_dimensions(&self) -> error::Result<(&[Dimension<'g>], usize)> {
+        if self.vartype != NC_CHAR {
+            return Err(error::Error::TypeMismatch);
+        }
+        Ok(match self.dimensions.split_last() {
+            Some((chars, dims)) => (dims, chars.len()),
+            None => (&[], 1),
+        })
+    }
+
+    /// Encoding of character data, as given by the `_Encoding` attribute
+    fn text_encoding(&self) -> error::Result<TextEncoding> {
+        let attr = {
+            let _l = LOCK.lock();
+            Attribute::find_from_name(self.ncid, Some(self.varid), "_Encoding")?
+        };
+        match attr.map(|attr| attr.value()).transpose()? {
+            Some(AttrValue::Str(name)) => Ok(TextEncoding::from_name(&name)),
+            Some(AttrValue::Strs(names)) if names.len() == 1 => {
+                Ok(TextEncoding::from_name(&names[0]))
+            }
+            _ => Ok(TextEncoding::Utf8),
+        }
     }
 
     #[cfg(feature = "ndarray")]
@@ -776,12 +1478,14 @@ impl<'g> Variable<'g> {
         indices: Option<&[usize]>,
         slice_len: Option<&[usize]>,
     ) -> error::Result<ArrayD<T>> {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
-            self.check_indices(x, false)?;
+            check_indices(dims, x, false)?;
             x
         } else {
-            indices_ = self.default_indices(false)?;
+            indices_ = default_indices(dims, false)?;
             &indices_
         };
         let slice_len_: Vec<usize>;
@@ -791,31 +1495,39 @@ impl<'g> Variable<'g> {
             if full_length == usize::max_value() {
                 return Err(error::Error::Overflow);
             }
-            self.check_sizelen(full_length, indices, x, false)?;
+            check_sizelen(dims, full_length, indices, x, false)?;
             x
         } else {
-            full_length = self.dimensions.iter().map(Dimension::len).product();
-            slice_len_ = self.default_sizelen(full_length, indices, false)?;
+            full_length = dims.iter().map(Dimension::len).product();
+            slice_len_ = default_sizelen(dims, full_length, indices, false)?;
             &slice_len_
         };
 
         let mut values = Vec::with_capacity(full_length);
         unsafe {
-            T::variable_to_ptr(self, indices, slice_len, values.as_mut_ptr())?;
+            T::variable_to_ptr(self, indices, slice_len, values.as_mut_ptr()).map_err(|e| {
+                self.range_error::<T>(e, indices, slice_len, None)
+                    .with_context(|| self.hyperslab_context(indices, slice_len))
+            })?;
             values.set_len(full_length);
         }
         Ok(ArrayD::from_shape_vec(slice_len, values).unwrap())
     }
 
-    /// Get the fill value of a variable
+    /// Get the fill value of a variable, converted to `T`
+    /// following the [`ConversionMode`] of the variable
     pub fn fill_value<T: Numeric>(&self) -> error::Result<Option<T>> {
-        if T::NCTYPE != self.vartype {
-            return Err(error::Error::TypeMismatch);
+        self.check_type::<T>()?;
+        if !T::is_native(self) || T::trailing_dimensions(self) > 0 {
+            return match self.fill_value_f64()? {
+                Some(x) if !fits::<T>(x) => Err(error::Error::Range { count: 1, total: 1 }),
+                x => Ok(x.map(T::from_f64)),
+            };
         }
         let mut location = std::mem::MaybeUninit::uninit();
         let mut nofill: nc_type = 0;
         unsafe {
-            let _l = LOCK.lock().unwrap();
+            let _l = LOCK.lock();
             error::checked(nc_inq_var_fill(
                 self.ncid,
                 self.varid,
@@ -829,6 +1541,111 @@ impl<'g> Variable<'g> {
 
         Ok(Some(unsafe { location.assume_init() }))
     }
+    /// The fill value in the type of the variable as a double
+    fn fill_value_f64(&self) -> error::Result<Option<f64>> {
+        Ok(match self.vartype {
+            NC_BYTE => self.fill_value::<i8>()?.map(|x| x.to_f64()),
+            NC_UBYTE => self.fill_value::<u8>()?.map(|x| x.to_f64()),
+            NC_SHORT => self.fill_value::<i16>()?.map(|x| x.to_f64()),
+            NC_USHORT => self.fill_value::<u16>()?.map(|x| x.to_f64()),
+            NC_INT => self.fill_value::<i32>()?.map(|x| x.to_f64()),
+            NC_UINT => self.fill_value::<u32>()?.map(|x| x.to_f64()),
+            NC_INT64 => self.fill_value::<i64>()?.map(|x| x.to_f64()),
+            NC_UINT64 => self.fill_value::<u64>()?.map(|x| x.to_f64()),
+            NC_FLOAT => self.fill_value::<f32>()?.map(|x| x.to_f64()),
+            NC_DOUBLE => self.fill_value::<f64>()?.map(|x| x.to_f64()),
+            _ => return Err(error::Error::TypeMismatch),
+        })
+    }
+    /// Numeric attribute of the variable as doubles
+    fn attribute_f64(&self, name: &str) -> error::Result<Option<Vec<f64>>> {
+        let value = match self.attribute(name) {
+            Some(attr) => attr.value()?,
+            None => return Ok(None),
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let values = match value {
+            AttrValue::Ulonglong(x) => vec![x as f64],
+            AttrValue::Ulonglongs(x) => x.into_iter().map(|x| x as f64).collect(),
+            AttrValue::Longlong(x) => vec![x as f64],
+            AttrValue::Longlongs(x) => x.into_iter().map(|x| x as f64).collect(),
+            value => Vec::<f64>::try_from(value)?,
+        };
+        Ok(Some(values))
+    }
+    /// Which values are not data, from `_FillValue` (or the default
+    /// fill value of the type), `missing_value`, and `valid_range`
+    /// (or `valid_min` and `valid_max`) as in the CF conventions
+    ///
+    /// # Errors
+    ///
+    /// Not a numeric variable, or the attributes are not numeric
+    pub fn mask(&self) -> error::Result<Mask> {
+        self.mask_with_fill(self.fill_value_f64()?)
+    }
+    /// The mask with the given fill value
+    fn mask_with_fill(&self, fill_value: Option<f64>) -> error::Result<Mask> {
+        let (valid_min, valid_max) = match self.attribute_f64("valid_range")? {
+            Some(range) if range.len() == 2 => (Some(range[0]), Some(range[1])),
+            Some(_) => return Err("valid_range must have two values".into()),
+            None => (
+                self.attribute_f64("valid_min")?
+                    .and_then(|x| x.first().copied()),
+                self.attribute_f64("valid_max")?
+                    .and_then(|x| x.first().copied()),
+            ),
+        };
+        Ok(Mask {
+            fill_value,
+            missing_values: self.attribute_f64("missing_value")?.unwrap_or_default(),
+            valid_min,
+            valid_max,
+        })
+    }
+    /// The mask with the fill and missing values as they are read as `T`
+    #[cfg(feature = "ndarray")]
+    fn mask_as<T: Numeric>(&self) -> error::Result<Mask> {
+        let fill_value = match self.fill_value::<T>() {
+            Ok(fill_value) => fill_value.map(|x| x.to_f64()),
+            Err(_) => self.fill_value_f64()?,
+        };
+        let mut mask = self.mask_with_fill(fill_value)?;
+        for x in mask.missing_values.iter_mut().filter(|x| fits::<T>(**x)) {
+            *x = T::from_f64(*x).to_f64();
+        }
+        Ok(mask)
+    }
+    #[cfg(feature = "ndarray")]
+    /// Fetches variable, where values which are not data are `None`,
+    /// see [`Self::mask`]
+    pub fn masked_values<T: Numeric + Copy>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<ArrayD<Option<T>>> {
+        let mask = self.mask_as::<T>()?;
+        let values = self.values::<T>(indices, slice_len)?;
+        Ok(values.mapv(|x| {
+            if mask.is_masked(x.to_f64()) {
+                None
+            } else {
+                Some(x)
+            }
+        }))
+    }
+    #[cfg(feature = "ndarray")]
+    /// Fetches variable together with the mask of values which
+    /// are not data, see [`Self::mask`]
+    pub fn values_with_mask<T: Numeric>(
+        &self,
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<(ArrayD<T>, ArrayD<bool>)> {
+        let mask = self.mask_as::<T>()?;
+        let values = self.values::<T>(indices, slice_len)?;
+        let masked = values.map(|x| mask.is_masked(x.to_f64()));
+        Ok((values, masked))
+    }
     /// Fetches variable into slice
     /// buffer must be able to hold all the requested elements
     pub fn values_to<T: Numeric>(
@@ -837,24 +1654,29 @@ impl<'g> Variable<'g> {
         indices: Option<&[usize]>,
         slice_len: Option<&[usize]>,
     ) -> error::Result<()> {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
-            self.check_indices(x, false)?;
+            check_indices(dims, x, false)?;
             x
         } else {
-            indices_ = self.default_indices(false)?;
+            indices_ = default_indices(dims, false)?;
             &indices_
         };
         let slice_len_: Vec<usize>;
         let slice_len = if let Some(x) = slice_len {
-            self.check_sizelen(buffer.len(), indices, x, false)?;
+            check_sizelen(dims, buffer.len(), indices, x, false)?;
             x
         } else {
-            slice_len_ = self.default_sizelen(buffer.len(), indices, false)?;
+            slice_len_ = default_sizelen(dims, buffer.len(), indices, false)?;
             &slice_len_
         };
 
-        unsafe { T::variable_to_ptr(self, indices, slice_len, buffer.as_mut_ptr()) }
+        unsafe { T::variable_to_ptr(self, indices, slice_len, buffer.as_mut_ptr()) }.map_err(|e| {
+            self.range_error::<T>(e, indices, slice_len, None)
+                .with_context(|| self.hyperslab_context(indices, slice_len))
+        })
     }
 
     /// Fetches variable into slice
@@ -866,30 +1688,28 @@ impl<'g> Variable<'g> {
         slice_len: Option<&[usize]>,
         strides: &[isize],
     ) -> error::Result<usize> {
-        if strides.len() != self.dimensions.len() {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
+        if strides.len() != dims.len() {
             return Err("stride_mismatch".into());
         }
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
-            self.check_indices(x, false)?;
+            check_indices(dims, x, false)?;
             x
         } else {
-            indices_ = self.default_indices(false)?;
+            indices_ = default_indices(dims, false)?;
             &indices_
         };
 
         let slice_len_: Vec<usize>;
         let slice_len = if let Some(slice_len) = slice_len {
-            if slice_len.len() != self.dimensions.len() {
+            if slice_len.len() != dims.len() {
                 return Err("slice mismatch".into());
             }
             #[allow(clippy::cast_possible_wrap)]
-            for (((d, &start), &count), &stride) in self
-                .dimensions
-                .iter()
-                .zip(indices)
-                .zip(slice_len)
-                .zip(strides)
+            for (((d, &start), &count), &stride) in
+                dims.iter().zip(indices).zip(slice_len).zip(strides)
             {
                 if stride == 0 && count != 1 {
                     return Err(error::Error::Stride);
@@ -906,8 +1726,7 @@ impl<'g> Variable<'g> {
             }
             slice_len
         } else {
-            slice_len_ = self
-                .dimensions
+            slice_len_ = dims
                 .iter()
                 .zip(indices)
                 .zip(strides)
@@ -926,7 +1745,11 @@ impl<'g> Variable<'g> {
         if buffer.len() < slice_len.iter().product() {
             return Err("buffer too small".into());
         }
-        unsafe { T::get_values_strided(self, indices, &slice_len, strides, buffer.as_mut_ptr())? };
+        unsafe { T::get_values_strided(self, indices, &slice_len, strides, buffer.as_mut_ptr()) }
+            .map_err(|e| {
+            self.range_error::<T>(e, indices, slice_len, Some(strides))
+                .with_context(|| self.hyperslab_context(indices, slice_len))
+        })?;
         Ok(slice_len.iter().product())
     }
 }
@@ -938,15 +1761,25 @@ impl<'g> VariableMut<'g> {
         value: T,
         indices: Option<&[usize]>,
     ) -> error::Result<()> {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
-            self.check_indices(x, true)?;
+            check_indices(dims, x, true)?;
             x
         } else {
-            indices_ = self.default_indices(true)?;
+            indices_ = default_indices(dims, true)?;
             &indices_
         };
-        unsafe { T::put_value_at(self, indices, value) }
+        // A hyperslab of one value, checked as `put_values` does
+        let value = std::slice::from_ref(&value);
+        let slice_len = vec![1; indices.len()];
+        self.check_range(value)
+            .and_then(|()| unsafe { T::put_values_at(self, indices, &slice_len, value) })
+            .map_err(|e| {
+                self.put_range_error(e, value)
+                    .with_context(|| self.hyperslab_context(indices, &[]))
+            })
     }
 
     /// Internally converts to a `CString`, avoid using this function when performance
@@ -976,13 +1809,22 @@ impl<'g> VariableMut<'g> {
         Ok(())
     }
 
-    /// Put a slice of values at `indices`
-    pub fn put_values<T: Numeric>(
+    /// Writes a slice of strings in one go, which is much faster than
+    /// writing element by element through `put_string`
+    ///
+    /// # Errors
+    ///
+    /// Not a string variable, a string contains an interior NUL,
+    /// or invalid indices or slice lengths
+    pub fn put_string_values<S: AsRef<str>>(
         &mut self,
-        values: &[T],
+        values: &[S],
         indices: Option<&[usize]>,
         slice_len: Option<&[usize]>,
     ) -> error::Result<()> {
+        if self.vartype != NC_STRING {
+            return Err(error::Error::TypeMismatch);
+        }
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
             self.check_indices(x, true)?;
@@ -999,7 +1841,130 @@ impl<'g> VariableMut<'g> {
             slice_len_ = self.default_sizelen(values.len(), indices, true)?;
             &slice_len_
         };
-        unsafe { T::put_values_at(self, indices, slice_len, values) }
+
+        let values = CStrings::new(values)?;
+        let mut ptrs = values.as_ptrs();
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_put_vara_string(
+                self.ncid,
+                self.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                ptrs.as_mut_ptr(),
+            ))
+        }
+    }
+
+    /// Writes strings into a character (`NC_CHAR`) variable. The last
+    /// dimension of the variable holds the characters of each string, and
+    /// `indices` and `slice_len` refers to all the other dimensions.
+    ///
+    /// Strings are encoded according to the `_Encoding` attribute of the variable
+    /// (defaulting to UTF-8), and padded with NUL to the length of the last dimension
+    ///
+    /// # Errors
+    ///
+    /// Not a character variable, a string does not fit in the last dimension,
+    /// or invalid indices or slice lengths
+    pub fn put_text_values<S: AsRef<str>>(
+        &mut self,
+        values: &[S],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        let encoding = self.text_encoding()?;
+        let values = values
+            .iter()
+            .map(|s| encoding.encode(s.as_ref()))
+            .collect::<error::Result<Vec<_>>>()?;
+
+        let (dims, strlen) = self.text_dimensions()?;
+        let strlen = match self.dimensions.last() {
+            Some(d) if d.is_unlimited() => values.iter().map(Vec::len).fold(strlen, usize::max),
+            _ => strlen,
+        };
+        if let Some(s) = values.iter().find(|s| s.len() > strlen) {
+            return Err(error::Error::Str(format!(
+                "string of length {} does not fit in {} characters",
+                s.len(),
+                strlen
+            )));
+        }
+
+        let indices_: Vec<usize>;
+        let indices = if let Some(x) = indices {
+            check_indices(dims, x, true)?;
+            x
+        } else {
+            indices_ = default_indices(dims, true)?;
+            &indices_
+        };
+        let slice_len_: Vec<usize>;
+        let slice_len = if let Some(x) = slice_len {
+            check_sizelen(dims, values.len(), indices, x, true)?;
+            x
+        } else {
+            slice_len_ = default_sizelen(dims, values.len(), indices, true)?;
+            &slice_len_
+        };
+        if strlen == 0 {
+            return Ok(());
+        }
+
+        let mut start = indices.to_vec();
+        let mut count = slice_len.to_vec();
+        if !self.dimensions.is_empty() {
+            start.push(0);
+            count.push(strlen);
+        }
+        let mut buf = vec![0_u8; values.len() * strlen];
+        for (chars, s) in buf.chunks_mut(strlen).zip(&values) {
+            chars[..s.len()].copy_from_slice(s);
+        }
+        unsafe {
+            let _l = LOCK.lock();
+            error::checked(nc_put_vara_text(
+                self.ncid,
+                self.varid,
+                start.as_ptr(),
+                count.as_ptr(),
+                buf.as_ptr() as *const _,
+            ))
+        }
+    }
+
+    /// Put a slice of values at `indices`
+    pub fn put_values<T: Numeric>(
+        &mut self,
+        values: &[T],
+        indices: Option<&[usize]>,
+        slice_len: Option<&[usize]>,
+    ) -> error::Result<()> {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
+        let indices_: Vec<usize>;
+        let indices = if let Some(x) = indices {
+            check_indices(dims, x, true)?;
+            x
+        } else {
+            indices_ = default_indices(dims, true)?;
+            &indices_
+        };
+        let slice_len_: Vec<usize>;
+        let slice_len = if let Some(x) = slice_len {
+            check_sizelen(dims, values.len(), indices, x, true)?;
+            x
+        } else {
+            slice_len_ = default_sizelen(dims, values.len(), indices, true)?;
+            &slice_len_
+        };
+        self.check_range(values)
+            .and_then(|()| unsafe { T::put_values_at(self, indices, slice_len, values) })
+            .map_err(|e| {
+                self.put_range_error(e, values)
+                    .with_context(|| self.hyperslab_context(indices, slice_len))
+            })
     }
 
     /// Put a slice of values at `indices`, with destination strided
@@ -1010,29 +1975,27 @@ impl<'g> VariableMut<'g> {
         slice_len: Option<&[usize]>,
         strides: &[isize],
     ) -> error::Result<usize> {
+        self.check_type::<T>()?;
+        let dims = self.dimensions_of::<T>();
         let indices_: Vec<usize>;
         let indices = if let Some(x) = indices {
-            self.check_indices(x, true)?;
+            check_indices(dims, x, true)?;
             x
         } else {
-            indices_ = self.default_indices(true)?;
+            indices_ = default_indices(dims, true)?;
             &indices_
         };
-        if strides.len() != self.dimensions.len() {
+        if strides.len() != dims.len() {
             return Err(error::Error::IndexMismatch);
         }
 
         let slice_len_: Vec<usize>;
         let slice_len = if let Some(slice_len) = slice_len {
-            if slice_len.len() != self.dimensions.len() {
+            if slice_len.len() != dims.len() {
                 return Err(error::Error::SliceMismatch);
             }
-            for (((d, &start), &count), &stride) in self
-                .dimensions
-                .iter()
-                .zip(indices)
-                .zip(slice_len)
-                .zip(strides)
+            for (((d, &start), &count), &stride) in
+                dims.iter().zip(indices).zip(slice_len).zip(strides)
             {
                 if count == 0 {
                     return Err(error::Error::ZeroSlice);
@@ -1048,8 +2011,7 @@ impl<'g> VariableMut<'g> {
             }
             slice_len
         } else {
-            slice_len_ = self
-                .dimensions
+            slice_len_ = dims
                 .iter()
                 .zip(indices)
                 .zip(strides)
@@ -1073,7 +2035,15 @@ impl<'g> VariableMut<'g> {
         if values.len() < slice_len.iter().product() {
             return Err("not enough values".into());
         }
-        unsafe { T::put_values_strided(self, indices, slice_len, strides, values.as_ptr())? };
+        let values = &values[..slice_len.iter().product()];
+        self.check_range(values)
+            .and_then(|()| unsafe {
+                T::put_values_strided(self, indices, slice_len, strides, values.as_ptr())
+            })
+            .map_err(|e| {
+                self.put_range_error(e, values)
+                    .with_context(|| self.hyperslab_context(indices, slice_len))
+            })?;
         Ok(slice_len.iter().product())
     }
 
@@ -1189,6 +2159,7 @@ impl<'g> VariableMut<'g> {
                 varid,
                 vartype: xtype,
                 dimensions,
+                conversion: ConversionMode::Converting,
                 _group: PhantomData,
             },
             PhantomData,
@@ -1196,6 +2167,27 @@ impl<'g> VariableMut<'g> {
     }
 }
 
+/// Finds the variable `name` in the group `ncid` or the nearest
+/// parent holding it. A `name` containing `/` is a path to the
+/// variable, taken from the root group when starting with `/`
+pub(crate) fn find_visible<'g>(ncid: nc_type, name: &str) -> error::Result<Option<Variable<'g>>> {
+    if let Some(pos) = name.rfind('/') {
+        let (path, name) = (&name[..=pos], &name[pos + 1..]);
+        return match super::group::group_from_path(ncid, path)? {
+            Some(group) => Variable::find_from_name(group.ncid, name),
+            None => Ok(None),
+        };
+    }
+    let mut group = Some(ncid);
+    while let Some(ncid) = group {
+        if let Some(var) = Variable::find_from_name(ncid, name)? {
+            return Ok(Some(var));
+        }
+        group = super::group::parent_at(ncid)?.map(|g| g.ncid);
+    }
+    Ok(None)
+}
+
 pub(crate) fn variables_at_ncid<'g>(
     ncid: nc_type,
 ) -> error::Result<impl Iterator<Item = error::Result<Variable<'g>>>> {
@@ -1223,11 +2215,32 @@ pub(crate) fn variables_at_ncid<'g>(
             varid,
             dimensions,
             vartype: xtype,
+            conversion: ConversionMode::Converting,
             _group: PhantomData,
         })
     }))
 }
 
+/// Copies the definition, attributes and data of `var` into the
+/// group `ncid`, which may be in another file. Dimensions which
+/// are not found in `ncid` or its parents are added to `ncid`
+pub(crate) fn copy_variable_to<'g>(
+    var: &Variable,
+    ncid: nc_type,
+) -> error::Result<VariableMut<'g>> {
+    for dim in &var.dimensions {
+        super::dimension::copy_to(dim, ncid, true)?;
+    }
+    unsafe {
+        error::checked(nc_copy_var(var.ncid, var.varid, ncid))?;
+    }
+    let name = var.name();
+    match Variable::find_from_name(ncid, &name)? {
+        Some(var) => Ok(VariableMut(var, PhantomData)),
+        None => Err(error::Error::NotFound(format!("variable {}", name))),
+    }
+}
+
 pub(crate) fn add_variable_from_identifiers<'g>(
     ncid: nc_type,
     name: &str,
@@ -1245,6 +2258,13 @@ pub(crate) fn add_variable_from_identifiers<'g>(
             if id.ncid >> 16 != ncid >> 16 {
                 return Err(error::Error::WrongDataset);
             }
+            // Only dimensions of this group and its parents can be used
+            if !super::dimension::is_visible_at(ncid, id.dimid)? {
+                return Err(error::Error::NotFound(format!(
+                    "dimension {} in this group or its parents",
+                    id.dimid
+                )));
+            }
             let mut dimlen = 0;
             unsafe {
                 error::checked(nc_inq_dimlen(id.ncid, id.dimid, &mut dimlen))?;
@@ -1276,6 +2296,7 @@ pub(crate) fn add_variable_from_identifiers<'g>(
             dimensions,
             varid,
             vartype: xtype,
+            conversion: ConversionMode::Converting,
             _group: PhantomData,
         },
         PhantomData,
@@ -0,0 +1,591 @@
+//! Values which are not built-in netCDF types: `bool`, `f16`
+//! (with the `half` feature) and complex numbers (with the
+//! `num-complex` feature)
+//!
+//! These are stored as
+//!
+//! * `bool`: `NC_BYTE`, zero for `false` and one for `true`. Any
+//!   nonzero byte is read as `true`
+//! * `f16`: an opaque type named `float16` of two bytes, holding
+//!   IEEE 754 binary16 values in little-endian byte order. Variables
+//!   of other numeric types are converted through `f32`
+//! * `Complex<f32>` and `Complex<f64>`: as in the conventions of
+//!   [nc-complex](https://github.com/PlasmaFAIR/nc-complex), a compound
+//!   type of the fields `r` and `i` (named `_PFNC_FLOAT_COMPLEX_TYPE` or
+//!   `_PFNC_DOUBLE_COMPLEX_TYPE` when created), or a trailing dimension
+//!   of length two named `complex`, `ri` or `_pfnc_complex`
+
+use super::{Numeric, Variable, VariableMut};
+use crate::error;
+#[cfg(any(feature = "half", feature = "num-complex"))]
+use crate::LOCK;
+#[cfg(feature = "half")]
+use half::f16;
+use netcdf_sys::*;
+#[cfg(feature = "num-complex")]
+use num_complex::Complex;
+
+/// Implements `Numeric` through functions reading and writing
+/// hyperslabs, with optional strides
+macro_rules! impl_numeric_with {
+    (
+        $t: ty,
+        $nc_type: expr,
+        range: ($min: expr, $max: expr),
+        to_f64: $to_f64: expr,
+        from_f64: $from_f64: expr,
+        read: $read: expr,
+        write: $write: expr,
+        $($extra: tt)*
+    ) => {
+        unsafe impl Numeric for $t {
+            const NCTYPE: nc_type = $nc_type;
+            const MIN: f64 = $min;
+            const MAX: f64 = $max;
+
+            fn to_f64(&self) -> f64 {
+                ($to_f64)(*self)
+            }
+            fn from_f64(value: f64) -> Self {
+                ($from_f64)(value)
+            }
+
+            $($extra)*
+
+            unsafe fn single_value_from_variable(
+                variable: &Variable,
+                indices: &[usize],
+            ) -> error::Result<Self> {
+                let mut value = std::mem::MaybeUninit::uninit();
+                ($read)(variable, indices, &vec![1; indices.len()], None, value.as_mut_ptr())?;
+                Ok(value.assume_init())
+            }
+
+            unsafe fn variable_to_ptr(
+                variable: &Variable,
+                indices: &[usize],
+                slice_len: &[usize],
+                values: *mut Self,
+            ) -> error::Result<()> {
+                ($read)(variable, indices, slice_len, None, values)
+            }
+
+            unsafe fn put_value_at(
+                variable: &mut VariableMut,
+                indices: &[usize],
+                value: Self,
+            ) -> error::Result<()> {
+                ($write)(variable, indices, &vec![1; indices.len()], None, &[value])
+            }
+
+            unsafe fn put_values_at(
+                variable: &mut VariableMut,
+                indices: &[usize],
+                slice_len: &[usize],
+                values: &[Self],
+            ) -> error::Result<()> {
+                ($write)(variable, indices, slice_len, None, values)
+            }
+
+            unsafe fn get_values_strided(
+                variable: &Variable,
+                indices: &[usize],
+                slice_len: &[usize],
+                strides: &[isize],
+                values: *mut Self,
+            ) -> error::Result<()> {
+                ($read)(variable, indices, slice_len, Some(strides), values)
+            }
+
+            unsafe fn put_values_strided(
+                variable: &mut VariableMut,
+                indices: &[usize],
+                slice_len: &[usize],
+                strides: &[isize],
+                values: *const Self,
+            ) -> error::Result<()> {
+                let values = std::slice::from_raw_parts(values, slice_len.iter().product());
+                ($write)(variable, indices, slice_len, Some(strides), values)
+            }
+        }
+    };
+}
+
+/// Reads values stored as `S`, converting each to `T`
+///
+/// # Safety
+///
+/// `values` must have space for the whole hyperslab
+unsafe fn get_via<S: Numeric + Copy + Default, T>(
+    variable: &Variable,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: *mut T,
+    from: impl Fn(S) -> T,
+) -> error::Result<()> {
+    let mut buffer = vec![S::default(); slice_len.iter().product()];
+    match strides {
+        Some(strides) => {
+            S::get_values_strided(variable, indices, slice_len, strides, buffer.as_mut_ptr())?;
+        }
+        None => S::variable_to_ptr(variable, indices, slice_len, buffer.as_mut_ptr())?,
+    }
+    for (i, x) in buffer.into_iter().enumerate() {
+        values.add(i).write(from(x));
+    }
+    Ok(())
+}
+
+/// Writes values stored as `S`, converting each from `T`
+///
+/// # Safety
+///
+/// `values` must hold the whole hyperslab
+unsafe fn put_via<S: Numeric, T: Copy>(
+    variable: &mut VariableMut,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: &[T],
+    to: impl Fn(T) -> S,
+) -> error::Result<()> {
+    let buffer = values.iter().map(|&x| to(x)).collect::<Vec<S>>();
+    match strides {
+        Some(strides) => {
+            S::put_values_strided(variable, indices, slice_len, strides, buffer.as_ptr())
+        }
+        None => S::put_values_at(variable, indices, slice_len, &buffer),
+    }
+}
+
+/// `bool` is stored as zero or one in `NC_BYTE`
+unsafe fn read_bool(
+    variable: &Variable,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: *mut bool,
+) -> error::Result<()> {
+    get_via(variable, indices, slice_len, strides, values, |x: i8| {
+        x != 0
+    })
+}
+
+unsafe fn write_bool(
+    variable: &mut VariableMut,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: &[bool],
+) -> error::Result<()> {
+    put_via(variable, indices, slice_len, strides, values, i8::from)
+}
+
+// Any byte is read as a flag, nonzero being `true`
+impl_numeric_with!(
+    bool,
+    NC_BYTE,
+    range: (-128.0, 127.0),
+    to_f64: |x: bool| f64::from(u8::from(x)),
+    from_f64: |x: f64| x != 0.0,
+    read: read_bool,
+    write: write_bool,
+
+    // Bytes are converted, and not every byte is a valid `bool`
+    fn is_native(_variable: &Variable) -> bool {
+        false
+    }
+);
+
+/// Name of the opaque type holding `f16`
+#[cfg(feature = "half")]
+const F16_TYPE: &[u8] = b"float16\0";
+
+/// Whether `xtype` is the opaque type holding `f16`
+#[cfg(feature = "half")]
+fn is_f16_type(ncid: nc_type, xtype: nc_type) -> bool {
+    if xtype < NC_FIRSTUSERTYPEID {
+        return false;
+    }
+    let _l = LOCK.lock();
+    let mut name = [0_u8; NC_MAX_NAME as usize + 1];
+    let (mut size, mut class) = (0, 0);
+    let e = unsafe {
+        nc_inq_user_type(
+            ncid,
+            xtype,
+            name.as_mut_ptr() as *mut _,
+            &mut size,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut class,
+        )
+    };
+    e == NC_NOERR
+        && class == NC_OPAQUE
+        && size == std::mem::size_of::<f16>()
+        && name.starts_with(F16_TYPE)
+}
+
+/// Opaque type for `f16` in the group,
+/// defined if the group does not already have it
+#[cfg(feature = "half")]
+fn f16_type(ncid: nc_type) -> error::Result<nc_type> {
+    let mut typeid = 0;
+    if unsafe { nc_inq_typeid(ncid, F16_TYPE.as_ptr() as *const _, &mut typeid) } == NC_NOERR {
+        return Ok(typeid);
+    }
+    unsafe {
+        error::checked(nc_def_opaque(
+            ncid,
+            std::mem::size_of::<f16>(),
+            F16_TYPE.as_ptr() as *const _,
+            &mut typeid,
+        ))?;
+    }
+    Ok(typeid)
+}
+
+/// `f16` is stored in the opaque type `F16_TYPE`,
+/// and is converted through `f32` for other types
+#[cfg(feature = "half")]
+unsafe fn read_f16(
+    variable: &Variable,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: *mut f16,
+) -> error::Result<()> {
+    if !is_f16_type(variable.ncid, variable.vartype) {
+        return get_via(variable, indices, slice_len, strides, values, f16::from_f32);
+    }
+    let mut bytes = vec![[0_u8; 2]; slice_len.iter().product()];
+    {
+        let _l = LOCK.lock();
+        error::checked(match strides {
+            Some(strides) => nc_get_vars(
+                variable.ncid,
+                variable.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                strides.as_ptr(),
+                bytes.as_mut_ptr().cast(),
+            ),
+            None => nc_get_vara(
+                variable.ncid,
+                variable.varid,
+                indices.as_ptr(),
+                slice_len.as_ptr(),
+                bytes.as_mut_ptr().cast(),
+            ),
+        })?;
+    }
+    for (i, x) in bytes.into_iter().enumerate() {
+        values.add(i).write(f16::from_le_bytes(x));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "half")]
+unsafe fn write_f16(
+    variable: &mut VariableMut,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: &[f16],
+) -> error::Result<()> {
+    if !is_f16_type(variable.ncid, variable.vartype) {
+        return put_via(variable, indices, slice_len, strides, values, f16::to_f32);
+    }
+    let bytes = values.iter().map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+    let _l = LOCK.lock();
+    error::checked(match strides {
+        Some(strides) => nc_put_vars(
+            variable.ncid,
+            variable.varid,
+            indices.as_ptr(),
+            slice_len.as_ptr(),
+            strides.as_ptr(),
+            bytes.as_ptr().cast(),
+        ),
+        None => nc_put_vara(
+            variable.ncid,
+            variable.varid,
+            indices.as_ptr(),
+            slice_len.as_ptr(),
+            bytes.as_ptr().cast(),
+        ),
+    })
+}
+
+#[cfg(feature = "half")]
+impl_numeric_with!(
+    f16,
+    NC_OPAQUE,
+    range: (-65504.0, 65504.0),
+    to_f64: f16::to_f64,
+    from_f64: f16::from_f64,
+    read: read_f16,
+    write: write_f16,
+
+    fn is_native(variable: &Variable) -> bool {
+        is_f16_type(variable.ncid, variable.vartype)
+    }
+    fn variable_type(ncid: nc_type) -> error::Result<nc_type> {
+        f16_type(ncid)
+    }
+);
+
+/// How a variable holds complex numbers, following the
+/// conventions of [nc-complex](https://github.com/PlasmaFAIR/nc-complex)
+#[cfg(feature = "num-complex")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ComplexStorage {
+    /// A compound type of the real and imaginary parts
+    Compound,
+    /// A trailing dimension of length two
+    Dimension,
+}
+
+/// Names of the trailing dimension holding the parts of complex numbers
+#[cfg(feature = "num-complex")]
+const COMPLEX_DIMENSIONS: &[&[u8]] = &[b"complex", b"ri", b"_pfnc_complex"];
+
+/// How the variable holds complex numbers, and their base type
+#[cfg(feature = "num-complex")]
+fn complex_storage(variable: &Variable) -> Option<(ComplexStorage, nc_type)> {
+    match variable.vartype {
+        NC_FLOAT | NC_DOUBLE => variable
+            .dimensions
+            .last()
+            .filter(|d| {
+                d.len() == 2
+                    && d.name_bytes()
+                        .is_ok_and(|name| COMPLEX_DIMENSIONS.contains(&name.as_slice()))
+            })
+            .map(|_| (ComplexStorage::Dimension, variable.vartype)),
+        t if t >= NC_FIRSTUSERTYPEID => compound_base(variable.ncid, t)
+            .ok()
+            .flatten()
+            .map(|base| (ComplexStorage::Compound, base)),
+        _ => None,
+    }
+}
+
+/// Base type of a compound type laid out as a complex number
+#[cfg(feature = "num-complex")]
+fn compound_base(ncid: nc_type, xtype: nc_type) -> error::Result<Option<nc_type>> {
+    let _l = LOCK.lock();
+    let (mut size, mut base, mut nfields, mut class) = (0, 0, 0, 0);
+    unsafe {
+        error::checked(nc_inq_user_type(
+            ncid,
+            xtype,
+            std::ptr::null_mut(),
+            &mut size,
+            &mut base,
+            &mut nfields,
+            &mut class,
+        ))?;
+    }
+    if class != NC_COMPOUND || nfields != 2 {
+        return Ok(None);
+    }
+    let mut fields = [(0, 0, 0); 2];
+    for (fieldid, (offset, typ, ndims)) in fields.iter_mut().enumerate() {
+        unsafe {
+            error::checked(nc_inq_compound_field(
+                ncid,
+                xtype,
+                fieldid as _,
+                std::ptr::null_mut(),
+                offset,
+                typ,
+                ndims,
+                std::ptr::null_mut(),
+            ))?;
+        }
+    }
+    let base = fields[0].1;
+    let base_size = match base {
+        NC_FLOAT => std::mem::size_of::<f32>(),
+        NC_DOUBLE => std::mem::size_of::<f64>(),
+        _ => return Ok(None),
+    };
+    let complex = size == 2 * base_size && fields == [(0, base, 0), (base_size, base, 0)];
+    Ok(if complex { Some(base) } else { None })
+}
+
+/// Compound type for complex numbers in the group,
+/// defined if the group does not already have it
+#[cfg(feature = "num-complex")]
+fn complex_type(ncid: nc_type, base: nc_type, name: &[u8]) -> error::Result<nc_type> {
+    let mut typeid = 0;
+    if unsafe { nc_inq_typeid(ncid, name.as_ptr() as *const _, &mut typeid) } == NC_NOERR {
+        return Ok(typeid);
+    }
+    let base_size = if base == NC_FLOAT {
+        std::mem::size_of::<f32>()
+    } else {
+        std::mem::size_of::<f64>()
+    };
+    unsafe {
+        error::checked(nc_def_compound(
+            ncid,
+            2 * base_size,
+            name.as_ptr() as *const _,
+            &mut typeid,
+        ))?;
+        error::checked(nc_insert_compound(
+            ncid,
+            typeid,
+            b"r\0".as_ptr() as *const _,
+            0,
+            base,
+        ))?;
+        error::checked(nc_insert_compound(
+            ncid,
+            typeid,
+            b"i\0".as_ptr() as *const _,
+            base_size,
+            base,
+        ))?;
+    }
+    Ok(typeid)
+}
+
+/// Hyperslab including the trailing dimension of the parts
+#[cfg(feature = "num-complex")]
+fn with_parts(
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+) -> (Vec<usize>, Vec<usize>, Option<Vec<isize>>) {
+    let extend = |x: &[usize], part| x.iter().copied().chain(Some(part)).collect();
+    (
+        extend(indices, 0),
+        extend(slice_len, 2),
+        strides.map(|x| x.iter().copied().chain(Some(1)).collect()),
+    )
+}
+
+#[cfg(feature = "num-complex")]
+unsafe fn read_complex<B: Numeric>(
+    variable: &Variable,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: *mut Complex<B>,
+) -> error::Result<()> {
+    match complex_storage(variable) {
+        Some((ComplexStorage::Compound, base)) if base == B::NCTYPE => {
+            let _l = LOCK.lock();
+            error::checked(match strides {
+                Some(strides) => nc_get_vars(
+                    variable.ncid,
+                    variable.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    strides.as_ptr(),
+                    values.cast(),
+                ),
+                None => nc_get_vara(
+                    variable.ncid,
+                    variable.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    values.cast(),
+                ),
+            })
+        }
+        Some((ComplexStorage::Dimension, _)) => {
+            let (indices, slice_len, strides) = with_parts(indices, slice_len, strides);
+            match strides {
+                Some(strides) => {
+                    B::get_values_strided(variable, &indices, &slice_len, &strides, values.cast())
+                }
+                None => B::variable_to_ptr(variable, &indices, &slice_len, values.cast()),
+            }
+        }
+        _ => Err(error::Error::TypeMismatch),
+    }
+}
+
+#[cfg(feature = "num-complex")]
+unsafe fn write_complex<B: Numeric>(
+    variable: &mut VariableMut,
+    indices: &[usize],
+    slice_len: &[usize],
+    strides: Option<&[isize]>,
+    values: &[Complex<B>],
+) -> error::Result<()> {
+    match complex_storage(variable) {
+        Some((ComplexStorage::Compound, base)) if base == B::NCTYPE => {
+            let _l = LOCK.lock();
+            error::checked(match strides {
+                Some(strides) => nc_put_vars(
+                    variable.ncid,
+                    variable.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    strides.as_ptr(),
+                    values.as_ptr().cast(),
+                ),
+                None => nc_put_vara(
+                    variable.ncid,
+                    variable.varid,
+                    indices.as_ptr(),
+                    slice_len.as_ptr(),
+                    values.as_ptr().cast(),
+                ),
+            })
+        }
+        Some((ComplexStorage::Dimension, _)) => {
+            let (indices, slice_len, strides) = with_parts(indices, slice_len, strides);
+            let parts = std::slice::from_raw_parts(values.as_ptr().cast::<B>(), 2 * values.len());
+            match strides {
+                Some(strides) => {
+                    B::put_values_strided(variable, &indices, &slice_len, &strides, parts.as_ptr())
+                }
+                None => B::put_values_at(variable, &indices, &slice_len, parts),
+            }
+        }
+        _ => Err(error::Error::TypeMismatch),
+    }
+}
+
+/// Complex numbers are stored as a compound type in
+/// `netCDF-4` files, or with a trailing dimension
+#[cfg(feature = "num-complex")]
+macro_rules! impl_complex {
+    ($base: ty, $nc_type: ident, $type_name: expr) => {
+        impl_numeric_with!(
+            Complex<$base>,
+            $nc_type,
+            range: (<$base>::MIN as f64, <$base>::MAX as f64),
+            to_f64: |x: Complex<$base>| x.re.to_f64(),
+            from_f64: |x: f64| Complex::new(<$base>::from_f64(x), 0.0),
+            read: read_complex::<$base>,
+            write: write_complex::<$base>,
+
+            fn is_native(variable: &Variable) -> bool {
+                complex_storage(variable).is_some_and(|(_, base)| base == $nc_type)
+            }
+            fn trailing_dimensions(variable: &Variable) -> usize {
+                match complex_storage(variable) {
+                    Some((ComplexStorage::Dimension, _)) => 1,
+                    _ => 0,
+                }
+            }
+            fn variable_type(ncid: nc_type) -> error::Result<nc_type> {
+                complex_type(ncid, $nc_type, $type_name)
+            }
+        );
+    };
+}
+
+#[cfg(feature = "num-complex")]
+impl_complex!(f32, NC_FLOAT, b"_PFNC_FLOAT_COMPLEX_TYPE\0");
+#[cfg(feature = "num-complex")]
+impl_complex!(f64, NC_DOUBLE, b"_PFNC_DOUBLE_COMPLEX_TYPE\0");
@@ -234,3 +234,65 @@ fn multi_attributes() {
     }
     assert_eq!(atts, 10);
 }
+
+#[test]
+fn string_attributes() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("string_attributes.nc");
+    {
+        let mut file = netcdf::create(path.clone()).unwrap();
+        file.add_attribute("char", "text").unwrap();
+        file.add_attribute("strings", vec!["a", "bb", "ccc"])
+            .unwrap();
+        file.add_string_attribute("string", &["single"]).unwrap();
+        file.add_dimension("x", 1).unwrap();
+        let mut var = file.add_variable::<i32>("var", &["x"]).unwrap();
+        var.add_string_attribute("flag_meanings", &["low", "high"])
+            .unwrap();
+    }
+    let file = netcdf::open(path).unwrap();
+    assert_eq!(
+        file.attribute("char").unwrap().value().unwrap(),
+        "text".into()
+    );
+    assert_eq!(
+        file.attribute("strings").unwrap().value().unwrap(),
+        vec!["a", "bb", "ccc"].into()
+    );
+    // A single string keeps its type
+    assert_eq!(
+        file.attribute("string").unwrap().value().unwrap(),
+        vec!["single"].into()
+    );
+    let var = file.variable("var").unwrap();
+    assert_eq!(
+        var.attribute("flag_meanings").unwrap().value().unwrap(),
+        vec!["low", "high"].into()
+    );
+}
+
+#[test]
+fn typed_attributes() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("typed_attributes.nc");
+    {
+        let mut file = netcdf::create(path.clone()).unwrap();
+        file.add_attribute("scale_factor", 0.5_f32).unwrap();
+        file.add_attribute("valid_range", vec![-10_i16, 10])
+            .unwrap();
+        file.add_attribute("units", "m").unwrap();
+    }
+    let file = netcdf::open(path).unwrap();
+    let scale = file.attribute("scale_factor").unwrap();
+    assert_eq!(scale.value_as::<f64>().unwrap(), 0.5);
+    assert_eq!(scale.value_as::<Vec<f32>>().unwrap(), vec![0.5]);
+    match scale.value_as::<i32>() {
+        Err(netcdf::error::Error::TypeMismatch) => {}
+        x => panic!("Unexpected {:?}", x),
+    }
+    let range = file.attribute("valid_range").unwrap();
+    assert_eq!(range.value_as::<Vec<i32>>().unwrap(), vec![-10, 10]);
+    assert_eq!(range.value().unwrap().to_string(), "-10, 10");
+    let units = file.attribute("units").unwrap();
+    assert_eq!(units.value_as::<String>().unwrap(), "m");
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "derive")]
+use netcdf::NetcdfDataset;
+
+#[derive(Debug, PartialEq, NetcdfDataset)]
+struct Forecast {
+    #[netcdf(dimension, unlimited)]
+    time: usize,
+    #[netcdf(dimension)]
+    x: usize,
+    #[netcdf(attribute)]
+    title: String,
+    #[netcdf(attribute, name = "version")]
+    version: Option<i32>,
+    #[netcdf(dimensions("time"), units = "hours since 2020-01-01")]
+    hours: Vec<f64>,
+    #[netcdf(name = "t2m", group = "surface", dimensions("time", "x"))]
+    #[netcdf(units = "K", compression = 4)]
+    temperature: Vec<f32>,
+    #[netcdf(attribute)]
+    comment: Option<String>,
+    #[netcdf(skip)]
+    cache: Vec<u8>,
+}
+
+#[test]
+fn roundtrip() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("derive_roundtrip.nc");
+
+    let forecast = Forecast {
+        time: 2,
+        x: 3,
+        title: "forecast".into(),
+        version: Some(2),
+        hours: vec![0.0, 6.0],
+        temperature: vec![270.0, 271.0, 272.0, 273.0, 274.0, 275.0],
+        comment: None,
+        cache: vec![1, 2, 3],
+    };
+
+    let mut file = netcdf::create(&path).unwrap();
+    forecast.write_to(&mut file).unwrap();
+    drop(file);
+
+    let file = netcdf::open(&path).unwrap();
+    let group = file.group("surface").unwrap().unwrap();
+    let var = group.variable("t2m").unwrap();
+    assert_eq!(
+        var.attribute("units").unwrap().value().unwrap(),
+        netcdf::AttrValue::Str("K".into())
+    );
+    assert!(file.attribute("comment").is_none());
+
+    let read = Forecast::from_file(&file).unwrap();
+    assert_eq!(
+        read,
+        Forecast {
+            cache: Vec::new(),
+            ..forecast
+        }
+    );
+}
+
+#[derive(Debug, NetcdfDataset)]
+#[netcdf(group = "surface")]
+struct Surface {
+    #[netcdf(name = "t2m")]
+    temperature: Vec<f32>,
+    #[netcdf(name = "q2m")]
+    humidity: Option<Vec<f32>>,
+}
+
+#[test]
+fn missing_items() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("derive_missing.nc");
+
+    let mut file = netcdf::create(&path).unwrap();
+    assert!(Surface::from_file(&file).is_err());
+
+    let mut group = file.add_group("surface").unwrap();
+    group.add_dimension("x", 2).unwrap();
+    let mut var = group.add_variable::<f32>("t2m", &["x"]).unwrap();
+    var.put_values(&[1.0, 2.0], None, None).unwrap();
+
+    let surface = Surface::from_file(&file).unwrap();
+    assert_eq!(surface.temperature, vec![1.0, 2.0]);
+    assert!(surface.humidity.is_none());
+}
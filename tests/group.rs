@@ -71,3 +71,105 @@ fn find_variable() {
         }
     }
 }
+
+#[test]
+fn group_paths() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("group_paths.nc");
+    let mut file = netcdf::create(path).unwrap();
+
+    let mut member = file.add_group_path("/forecast/member_03").unwrap();
+    assert_eq!(member.name(), "member_03");
+    assert_eq!(member.full_name(), "/forecast/member_03");
+    member.add_group_path("surface/2m").unwrap();
+    assert_eq!(member.parent().unwrap().full_name(), "/forecast");
+    assert!(member
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .parent()
+        .is_none());
+    assert_eq!(
+        member.group_by_path("/forecast").unwrap().full_name(),
+        "/forecast"
+    );
+
+    file.add_group_path("forecast/member_04").unwrap();
+    // Adding an existing path is not an error
+    file.add_group_path("/forecast/member_03/surface").unwrap();
+
+    let group = file.group_by_path("/forecast/member_03/surface").unwrap();
+    assert_eq!(group.unwrap().full_name(), "/forecast/member_03/surface");
+    assert!(file.group_by_path("forecast/member_05").unwrap().is_none());
+    assert!(file
+        .group_mut_by_path("forecast/member_04")
+        .unwrap()
+        .is_some());
+
+    let names = file
+        .walk_groups()
+        .map(|g| g.full_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "/",
+            "/forecast",
+            "/forecast/member_03",
+            "/forecast/member_03/surface",
+            "/forecast/member_03/surface/2m",
+            "/forecast/member_04",
+        ]
+    );
+    let forecast = file.group("forecast").unwrap().unwrap();
+    assert_eq!(forecast.walk().count(), 5);
+    let groups = forecast.try_walk().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(groups.len(), 5);
+    assert!(file.try_walk_groups().all(|g| g.is_ok()));
+    assert!(file.root().unwrap().try_parent().unwrap().is_none());
+}
+
+#[test]
+fn visible_dimensions() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("visible_dimensions.nc");
+    let mut file = netcdf::create(path).unwrap();
+    file.add_unlimited_dimension("time").unwrap();
+    file.add_dimension("x", 4).unwrap();
+    let mut forecast = file.add_group("forecast").unwrap();
+    forecast.add_dimension("x", 2).unwrap();
+    let mut member = forecast.add_group("member").unwrap();
+    member.add_dimension("level", 3).unwrap();
+
+    let dims = member
+        .visible_dimensions()
+        .map(|d| (d.full_name(), d.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dims,
+        vec![
+            ("/forecast/member/level".to_string(), 3),
+            ("/forecast/x".to_string(), 2),
+            ("/time".to_string(), 0),
+        ]
+    );
+    assert_eq!(member.dimensions().count(), 1);
+
+    let x = member.dimension("x").unwrap();
+    assert_eq!(x.defining_group().full_name(), "/forecast");
+    assert_eq!(x.try_defining_group().unwrap().name(), "forecast");
+    assert_eq!(x.len(), 2);
+
+    // Variables use the nearest dimension of the name
+    let var = member.add_variable::<f32>("v", &["time", "x"]).unwrap();
+    assert_eq!(var.dimensions()[1].len(), 2);
+    assert_eq!(var.dimensions()[0].defining_group().full_name(), "/");
+
+    // Dimensions of other branches can not be used
+    let level = member.dimension("level").unwrap().identifier();
+    let mut other = file.add_group("other").unwrap();
+    assert!(other
+        .add_variable_from_identifiers::<f32>("w", &[level])
+        .is_err());
+}
@@ -27,13 +27,16 @@ fn use_string_to_open() {
 fn bad_filename() {
     let f = test_location().join("blah_stuff.nc");
     let res_file = netcdf::open(&f);
-    assert!(
-        if let netcdf::error::Error::Netcdf(2) = res_file.unwrap_err() {
-            true
-        } else {
-            false
-        }
-    );
+    let err = res_file.unwrap_err();
+    assert_eq!(err.code(), Some(2));
+    assert_eq!(err.kind(), Some(netcdf::error::ErrorKind::Os(2)));
+    assert_eq!(err.context().unwrap().path.as_deref(), Some(f.as_path()));
+    assert!(matches!(
+        std::error::Error::source(&err)
+            .unwrap()
+            .downcast_ref::<netcdf::error::Error>(),
+        Some(netcdf::error::Error::Netcdf(2))
+    ));
 }
 
 // Read tests
@@ -948,9 +951,10 @@ fn add_conflicting_dimensions() {
     file.add_dimension("x", 10).unwrap();
     let e = file.add_dimension("x", 11).unwrap_err();
     assert!(match e {
-        netcdf::error::Error::AlreadyExists => true,
+        netcdf::error::Error::AlreadyExists(_) => true,
         _ => false,
     });
+    assert_eq!(e.kind(), Some(netcdf::error::ErrorKind::NameInUse));
     assert_eq!(file.dimension("x").unwrap().len(), 10);
 }
 
@@ -966,7 +970,7 @@ fn add_conflicting_variables() {
 
     let e = file.add_variable::<f32>("x", &["y"]).unwrap_err();
     assert!(match e {
-        netcdf::error::Error::AlreadyExists => {
+        netcdf::error::Error::AlreadyExists(_) => {
             true
         }
         e => {
@@ -1175,6 +1179,117 @@ fn string_variables() {
     var.string_value(None).unwrap_err();
 }
 
+#[test]
+fn string_variables_bulk() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("string_variables_bulk.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+
+        file.add_unlimited_dimension("x").unwrap();
+        file.add_dimension("y", 3).unwrap();
+
+        let var = &mut file.add_string_variable("str", &["x", "y"]).unwrap();
+        var.put_string_values(&["a", "bb", "ccc", "dddd", "eeeee", "ffffff"], None, None)
+            .unwrap();
+        var.put_string_values(&["ßæøå".to_string()], Some(&[3, 1]), Some(&[1, 1]))
+            .unwrap();
+        var.put_string_values(&["a\0b"], Some(&[4, 0]), Some(&[1, 1]))
+            .unwrap_err();
+
+        let var = &mut file.add_variable::<i32>("i", &["y"]).unwrap();
+        var.put_string_values(&["1", "2", "3"], None, None)
+            .unwrap_err();
+    }
+    let file = netcdf::open(path).unwrap();
+
+    let var = &file.variable("str").unwrap();
+    let values = var.string_values(None, None).unwrap();
+    assert_eq!(
+        values,
+        &["a", "bb", "ccc", "dddd", "eeeee", "ffffff", "", "", "", "", "ßæøå", ""]
+    );
+    assert_eq!(
+        var.string_values(Some(&[1, 1]), Some(&[1, 2])).unwrap(),
+        &["eeeee", "ffffff"]
+    );
+    assert_eq!(var.string_value(Some(&[1, 0])).unwrap(), "dddd");
+    // Only the offset, the rest of each dimension is read
+    assert_eq!(
+        var.string_values(Some(&[3, 1]), None).unwrap(),
+        &["ßæøå", ""]
+    );
+    #[cfg(feature = "ndarray")]
+    {
+        let values = var.string_array(Some(&[0, 1]), None).unwrap();
+        assert_eq!(values.shape(), &[4, 2]);
+        assert_eq!(values[[1, 1]], "ffffff");
+    }
+
+    file.variable("i")
+        .unwrap()
+        .string_values(None, None)
+        .unwrap_err();
+}
+
+#[test]
+fn char_variables() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("char_variables.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+
+        file.add_unlimited_dimension("station").unwrap();
+        file.add_dimension("name_strlen", 8).unwrap();
+
+        let var = &mut file
+            .add_char_variable("name", &["station", "name_strlen"])
+            .unwrap();
+        var.put_text_values(&["Oslo", "Blindern"], None, None)
+            .unwrap();
+        var.put_text_values(&["Tromsø"], Some(&[3]), None).unwrap();
+        var.put_text_values(&["Longyearbyen"], Some(&[4]), None)
+            .unwrap_err();
+
+        let var = &mut file
+            .add_char_variable("latin", &["station", "name_strlen"])
+            .unwrap();
+        var.add_attribute("_Encoding", "ISO-8859-1").unwrap();
+        var.put_text_values(&["Tromsø", "Ålesund"], None, None)
+            .unwrap();
+        var.put_text_values(&["😀"], Some(&[2]), None).unwrap_err();
+    }
+    let file = netcdf::open(path).unwrap();
+
+    let var = &file.variable("name").unwrap();
+    assert_eq!(var.vartype(), netcdf_sys::NC_CHAR);
+    assert_eq!(
+        var.text_values(None, None).unwrap(),
+        &["Oslo", "Blindern", "", "Tromsø"]
+    );
+    assert_eq!(
+        var.text_values(Some(&[1]), Some(&[1])).unwrap(),
+        &["Blindern"]
+    );
+    assert_eq!(
+        var.text_values(Some(&[1]), None).unwrap(),
+        &["Blindern", "", "Tromsø"]
+    );
+    let mut chars = vec![0_u8; 8];
+    var.values_to(&mut chars, Some(&[3, 0]), None).unwrap_err();
+
+    let var = &file.variable("latin").unwrap();
+    assert_eq!(
+        var.text_values(None, None).unwrap(),
+        &["Tromsø", "Ålesund", "", ""]
+    );
+
+    file.variable("name")
+        .unwrap()
+        .string_value(Some(&[0, 0]))
+        .unwrap_err();
+}
+
 #[test]
 fn unlimited_in_parents() {
     let d = tempfile::tempdir().expect("Could not create tempdir");
@@ -1520,3 +1635,1098 @@ fn count_dimensions() {
 
     assert_eq!(file.dimensions().count(), 6);
 }
+
+#[test]
+fn copy_between_files() {
+    let d = tempfile::tempdir().unwrap();
+    let src_path = d.path().join("copy_src.nc");
+    let dst_path = d.path().join("copy_dst.nc");
+    {
+        let mut file = netcdf::create(&src_path).unwrap();
+        file.add_attribute("title", "source").unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_dimension("x", 3).unwrap();
+        let mut var = file.add_variable::<i32>("v", &["time", "x"]).unwrap();
+        var.add_attribute("units", "K").unwrap();
+        var.put_values(&[1, 2, 3, 4, 5, 6], None, Some(&[2, 3]))
+            .unwrap();
+
+        let mut g = file.add_group("g").unwrap();
+        g.add_dimension("y", 2).unwrap();
+        g.add_attribute("level", 1).unwrap();
+        let mut w = g.add_variable::<f32>("w", &["x", "y"]).unwrap();
+        w.put_values(&[0.5; 6], None, None).unwrap();
+        g.add_group("h").unwrap();
+    }
+
+    let src = netcdf::open(&src_path).unwrap();
+    {
+        let mut dst = netcdf::create(&dst_path).unwrap();
+        dst.copy_attribute(&src.attribute("title").unwrap())
+            .unwrap();
+        dst.copy_variable(&src.variable("v").unwrap()).unwrap();
+        dst.copy_group(&src.group("g").unwrap().unwrap()).unwrap();
+    }
+
+    let dst = netcdf::open(&dst_path).unwrap();
+    assert_eq!(
+        dst.attribute("title").unwrap().value().unwrap(),
+        "source".into()
+    );
+    let time = dst.dimension("time").unwrap();
+    assert_eq!(time.len(), 2);
+    let v = dst.variable("v").unwrap();
+    assert_eq!(v.attribute("units").unwrap().value().unwrap(), "K".into());
+    let mut values = [0; 6];
+    v.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+    let g = dst.group("g").unwrap().unwrap();
+    assert_eq!(g.dimension("y").unwrap().len(), 2);
+    assert_eq!(g.attribute("level").unwrap().value().unwrap(), 1.into());
+    let mut values = [0.0; 6];
+    g.variable("w")
+        .unwrap()
+        .values_to(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, [0.5; 6]);
+    assert!(g.group("h").is_some());
+
+    // The same copies, from the source side
+    let mut dst = netcdf::create(d.path().join("copy_dst2.nc")).unwrap();
+    let title = src.attribute("title").unwrap();
+    title.copy_attribute_to(&mut dst).unwrap();
+    src.variable("v")
+        .unwrap()
+        .copy_variable_to(&mut dst)
+        .unwrap();
+    let mut g = dst.add_group("g").unwrap();
+    let src_g = src.group("g").unwrap().unwrap();
+    src_g
+        .variable("w")
+        .unwrap()
+        .copy_variable_to(&mut g)
+        .unwrap();
+    assert_eq!(g.dimension("y").unwrap().len(), 2);
+    assert_eq!(dst.variable("v").unwrap().len(), 6);
+    assert!(dst.attribute("title").is_some());
+
+    // Dimensions must agree with existing ones
+    let mut dst = netcdf::append(&dst_path).unwrap();
+    dst.add_group("other")
+        .unwrap()
+        .add_dimension("x", 4)
+        .unwrap();
+    let mut other = dst.group_mut("other").unwrap().unwrap();
+    assert!(other.copy_variable(&src.variable("v").unwrap()).is_err());
+}
+
+#[test]
+fn convert_format() {
+    use netcdf::convert::{Format, Options};
+    let d = tempfile::tempdir().unwrap();
+    let src_path = d.path().join("convert_src.nc");
+    {
+        let mut file = netcdf::create(&src_path).unwrap();
+        file.add_attribute("title", "converted").unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_dimension("x", 7).unwrap();
+        let mut var = file.add_variable::<f64>("v", &["time", "x"]).unwrap();
+        var.add_attribute("units", "m").unwrap();
+        let values = (0..21).map(f64::from).collect::<Vec<_>>();
+        var.put_values(&values, None, Some(&[3, 7])).unwrap();
+        let mut name = file.add_char_variable("name", &["x"]).unwrap();
+        name.put_text_values(&["station"], None, None).unwrap();
+    }
+    let src = netcdf::open(&src_path).unwrap();
+
+    let dst_path = d.path().join("convert_classic.nc");
+    let options = Options {
+        format: Format::Offset64,
+        // Force the data through several blocks
+        buffer_len: 4,
+        ..Options::default()
+    };
+    netcdf::convert::convert(&src, &dst_path, &options).unwrap();
+    let dst = netcdf::open(&dst_path).unwrap();
+    assert!(dst.root().is_none());
+    assert_eq!(
+        dst.attribute("title").unwrap().value().unwrap(),
+        "converted".into()
+    );
+    let var = dst.variable("v").unwrap();
+    assert_eq!(var.attribute("units").unwrap().value().unwrap(), "m".into());
+    let mut values = vec![0.0; 21];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, (0..21).map(f64::from).collect::<Vec<_>>());
+    assert_eq!(
+        dst.variable("name")
+            .unwrap()
+            .text_values(None, None)
+            .unwrap(),
+        vec!["station"]
+    );
+
+    let dst_path = d.path().join("convert_chunked.nc");
+    let mut options = Options {
+        deflate: Some(5),
+        ..Options::default()
+    };
+    options.chunking.insert("x".to_string(), 2);
+    netcdf::convert::convert(&src, &dst_path, &options).unwrap();
+    let dst = netcdf::open(&dst_path).unwrap();
+    let mut values = vec![0.0; 21];
+    dst.variable("v")
+        .unwrap()
+        .values_to(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, (0..21).map(f64::from).collect::<Vec<_>>());
+}
+
+#[test]
+fn cdl_header() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("cdl_header.nc");
+    {
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_attribute("title", "a \"quoted\" title").unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_dimension("x", 3).unwrap();
+        let mut x = file.add_variable::<f32>("x", &["x"]).unwrap();
+        x.put_values(&[0.5, 1.0, 1.5], None, None).unwrap();
+        let mut v = file.add_variable::<i16>("v", &["time", "x"]).unwrap();
+        v.add_attribute("scale_factor", 0.5_f64).unwrap();
+        v.add_attribute("valid_range", vec![0_i16, 100]).unwrap();
+        v.put_values(&[1, 2, 3, 4, 5, 6], None, Some(&[2, 3]))
+            .unwrap();
+        let mut g = file.add_group("g").unwrap();
+        g.add_attribute("level", 1_u8).unwrap();
+    }
+    let file = netcdf::open(&path).unwrap();
+    let expected = "netcdf cdl_header {
+dimensions:
+\ttime = UNLIMITED ; // (2 currently)
+\tx = 3 ;
+variables:
+\tfloat x(x) ;
+\tshort v(time, x) ;
+\t\tv:scale_factor = 0.5 ;
+\t\tv:valid_range = 0s, 100s ;
+
+// global attributes:
+\t\t:title = \"a \\\"quoted\\\" title\" ;
+
+group: g {
+
+  // group attributes:
+  \t\t:level = 1UB ;
+  } // group g
+}
+";
+    assert_eq!(file.to_cdl().unwrap(), expected);
+
+    let options = netcdf::cdl::Options {
+        data: netcdf::cdl::Data::Variables(vec!["x".into(), "v".into()]),
+        ..Default::default()
+    };
+    let cdl = file.to_cdl_with(&options).unwrap();
+    assert!(cdl.contains("data:\n\n x = 0.5, 1, 1.5 ;\n\n v =\n  1, 2, 3,\n  4, 5, 6 ;\n"));
+}
+
+#[test]
+fn cdl_create() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("cdl_create.nc");
+    let cdl = r#"netcdf cdl_create {
+dimensions:
+	time = UNLIMITED ; // (2 currently)
+	x = 3 ;
+	strlen = 8 ;
+variables:
+	double x(x) ;
+		x:units = "m" ;
+	short v(time, x) ;
+		v:_FillValue = -1s ;
+		v:scale_factor = 0.5 ;
+	char name(x, strlen) ;
+	string label ;
+
+// global attributes:
+		:title = "from CDL" ;
+		string :keywords = "a", "b" ;
+data:
+
+ x = 0.5, 1, 1.5 ;
+
+ v =
+  1, 2, 3,
+  4, _, 6 ;
+
+ name = "one", "two", "three" ;
+
+ label = "scalar" ;
+
+group: g {
+  variables:
+  	ubyte flags(x) ;
+  data:
+
+   flags = 1, 2, 255 ;
+  } // group g
+}
+"#;
+    netcdf::cdl::create(&path, cdl).unwrap();
+
+    let file = netcdf::open(&path).unwrap();
+    assert_eq!(
+        file.attribute("title").unwrap().value().unwrap(),
+        "from CDL".into()
+    );
+    assert_eq!(
+        file.attribute("keywords").unwrap().value().unwrap(),
+        vec!["a", "b"].into()
+    );
+    let mut values = [0.0; 3];
+    file.variable("x")
+        .unwrap()
+        .values_to(&mut values, None, None)
+        .unwrap();
+    assert_eq!(values, [0.5, 1.0, 1.5]);
+    let v = file.variable("v").unwrap();
+    let mut values = [0_i16; 6];
+    v.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1, 2, 3, 4, -1, 6]);
+    assert_eq!(
+        v.attribute("scale_factor").unwrap().value().unwrap(),
+        0.5_f64.into()
+    );
+    assert_eq!(
+        file.variable("name")
+            .unwrap()
+            .text_values(None, None)
+            .unwrap(),
+        vec!["one", "two", "three"]
+    );
+    assert_eq!(
+        file.variable("label").unwrap().string_value(None).unwrap(),
+        "scalar"
+    );
+    let g = file.group("g").unwrap().unwrap();
+    let mut flags = [0_u8; 3];
+    g.variable("flags")
+        .unwrap()
+        .values_to(&mut flags, None, None)
+        .unwrap();
+    assert_eq!(flags, [1, 2, 255]);
+
+    // The CDL written back describes the same dataset
+    let options = netcdf::cdl::Options {
+        data: netcdf::cdl::Data::All,
+        ..Default::default()
+    };
+    let written = file.to_cdl_with(&options).unwrap();
+    assert_eq!(
+        netcdf::cdl::parse(&written).unwrap(),
+        netcdf::cdl::parse(cdl).unwrap()
+    );
+}
+
+#[test]
+#[cfg(feature = "ncml")]
+fn ncml_aggregation() {
+    let d = tempfile::tempdir().unwrap();
+    for (i, name) in ["part0.nc", "part1.nc"].iter().enumerate() {
+        let mut file = netcdf::create(d.path().join(name)).unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_dimension("x", 2).unwrap();
+        file.add_attribute("title", "part").unwrap();
+        let mut var = file.add_variable::<i32>("v", &["time", "x"]).unwrap();
+        var.add_attribute("units", "m").unwrap();
+        let values = (0..4).map(|x| x + 4 * i as i32).collect::<Vec<_>>();
+        var.put_values(&values, Some(&[0, 0]), Some(&[2, 2]))
+            .unwrap();
+        let mut var = file.add_variable::<f32>("x", &["x"]).unwrap();
+        var.put_values(&[0.5, 1.5], None, None).unwrap();
+    }
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<netcdf xmlns="http://www.unidata.ucar.edu/namespaces/netcdf/ncml-2.2">
+  <dimension name="t" orgName="time"/>
+  <attribute name="title" value="aggregated"/>
+  <attribute name="levels" type="int" value="1 2 3"/>
+  <variable name="values" orgName="v">
+    <attribute name="units" value="km"/>
+  </variable>
+  <remove type="variable" name="x"/>
+  <aggregation type="joinExisting" dimName="time">
+    <netcdf location="part0.nc"/>
+    <scan location="." suffix="1.nc"/>
+  </aggregation>
+</netcdf>
+"#;
+    let path = d.path().join("agg.ncml");
+    std::fs::write(&path, xml).unwrap();
+    let dataset = netcdf::ncml::open(&path).unwrap();
+
+    assert_eq!(dataset.dimension("t").unwrap().len(), 4);
+    assert!(dataset.dimension("t").unwrap().is_unlimited());
+    assert!(dataset.dimension("time").is_none());
+    assert!(dataset.variable("x").is_none());
+    assert!(dataset.variable("v").is_none());
+    assert_eq!(
+        dataset.attribute("title").unwrap().value().unwrap(),
+        netcdf::AttrValue::Str("aggregated".into())
+    );
+    assert_eq!(
+        dataset.attribute("levels").unwrap().value().unwrap(),
+        netcdf::AttrValue::Ints(vec![1, 2, 3])
+    );
+
+    let var = dataset.variable("values").unwrap();
+    assert_eq!(var.dimensions()[0].name(), "t");
+    assert_eq!(var.len(), 8);
+    assert_eq!(
+        var.attribute("units").unwrap().value().unwrap(),
+        netcdf::AttrValue::Str("km".into())
+    );
+    let mut values = [0; 8];
+    var.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [0, 1, 2, 3, 4, 5, 6, 7]);
+    // Reading across the boundary between the files
+    let mut values = [0; 2];
+    var.values_to(&mut values, Some(&[1, 1]), Some(&[2, 1]))
+        .unwrap();
+    assert_eq!(values, [3, 5]);
+    assert_eq!(var.value::<i32>(Some(&[3, 0])).unwrap(), 6);
+}
+
+#[test]
+fn open_multi() {
+    let d = tempfile::tempdir().unwrap();
+    let mut paths = Vec::new();
+    for day in 0..3 {
+        let path = d.path().join(format!("day{}.nc", day));
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_unlimited_dimension("time").unwrap();
+        file.add_dimension("x", 3).unwrap();
+        let mut var = file.add_variable::<f64>("time", &["time"]).unwrap();
+        var.put_values(&[day as f64, day as f64 + 0.5], None, None)
+            .unwrap();
+        let mut var = file.add_variable::<i16>("v", &["time", "x"]).unwrap();
+        let values = (0..6).map(|x| x + 6 * day).collect::<Vec<i16>>();
+        var.put_values(&values, Some(&[0, 0]), Some(&[2, 3]))
+            .unwrap();
+        let mut var = file.add_variable::<u8>("x", &["x"]).unwrap();
+        var.put_values(&[1, 2, 3], None, None).unwrap();
+        paths.push(path);
+    }
+
+    let dataset = netcdf::open_multi(&paths, "time").unwrap();
+    assert_eq!(dataset.dimension("time").unwrap().len(), 6);
+    assert_eq!(dataset.dimension("x").unwrap().len(), 3);
+    let time = dataset.variable("time").unwrap();
+    let mut values = [0.0; 6];
+    time.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [0.0, 0.5, 1.0, 1.5, 2.0, 2.5]);
+
+    let var = dataset.variable("v").unwrap();
+    assert_eq!(var.len(), 18);
+    let mut values = [0_i16; 8];
+    var.values_to(&mut values, Some(&[1, 1]), Some(&[4, 2]))
+        .unwrap();
+    assert_eq!(values, [4, 5, 7, 8, 10, 11, 13, 14]);
+    assert_eq!(var.value::<i16>(Some(&[5, 2])).unwrap(), 17);
+    assert!(var.value::<i16>(Some(&[6, 0])).is_err());
+    let mut values = [0_i16; 3];
+    assert!(var.values_to(&mut values, None, None).is_err());
+
+    let x = dataset.variable("x").unwrap();
+    let mut values = [0_u8; 3];
+    x.values_to(&mut values, None, None).unwrap();
+    assert_eq!(values, [1, 2, 3]);
+
+    // Non-aggregated dimensions must agree
+    let path = d.path().join("other.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_unlimited_dimension("time").unwrap();
+    file.add_dimension("x", 4).unwrap();
+    std::mem::drop(file);
+    assert!(netcdf::open_multi(&[&paths[0], &path], "time").is_err());
+
+    // Variable types must agree
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_unlimited_dimension("time").unwrap();
+    file.add_dimension("x", 3).unwrap();
+    file.add_variable::<i32>("v", &["time", "x"]).unwrap();
+    std::mem::drop(file);
+    assert!(netcdf::open_multi(&[&paths[0], &path], "time").is_err());
+}
+
+#[test]
+fn cdl_options() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("cdl_options.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_unlimited_dimension("time").unwrap();
+    let mut var = file.add_variable::<f64>("time", &["time"]).unwrap();
+    var.add_attribute("units", "hours since 2000-01-01 00:00:00")
+        .unwrap();
+    var.add_attribute("calendar", "gregorian").unwrap();
+    var.put_values(&[0.0, 36.0, 36.5], None, None).unwrap();
+    let mut var = file.add_variable::<i32>("days", &["time"]).unwrap();
+    var.add_attribute("units", "days since 2000-01-01").unwrap();
+    var.put_values(&[0, 1], None, Some(&[2])).unwrap();
+    file.add_dimension("one", 1).unwrap();
+    let mut var = file.add_variable::<f32>("pi", &["one"]).unwrap();
+    var.compression(4).unwrap();
+    var.put_values(&[std::f32::consts::PI], None, None).unwrap();
+    let mut g = file.add_group("a").unwrap();
+    g.add_group("b").unwrap();
+    file.add_group("c").unwrap();
+    assert_eq!(file.format().unwrap(), netcdf::convert::Format::Netcdf4);
+    assert_eq!(file.format().unwrap().to_string(), "netCDF-4");
+
+    let options = netcdf::cdl::Options {
+        data: netcdf::cdl::Data::All,
+        times: true,
+        float_digits: Some(3),
+        ..Default::default()
+    };
+    let cdl = file.to_cdl_with(&options).unwrap();
+    assert!(cdl.contains(" time = \"2000-01-01\", \"2000-01-02 12\", \"2000-01-02 12:30\" ;"));
+    assert!(cdl.contains(" days = \"2000-01-01\", \"2000-01-02\", _ ;"));
+    assert!(cdl.contains(" pi = 3.14 ;"));
+
+    let options = netcdf::cdl::Options {
+        special: true,
+        groups: vec!["a/b".into()],
+        ..Default::default()
+    };
+    let cdl = file.to_cdl_with(&options).unwrap();
+    assert!(cdl.contains("\t\t:_Format = \"netCDF-4\" ;"));
+    assert!(cdl.contains("\t\tpi:_DeflateLevel = 4 ;"));
+    assert!(cdl.contains("\t\ttime:_Storage = \"chunked\" ;"));
+    assert!(cdl.contains("group: a {"));
+    assert!(cdl.contains("group: b {"));
+    assert!(!cdl.contains("group: c {"));
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn ncdump_binary() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("ncdump_binary.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 2).unwrap();
+    let mut var = file.add_variable::<f64>("x", &["x"]).unwrap();
+    var.put_values(&[0.123_456, 1.0], None, None).unwrap();
+    file.add_variable::<i32>("v", &["x"]).unwrap();
+    std::mem::drop(file);
+
+    let ncdump = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_ncdump"))
+            .args(args)
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(ncdump(&["-k"]), "netCDF-4\n");
+    assert!(ncdump(&["-K"]).starts_with("hdf5 mode="));
+    assert!(!ncdump(&["-h"]).contains("data:"));
+    let cdl = ncdump(&["-c", "-p", "7,3"]);
+    assert!(cdl.contains(" x = 0.123, 1 ;"));
+    assert!(!cdl.contains(" v ="));
+    assert!(ncdump(&["-v", "v"]).contains(" v = _, _ ;"));
+    assert!(ncdump(&["-s"]).contains(":_Format = \"netCDF-4\" ;"));
+}
+
+#[test]
+fn compare_files() {
+    use netcdf::compare::{compare, Difference, Options, Side, Value};
+
+    let d = tempfile::tempdir().unwrap();
+    let create = |name: &str, values: &[f32], units: &str, extra: bool| {
+        let path = d.path().join(name);
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_dimension("x", 4).unwrap();
+        file.add_attribute("history", name).unwrap();
+        if extra {
+            file.add_string_attribute("title", &["t"]).unwrap();
+        } else {
+            file.add_attribute("title", "t").unwrap();
+        }
+        let mut var = file.add_variable::<f32>("v", &["x"]).unwrap();
+        var.set_fill_value(-1.0_f32).unwrap();
+        var.add_attribute("units", units).unwrap();
+        var.put_values(values, None, None).unwrap();
+        if extra {
+            file.add_group("g").unwrap();
+        }
+        path
+    };
+    let a = create("a.nc", &[1.0, 2.0, 3.0, -1.0], "m", false);
+    let b = create("b.nc", &[1.0, 2.01, 3.5, 4.0], "km", true);
+    let (a, b) = (netcdf::open(&a).unwrap(), netcdf::open(&b).unwrap());
+
+    let options = Options {
+        absolute_tolerance: 0.1,
+        exclude_attributes: vec!["history".into()],
+        ..Default::default()
+    };
+    let differences = compare(&a, &b, &options).unwrap();
+    assert_eq!(
+        differences,
+        vec![
+            Difference::AttributeType {
+                path: "/:title".into(),
+                first: netcdf_sys::NC_CHAR,
+                second: netcdf_sys::NC_STRING,
+            },
+            Difference::AttributeValue {
+                path: "/v:units".into(),
+                first: "m".into(),
+                second: "km".into(),
+            },
+            Difference::Value {
+                path: "/v".into(),
+                index: vec![2],
+                first: Value::Number(3.0),
+                second: Value::Number(3.5),
+            },
+            Difference::Value {
+                path: "/v".into(),
+                index: vec![3],
+                first: Value::Fill,
+                second: Value::Number(4.0),
+            },
+            Difference::Missing {
+                path: "/g".into(),
+                only_in: Side::Second,
+            },
+        ]
+    );
+    assert_eq!(differences[0].to_string(), "/:title: type char != string");
+    assert_eq!(differences[2].to_string(), "/v[2]: 3 != 3.5");
+
+    let options = Options {
+        data: false,
+        ..Default::default()
+    };
+    let differences = compare(&a, &b, &options).unwrap();
+    assert_eq!(differences.len(), 4);
+    assert_eq!(differences[0].path(), "/:history");
+    assert!(compare(&a, &a, &Options::default()).unwrap().is_empty());
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn nccmp_binary() {
+    let d = tempfile::tempdir().unwrap();
+    let create = |name: &str, units: &str| {
+        let path = d.path().join(name);
+        let mut file = netcdf::create(&path).unwrap();
+        file.add_attribute("units", units).unwrap();
+        path
+    };
+    let (a, b) = (create("a.nc", "m"), create("b.nc", "km"));
+    let nccmp = |args: &[&str], second: &std::path::Path| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_nccmp"))
+            .args(args)
+            .arg(&a)
+            .arg(second)
+            .output()
+            .unwrap()
+    };
+    assert_eq!(nccmp(&[], &a).status.code(), Some(0));
+    let output = nccmp(&["--json"], &b);
+    assert_eq!(output.status.code(), Some(1));
+    let json = String::from_utf8(output.stdout).unwrap();
+    assert!(json.starts_with('['));
+    assert!(json.contains("\"AttributeValue\""));
+    assert!(json.contains("\"path\": \"/:units\""));
+}
+
+#[test]
+fn schema_validation() {
+    use netcdf::schema::{Attribute, Dimension, Schema, Type, Variable, Violation};
+
+    let schema = Schema {
+        dimensions: vec![Dimension::unlimited("time"), Dimension::new("x", 3)],
+        variables: vec![
+            Variable {
+                attributes: vec![Attribute::allowing("units", &["K", "degC"])],
+                ..Variable::new("t", Type::Float, &["time", "x"])
+            },
+            Variable {
+                optional: true,
+                ..Variable::new("q", Type::Double, &["x"])
+            },
+        ],
+        attributes: vec![Attribute {
+            attrtype: Some(Type::Int),
+            ..Attribute::new("version")
+        }],
+        groups: vec![Schema {
+            name: "meta".into(),
+            attributes: vec![Attribute::new("source")],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let d = tempfile::tempdir().unwrap();
+    let file = schema.create(d.path().join("conforming.nc")).unwrap();
+    assert!(schema.validate(&file).unwrap().is_empty());
+    assert_eq!(
+        file.variable("t")
+            .unwrap()
+            .attribute("units")
+            .unwrap()
+            .value()
+            .unwrap(),
+        netcdf::AttrValue::Str("K".into())
+    );
+
+    let mut file = netcdf::create(d.path().join("violating.nc")).unwrap();
+    file.add_dimension("time", 2).unwrap();
+    file.add_dimension("x", 4).unwrap();
+    file.add_attribute("version", 1.5).unwrap();
+    let mut var = file.add_variable::<f64>("t", &["x"]).unwrap();
+    var.add_attribute("units", "F").unwrap();
+    let violations = schema.validate(&file).unwrap();
+    assert_eq!(
+        violations,
+        vec![
+            Violation::NotUnlimited {
+                path: "/time".into()
+            },
+            Violation::DimensionLength {
+                path: "/x".into(),
+                expected: 3,
+                found: 4
+            },
+            Violation::Type {
+                path: "/:version".into(),
+                expected: Type::Int,
+                found: netcdf_sys::NC_DOUBLE
+            },
+            Violation::Type {
+                path: "/t".into(),
+                expected: Type::Float,
+                found: netcdf_sys::NC_DOUBLE
+            },
+            Violation::Dimensions {
+                path: "/t".into(),
+                expected: vec!["time".into(), "x".into()],
+                found: vec!["x".into()]
+            },
+            Violation::Value {
+                path: "/t:units".into(),
+                found: netcdf::AttrValue::Str("F".into())
+            },
+            Violation::Missing {
+                path: "/meta".into()
+            },
+        ]
+    );
+    assert_eq!(violations[1].to_string(), "/x: length 4 instead of 3");
+
+    // A single string attribute matches the same text
+    let schema = Schema {
+        attributes: vec![Attribute {
+            attrtype: Some(Type::String),
+            ..Attribute::allowing("source", &["model"])
+        }],
+        ..Default::default()
+    };
+    let file = schema.create(d.path().join("strings.nc")).unwrap();
+    assert!(schema.validate(&file).unwrap().is_empty());
+}
+
+#[test]
+fn coordinate_lookups() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("coordinate_lookups.nc");
+    let mut file = netcdf::create(path).unwrap();
+    file.add_dimension("time", 2).unwrap();
+    file.add_dimension("x", 3).unwrap();
+    file.add_variable::<f64>("time", &["time"]).unwrap();
+    file.add_variable::<f64>("lon", &["x"]).unwrap();
+    let mut group = file.add_group("surface").unwrap();
+    group.add_variable::<f64>("lat", &["x"]).unwrap();
+    let mut var = group.add_variable::<f32>("t2m", &["time", "x"]).unwrap();
+    var.add_attribute("coordinates", "lat lon missing /time")
+        .unwrap();
+    group.add_variable::<f32>("height", &[]).unwrap();
+
+    let time = file.dimension("time").unwrap();
+    assert_eq!(time.coordinate_variable().unwrap().name(), "time");
+    let names = time.variables().map(|v| v.name()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["time", "t2m"]);
+
+    let x = file.dimension("x").unwrap();
+    assert!(x.coordinate_variable().is_none());
+    assert_eq!(x.variables().count(), 3);
+    assert!(x.try_coordinate_variable().unwrap().is_none());
+    assert_eq!(x.try_variables().unwrap().len(), 3);
+
+    let group = file.group("surface").unwrap().unwrap();
+    let names = group
+        .variables_with_dimension("x")
+        .map(|v| v.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["lat", "t2m"]);
+    assert_eq!(group.variables_with_dimension("level").count(), 0);
+    let vars = group.try_variables_with_dimension("time").unwrap();
+    assert_eq!(vars.len(), 1);
+
+    let var = group.variable("t2m").unwrap();
+    let names = var
+        .coordinate_variables()
+        .map(|v| v.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["time"]);
+    let names = var
+        .auxiliary_coordinates()
+        .map(|v| v.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["lat", "lon", "time"]);
+}
+
+#[test]
+fn file_provenance() {
+    use netcdf::convert::{ExtendedFormat, Format};
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("provenance.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 2).unwrap();
+    std::mem::drop(file);
+
+    let file = netcdf::open(&path).unwrap();
+    assert_eq!(file.format().unwrap(), Format::Netcdf4);
+    let (format, _mode) = file.format_extended().unwrap();
+    assert_eq!(format, ExtendedFormat::Hdf5);
+    assert_eq!(format.to_string(), "hdf5");
+    let provenance = file.provenance().unwrap();
+    assert!(provenance.properties.unwrap().contains("netcdf="));
+    assert_eq!(provenance.is_netcdf4, Some(true));
+    assert!(provenance.superblock_version.is_some());
+    // Hidden from the normal attributes
+    assert_eq!(file.attributes().count(), 0);
+
+    let classic_path = d.path().join("provenance_classic.nc");
+    let options = netcdf::convert::Options {
+        format: Format::Classic,
+        ..Default::default()
+    };
+    netcdf::convert::convert(&file, &classic_path, &options).unwrap();
+    let classic = netcdf::open(&classic_path).unwrap();
+    assert_eq!(classic.format().unwrap(), Format::Classic);
+    assert_eq!(classic.format_extended().unwrap().0, ExtendedFormat::Nc3);
+    assert_eq!(classic.provenance().unwrap(), netcdf::Provenance::default());
+}
+
+#[test]
+fn library_info() {
+    use netcdf::library::{filters, Version};
+
+    let library = netcdf::library();
+    assert!(library.version >= Version::new(4, 0, 0));
+    assert!(library
+        .version_string
+        .starts_with(&library.version.to_string()));
+    // All other tests use netCDF-4 files
+    assert!(library.features.netcdf4);
+    assert!(library.has_filter(filters::DEFLATE, &[4]));
+    #[cfg(not(feature = "mpi"))]
+    assert!(!library.features.parallel);
+
+    let err = library
+        .require(Version::new(99, 0, 0), "time travel")
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        netcdf::error::Error::Unsupported { code: None, .. }
+    ));
+    assert!(library.require(library.version, "this test").is_ok());
+}
+
+#[test]
+fn error_kinds_and_context() {
+    use netcdf::error::{Error, ErrorKind};
+
+    assert_eq!(
+        ErrorKind::from_code(netcdf_sys::NC_ENOTVAR),
+        ErrorKind::NotVar
+    );
+    assert_eq!(ErrorKind::NotVar.code(), netcdf_sys::NC_ENOTVAR);
+    assert_eq!(ErrorKind::from_code(-9999), ErrorKind::Unknown(-9999));
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("error_context.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    let mut group = file.add_group("g").unwrap();
+    group.add_dimension("x", 2).unwrap();
+    let var = group.add_char_variable("c", &["x"]).unwrap();
+
+    let err = var.value::<f32>(Some(&[1])).unwrap_err();
+    assert_eq!(err.kind(), Some(ErrorKind::Char));
+    let context = err.context().unwrap();
+    assert_eq!(context.path.as_deref(), Some(path.as_path()));
+    assert_eq!(context.group.as_deref(), Some("/g"));
+    assert_eq!(context.variable.as_deref(), Some("c"));
+    assert_eq!(context.start.as_deref(), Some(&[1][..]));
+    assert_eq!(context.count.as_deref(), Some(&[1][..]));
+    assert!(err.to_string().contains("variable c"));
+
+    // Errors of this crate are not wrapped
+    let err = var.value::<f32>(Some(&[1, 2])).unwrap_err();
+    assert!(matches!(err, Error::IndexLen));
+    assert!(err.kind().is_none());
+}
+
+#[test]
+fn fallible_metadata() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("fallible_metadata.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_unlimited_dimension("time").unwrap();
+    let mut var = file.add_variable::<i32>("value", &["time"]).unwrap();
+    var.add_attribute("units", "m").unwrap();
+    var.put_values(&[1, 2, 3], Some(&[0]), Some(&[3])).unwrap();
+
+    let dim = file.dimension("time").unwrap();
+    assert_eq!(dim.try_name().unwrap(), "time");
+    assert_eq!(dim.name_bytes().unwrap(), b"time");
+    assert_eq!(dim.try_len().unwrap(), 3);
+
+    let var = file.variable("value").unwrap();
+    assert_eq!(var.try_name().unwrap(), "value");
+    assert_eq!(var.name_bytes().unwrap(), b"value");
+    let attr = var.attribute("units").unwrap();
+    assert_eq!(attr.try_name().unwrap(), "units");
+    assert_eq!(attr.name_bytes(), b"units");
+
+    let root = file.try_root().unwrap().unwrap();
+    assert_eq!(root.try_name().unwrap(), "/");
+    assert_eq!(root.try_full_name().unwrap(), "/");
+}
+
+#[test]
+fn conversion_modes() {
+    use netcdf::error::Error;
+    use netcdf::ConversionMode;
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("conversion_modes.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 3).unwrap();
+    let mut var = file.add_variable::<i16>("short", &["x"]).unwrap();
+    var.set_fill_value(-1_i16).unwrap();
+    let mut var = file.add_variable::<f64>("double", &["x"]).unwrap();
+    var.put_values(&[1.0, 1e10, -1e10], None, None).unwrap();
+
+    let mut var = file.variable_mut("short").unwrap();
+    assert_eq!(var.conversion(), ConversionMode::Converting);
+    let err = var
+        .put_values(&[1_i32, 70_000, -70_000], None, None)
+        .unwrap_err();
+    assert_eq!(err.kind(), Some(netcdf::error::ErrorKind::Range));
+    assert_eq!(
+        err.to_string().split(' ').take(3).collect::<Vec<_>>(),
+        ["2", "of", "3"]
+    );
+    // Nothing was written
+    assert_eq!(var.value::<i16>(Some(&[0])).unwrap(), -1);
+    var.put_values(&[1_i32, 2, 3], None, None).unwrap();
+    assert_eq!(var.fill_value::<f64>().unwrap(), Some(-1.0));
+
+    let var = var.with_conversion(ConversionMode::Exact);
+    assert!(matches!(var.value::<f64>(None), Err(Error::TypeMismatch)));
+    assert!(matches!(var.fill_value::<f64>(), Err(Error::TypeMismatch)));
+    assert_eq!(var.value::<i16>(Some(&[2])).unwrap(), 3);
+
+    let var = file.variable("double").unwrap();
+    let mut buffer = [0_i32; 3];
+    let err = var.values_to(&mut buffer, None, None).unwrap_err();
+    assert_eq!(err.kind(), Some(netcdf::error::ErrorKind::Range));
+    assert_eq!(err.context().unwrap().variable.as_deref(), Some("double"));
+    assert_eq!(
+        err.to_string().split(' ').take(3).collect::<Vec<_>>(),
+        ["2", "of", "3"]
+    );
+
+    // Single values are checked as slices are, bytes may be stored as unsigned
+    let mut var = file.add_variable::<i8>("byte", &["x"]).unwrap();
+    var.put_value(200_u8, Some(&[0])).unwrap();
+    var.put_values(&[200_u8], Some(&[1]), Some(&[1])).unwrap();
+    assert_eq!(var.value::<u8>(Some(&[0])).unwrap(), 200);
+    assert_eq!(var.value::<u8>(Some(&[1])).unwrap(), 200);
+    let err = var.put_value(300_i32, Some(&[2])).unwrap_err();
+    assert_eq!(err.kind(), Some(netcdf::error::ErrorKind::Range));
+
+    // The limits of 64-bit integers are not rounded
+    let mut var = file.add_variable::<i64>("long", &["x"]).unwrap();
+    var.put_value(i64::MAX as u64, Some(&[0])).unwrap();
+    let err = var.put_value(i64::MAX as u64 + 1, Some(&[1])).unwrap_err();
+    assert_eq!(err.kind(), Some(netcdf::error::ErrorKind::Range));
+    let err = var.put_value(2_f64.powi(63), Some(&[1])).unwrap_err();
+    assert_eq!(err.kind(), Some(netcdf::error::ErrorKind::Range));
+    assert_eq!(var.value::<u64>(Some(&[0])).unwrap(), i64::MAX as u64);
+}
+
+#[test]
+#[cfg(feature = "ndarray")]
+fn masked_values() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("masked_values.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 5).unwrap();
+    let mut var = file.add_variable::<f32>("temperature", &["x"]).unwrap();
+    var.add_attribute("missing_value", -999.0_f32).unwrap();
+    var.add_attribute("valid_range", vec![0.0_f32, 100.0])
+        .unwrap();
+    // The last value is left as the default fill value
+    var.put_values(&[1.0_f32, -999.0, 150.0, 50.0], Some(&[0]), Some(&[4]))
+        .unwrap();
+
+    let var = file.variable("temperature").unwrap();
+    let mask = var.mask().unwrap();
+    assert_eq!(mask.fill_value, Some(f64::from(netcdf_sys::NC_FILL_FLOAT)));
+    assert_eq!(mask.missing_values, [-999.0]);
+    assert_eq!((mask.valid_min, mask.valid_max), (Some(0.0), Some(100.0)));
+
+    let values = var.masked_values::<f32>(None, None).unwrap();
+    assert_eq!(
+        values.iter().copied().collect::<Vec<_>>(),
+        [Some(1.0), None, None, Some(50.0), None]
+    );
+    let (values, masked) = var.values_with_mask::<f64>(None, None).unwrap();
+    assert_eq!(values[[3]], 50.0);
+    assert_eq!(
+        masked.iter().copied().collect::<Vec<_>>(),
+        [false, true, true, false, true]
+    );
+}
+
+#[test]
+fn bool_values() {
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("bool_values.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 4).unwrap();
+    let mut var = file.add_variable::<bool>("flags", &["x"]).unwrap();
+    assert_eq!(var.vartype(), netcdf_sys::NC_BYTE);
+    var.put_values(&[true, false, false, true], None, None)
+        .unwrap();
+    var.put_value(true, Some(&[1])).unwrap();
+
+    let var = file.variable("flags").unwrap();
+    let mut bytes = [0_i8; 4];
+    var.values_to(&mut bytes, None, None).unwrap();
+    assert_eq!(bytes, [1, 1, 0, 1]);
+    let mut flags = [false; 4];
+    var.values_to(&mut flags, None, None).unwrap();
+    assert_eq!(flags, [true, true, false, true]);
+    assert!(!var.value::<bool>(Some(&[2])).unwrap());
+    // The default fill value of bytes is nonzero
+    assert_eq!(var.fill_value::<bool>().unwrap(), Some(true));
+
+    let mut var = file.add_variable::<bool>("unset", &["x"]).unwrap();
+    var.set_fill_value(false).unwrap();
+    assert_eq!(var.fill_value::<bool>().unwrap(), Some(false));
+    assert!(!var.value::<bool>(Some(&[0])).unwrap());
+}
+
+#[test]
+#[cfg(feature = "half")]
+fn f16_values() {
+    use half::f16;
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("f16_values.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 3).unwrap();
+    let values = [f16::from_f32(1.5), f16::from_f32(-0.25), f16::MAX];
+    let mut var = file.add_variable::<f16>("half", &["x"]).unwrap();
+    assert!(var.vartype() >= netcdf_sys::NC_FIRSTUSERTYPEID);
+    var.put_values(&values, None, None).unwrap();
+    // The opaque type is shared by variables in the group
+    let vartype = var.vartype();
+    let var = file.add_variable::<f16>("other", &["x"]).unwrap();
+    assert_eq!(var.vartype(), vartype);
+    let mut var = file.add_variable::<f32>("float", &["x"]).unwrap();
+    var.put_values(&values, None, None).unwrap();
+
+    for name in &["half", "float"] {
+        let var = file.variable(name).unwrap();
+        let mut buffer = [f16::ZERO; 3];
+        var.values_to(&mut buffer, None, None).unwrap();
+        assert_eq!(buffer, values);
+    }
+    let var = file.variable("float").unwrap();
+    assert_eq!(var.value::<f32>(Some(&[0])).unwrap(), 1.5);
+
+    // Other numeric types are converted
+    let mut var = file.add_variable::<i16>("short", &["x"]).unwrap();
+    var.put_values(&[1_i16, -3], None, Some(&[2])).unwrap();
+    let mut buffer = [f16::ZERO; 2];
+    var.values_to(&mut buffer, None, Some(&[2])).unwrap();
+    assert_eq!(buffer, [f16::ONE, f16::from_f32(-3.0)]);
+    #[cfg(feature = "ndarray")]
+    {
+        let values = var.masked_values::<f16>(None, None).unwrap();
+        assert_eq!(values[[1]], Some(f16::from_f32(-3.0)));
+        assert_eq!(values[[2]], None);
+    }
+}
+
+#[test]
+#[cfg(feature = "num-complex")]
+fn complex_values() {
+    use netcdf::ConversionMode;
+    use num_complex::Complex;
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("complex_values.nc");
+    let mut file = netcdf::create(&path).unwrap();
+    file.add_dimension("x", 2).unwrap();
+    file.add_dimension("ri", 2).unwrap();
+    let values = [Complex::new(1.0, -1.0), Complex::new(0.5, 2.0)];
+
+    // Compound type
+    let mut var = file
+        .add_variable::<Complex<f64>>("compound", &["x"])
+        .unwrap();
+    var.put_values(&values, None, None).unwrap();
+    // Trailing dimension
+    let mut var = file.add_variable::<f64>("dimension", &["x", "ri"]).unwrap();
+    var.put_values(&[1.0, -1.0, 0.5, 2.0], None, None).unwrap();
+
+    for name in &["compound", "dimension"] {
+        let var = file
+            .variable(name)
+            .unwrap()
+            .with_conversion(ConversionMode::Exact);
+        let mut buffer = [Complex::new(0.0, 0.0); 2];
+        var.values_to(&mut buffer, None, None).unwrap();
+        assert_eq!(buffer, values);
+        assert_eq!(var.value::<Complex<f64>>(Some(&[1])).unwrap(), values[1]);
+        #[cfg(feature = "ndarray")]
+        assert_eq!(var.values::<Complex<f64>>(None, None).unwrap().shape(), [2]);
+    }
+
+    let mut var = file.variable_mut("dimension").unwrap();
+    var.put_value(Complex::new(3.0_f32, 4.0), Some(&[0]))
+        .unwrap();
+    let var = file.variable("dimension").unwrap();
+    assert_eq!(var.value::<f64>(Some(&[0, 1])).unwrap(), 4.0);
+
+    // Only dimensions named as in nc-complex hold the parts
+    file.add_dimension("y", 2).unwrap();
+    file.add_variable::<f64>("pairs", &["x", "y"]).unwrap();
+    let var = file.variable("pairs").unwrap();
+    assert!(var.value::<Complex<f64>>(Some(&[0])).is_err());
+}
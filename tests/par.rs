@@ -0,0 +1,36 @@
+#![cfg(feature = "mpi")]
+use netcdf::variable::ParallelAccess;
+
+#[test]
+fn single_rank() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+
+    let d = tempfile::tempdir().unwrap();
+    let path = d.path().join("single_rank.nc");
+    {
+        let mut file = netcdf::create_par(&path, &world).unwrap();
+        file.add_dimension("x", 4).unwrap();
+        let mut var = file.add_variable::<i32>("data", &["x"]).unwrap();
+        var.par_access(ParallelAccess::Collective).unwrap();
+        var.put_values(&[1, 2, 3, 4], None, None).unwrap();
+    }
+    {
+        let mut file = netcdf::append_par(&path, &world).unwrap();
+        let mut var = file.variable_mut("data").unwrap();
+        var.par_access(ParallelAccess::Independent).unwrap();
+        var.put_values(&[5], Some(&[3]), Some(&[1])).unwrap();
+    }
+
+    let file = netcdf::open_par(&path, &world).unwrap();
+    let var = file.variable("data").unwrap();
+    var.par_access(ParallelAccess::Independent).unwrap();
+    let mut data = [0; 4];
+    var.values_to(&mut data, None, None).unwrap();
+    assert_eq!(data, [1, 2, 3, 5]);
+    drop(file);
+
+    let file = netcdf::create_par_with(&path, &world, netcdf_sys::NC_NETCDF4).unwrap();
+    drop(file);
+    assert!(netcdf::open(&path).unwrap().variable("data").is_none());
+}